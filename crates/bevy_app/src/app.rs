@@ -1,5 +1,9 @@
 use crate::app_builder::AppBuilder;
-use bevy_ecs::{ParallelExecutor, Resources, Schedule, World};
+use bevy_ecs::{
+    AsyncComputeTaskPool, ComponentHashRegistry, ComputeTaskPool, IoTaskPool, ParallelExecutor,
+    ParallelExecutorOptions, Resource, Resources, Schedule, TaskPoolOptions, World,
+};
+use std::any::TypeId;
 
 /// Containers of app logic and data
 ///
@@ -30,6 +34,7 @@ pub struct App {
     pub executor: ParallelExecutor,
     pub startup_schedule: Schedule,
     pub startup_executor: ParallelExecutor,
+    pub sub_apps: Vec<SubApp>,
 }
 
 impl Default for App {
@@ -42,6 +47,7 @@ impl Default for App {
             startup_schedule: Default::default(),
             startup_executor: ParallelExecutor::without_tracker_clears(),
             runner: Box::new(run_once),
+            sub_apps: Vec::new(),
         }
     }
 }
@@ -56,12 +62,104 @@ impl App {
     }
 
     pub fn update(&mut self) {
+        self.insert_task_pools();
         self.schedule.initialize(&mut self.resources);
         self.executor
             .run(&mut self.schedule, &mut self.world, &mut self.resources);
+        self.update_world_hash();
+
+        for sub_app in self.sub_apps.iter_mut() {
+            (sub_app.extract)(&mut self.world, &mut self.resources, &mut sub_app.app);
+            sub_app.app.update();
+        }
+    }
+
+    /// Makes every executor that runs with this app's resources, and those of its sub-apps, run
+    /// deterministically. See [AppBuilder::set_deterministic]
+    pub(crate) fn set_deterministic(&mut self, deterministic: bool) {
+        let options = self
+            .resources
+            .get::<ParallelExecutorOptions>()
+            .map(|options| (*options).clone())
+            .unwrap_or_default();
+        self.resources
+            .insert(options.with_deterministic(deterministic));
+        for sub_app in self.sub_apps.iter_mut() {
+            sub_app.app.set_deterministic(deterministic);
+        }
+    }
+
+    pub(crate) fn is_deterministic(&self) -> bool {
+        self.resources
+            .get::<ParallelExecutorOptions>()
+            .map_or(false, |options| options.is_deterministic())
+    }
+
+    /// The sub-app added with `label`, see [AppBuilder::add_sub_app]
+    pub fn sub_app(&self, label: &'static str) -> Option<&App> {
+        self.sub_apps
+            .iter()
+            .find(|sub_app| sub_app.label == label)
+            .map(|sub_app| &sub_app.app)
+    }
+
+    /// The sub-app added with `label`, see [AppBuilder::add_sub_app]
+    pub fn sub_app_mut(&mut self, label: &'static str) -> Option<&mut App> {
+        self.sub_apps
+            .iter_mut()
+            .find(|sub_app| sub_app.label == label)
+            .map(|sub_app| &mut sub_app.app)
+    }
+
+    fn update_world_hash(&mut self) {
+        let hash = match self.resources.get::<ComponentHashRegistry>() {
+            Some(registry) => registry.hash_world(&self.world),
+            None => return,
+        };
+        if let Some(mut world_hash) = self.resources.get_mut::<WorldHash>() {
+            world_hash.frame += 1;
+            world_hash.hash = hash;
+            return;
+        }
+        self.resources.insert(WorldHash { frame: 0, hash });
     }
 
     pub fn run(mut self) {
+        self.insert_task_pools();
+        self.startup();
+
+        if self.resources.contains::<ReportExecutionOrderAmbiguities>() {
+            self.report_execution_order_ambiguities();
+        }
+
+        let runner = std::mem::replace(&mut self.runner, Box::new(run_once));
+        (runner)(self);
+    }
+
+    /// Inserts the task pools that are missing, configured by the [TaskPoolOptions] resource, and
+    /// shares them with the sub-apps
+    pub(crate) fn insert_task_pools(&mut self) {
+        if !self.resources.contains::<ComputeTaskPool>()
+            || !self.resources.contains::<AsyncComputeTaskPool>()
+            || !self.resources.contains::<IoTaskPool>()
+        {
+            self.resources
+                .get::<TaskPoolOptions>()
+                .map(|options| (*options).clone())
+                .unwrap_or_default()
+                .create_default_pools(&mut self.resources);
+        }
+
+        for sub_app in self.sub_apps.iter_mut() {
+            let sub_resources = &mut sub_app.app.resources;
+            share_resource::<ComputeTaskPool>(&self.resources, sub_resources);
+            share_resource::<AsyncComputeTaskPool>(&self.resources, sub_resources);
+            share_resource::<IoTaskPool>(&self.resources, sub_resources);
+        }
+    }
+
+    /// Runs the startup systems of this app, then those of its sub-apps, which share its task pools
+    fn startup(&mut self) {
         self.startup_schedule.initialize(&mut self.resources);
         self.startup_executor.run(
             &mut self.startup_schedule,
@@ -69,10 +167,219 @@ impl App {
             &mut self.resources,
         );
 
-        let runner = std::mem::replace(&mut self.runner, Box::new(run_once));
-        (runner)(self);
+        for sub_app in self.sub_apps.iter_mut() {
+            sub_app.app.insert_task_pools();
+            sub_app.app.startup();
+        }
+    }
+
+    fn report_execution_order_ambiguities(&self) {
+        let ambiguities = self
+            .startup_schedule
+            .ambiguities(&self.world)
+            .into_iter()
+            .chain(self.schedule.ambiguities(&self.world));
+        for ambiguity in ambiguities {
+            let names = |types: &[(TypeId, &'static str)]| {
+                types
+                    .iter()
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            log::warn!(
+                "execution order of {} and {} in stage {} is ambiguous: they conflict on components [{}] in {} archetypes and resources [{}]",
+                ambiguity.systems[0],
+                ambiguity.systems[1],
+                ambiguity.stage,
+                names(&ambiguity.components),
+                ambiguity.archetypes.len(),
+                names(&ambiguity.resources),
+            );
+        }
     }
 }
 
+fn share_resource<T: Resource + Clone>(from: &Resources, to: &mut Resources) {
+    if to.contains::<T>() {
+        return;
+    }
+    if let Some(resource) = from.get::<T>() {
+        let resource = (*resource).clone();
+        to.insert(resource);
+    }
+}
+
+/// An [App] with its own world, resources and schedule, owned by another App
+///
+/// Each time the owning App updates, it passes its world and resources along with the sub-app to
+/// the sub-app's extract function, which copies the data the sub-app needs across. The sub-app is
+/// then updated. Sub-apps are added with [AppBuilder::add_sub_app].
+pub struct SubApp {
+    pub label: &'static str,
+    pub app: App,
+    pub extract: Box<dyn Fn(&mut World, &mut Resources, &mut App)>,
+}
+
+/// When this resource is present, the App logs the systems whose execution order is ambiguous after
+/// the startup systems have run. See [Schedule::ambiguities]
+pub struct ReportExecutionOrderAmbiguities;
+
+/// The hash of the components registered with
+/// [AppBuilder::register_hashed_component](crate::AppBuilder::register_hashed_component), taken
+/// after each update
+///
+/// Apps that run deterministically produce the same hashes every time they are given the same
+/// inputs, so comparing hashes between runs or peers detects desyncs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WorldHash {
+    /// The number of updates before the one this hash was taken after
+    pub frame: u64,
+    pub hash: u64,
+}
+
 /// An event that indicates the app should exit. This will fully exit the app process.
 pub struct AppExit;
+
+#[cfg(test)]
+mod tests {
+    use super::{App, WorldHash};
+    use bevy_ecs::{
+        ComputeTaskPool, IntoQuerySystem, Mut, Query, Res, ResMut, TaskPoolOptions, World,
+    };
+    use std::thread::ThreadId;
+
+    fn increment(mut query: Query<Mut<u32>>) {
+        for mut value in &mut query.iter() {
+            *value += 1;
+        }
+    }
+
+    fn build_app(start: u32) -> App {
+        let mut world = World::new();
+        world.spawn((start,));
+        world.spawn((start + 1,));
+        let mut app_builder = App::build();
+        app_builder
+            .set_world(world)
+            .set_deterministic(true)
+            .register_hashed_component::<u32>()
+            .add_system(increment.system());
+        std::mem::take(&mut app_builder.app)
+    }
+
+    #[test]
+    fn world_hash() {
+        let mut a = build_app(0);
+        let mut b = build_app(0);
+        let mut c = build_app(1);
+        for app in [&mut a, &mut b, &mut c].iter_mut() {
+            app.update();
+            app.update();
+        }
+
+        let hash = |app: &App| *app.resources.get::<WorldHash>().unwrap();
+        assert_eq!(hash(&a).frame, 1);
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a).hash, hash(&c).hash);
+    }
+
+    #[test]
+    fn sub_app() {
+        struct Frame(u32);
+
+        fn count_frames(mut frame: ResMut<Frame>) {
+            frame.0 += 1;
+        }
+
+        fn record_frames(frame: Res<Frame>, mut frames: ResMut<Vec<u32>>) {
+            frames.push(frame.0);
+        }
+
+        let mut sub_app_builder = App::build();
+        sub_app_builder
+            .add_resource(Vec::<u32>::new())
+            .add_system(record_frames.system());
+
+        let mut app_builder = App::build();
+        app_builder
+            .add_resource(Frame(0))
+            .add_system(count_frames.system())
+            .add_sub_app(
+                "sub",
+                std::mem::take(&mut sub_app_builder.app),
+                |_world, resources, sub_app| {
+                    let frame = resources.get::<Frame>().unwrap().0;
+                    sub_app.resources.insert(Frame(frame));
+                },
+            );
+        let mut app = std::mem::take(&mut app_builder.app);
+        app.update();
+        app.update();
+
+        let sub_app = app.sub_app("sub").unwrap();
+        assert_eq!(*sub_app.resources.get::<Vec<u32>>().unwrap(), vec![1, 2]);
+        assert!(app.sub_app("missing").is_none());
+        assert_eq!(
+            sub_app
+                .resources
+                .get::<ComputeTaskPool>()
+                .unwrap()
+                .thread_num(),
+            app.resources.get::<ComputeTaskPool>().unwrap().thread_num(),
+            "sub-apps share the task pools of their app"
+        );
+    }
+
+    #[test]
+    fn deterministic_sub_apps() {
+        fn record_thread(mut threads: ResMut<Vec<ThreadId>>) {
+            threads.push(std::thread::current().id());
+        }
+
+        let build_sub_app = || {
+            let mut sub_app_builder = App::build();
+            sub_app_builder
+                .add_resource(Vec::<ThreadId>::new())
+                .add_system(record_thread.system());
+            std::mem::take(&mut sub_app_builder.app)
+        };
+
+        // sub-apps added before and after the app is made deterministic
+        let mut app_builder = App::build();
+        app_builder
+            .add_sub_app("before", build_sub_app(), |_, _, _| {})
+            .set_deterministic(true)
+            .add_sub_app("after", build_sub_app(), |_, _, _| {});
+        let mut app = std::mem::take(&mut app_builder.app);
+        app.update();
+
+        // deterministic executors run systems on the calling thread
+        for label in ["before", "after"].iter() {
+            let sub_app = app.sub_app(label).unwrap();
+            assert_eq!(
+                *sub_app.resources.get::<Vec<ThreadId>>().unwrap(),
+                vec![std::thread::current().id()]
+            );
+        }
+    }
+
+    #[test]
+    fn task_pools() {
+        fn use_pool(pool: Res<ComputeTaskPool>, mut results: ResMut<Vec<usize>>) {
+            results.push(pool.thread_num());
+        }
+
+        let mut app = App::default();
+        app.resources.insert(TaskPoolOptions {
+            compute_threads: Some(1),
+            ..Default::default()
+        });
+        app.resources.insert(Vec::<usize>::new());
+        app.schedule.add_stage("update");
+        app.schedule
+            .add_system_to_stage("update", use_pool.system());
+        app.update();
+        assert_eq!(*app.resources.get::<Vec<usize>>().unwrap(), vec![1]);
+    }
+}
@@ -1,21 +1,26 @@
 use crate::{
-    app::{App, AppExit},
+    app::{App, AppExit, SubApp},
     event::Events,
-    plugin::{dynamically_load_plugin, Plugin},
+    plugin::{dynamically_load_plugin, Plugin, PluginDependencies},
+    plugin_group::{PluginGroup, PluginGroupBuilder},
     stage, startup_stage,
+    state::{State, StateDriver},
 };
-use bevy_ecs::{FromResources, IntoQuerySystem, Resources, System, World};
+use bevy_ecs::{
+    Component, ComponentHashRegistry, FromResources, IntoQuerySystem, IntoThreadLocalSystem,
+    Resources, RunCriteria, System, World,
+};
+use std::{any::TypeId, collections::HashSet, hash::Hash};
 
 /// Configure [App]s using the builder pattern
 pub struct AppBuilder {
     pub app: App,
+    plugins: HashSet<TypeId>,
 }
 
 impl Default for AppBuilder {
     fn default() -> Self {
-        let mut app_builder = AppBuilder {
-            app: App::default(),
-        };
+        let mut app_builder = AppBuilder::empty();
 
         app_builder.add_default_stages();
         app_builder.add_event::<AppExit>();
@@ -27,6 +32,7 @@ impl AppBuilder {
     pub fn empty() -> AppBuilder {
         AppBuilder {
             app: App::default(),
+            plugins: HashSet::new(),
         }
     }
 
@@ -67,6 +73,17 @@ impl AppBuilder {
         self
     }
 
+    pub fn set_stage_run_criteria(
+        &mut self,
+        stage_name: &'static str,
+        run_criteria: impl RunCriteria,
+    ) -> &mut Self {
+        self.app
+            .schedule
+            .set_stage_run_criteria(stage_name, run_criteria);
+        self
+    }
+
     pub fn add_startup_stage(&mut self, stage_name: &'static str) -> &mut Self {
         self.app.startup_schedule.add_stage(stage_name);
         self
@@ -197,6 +214,79 @@ impl AppBuilder {
             .add_system_to_stage(stage::EVENT_UPDATE, Events::<T>::update_system.system())
     }
 
+    /// Like [AppBuilder::add_event], but events are kept until every reader registered with
+    /// [Events::register_reader] has read them. See [Events::persistent]
+    pub fn add_persistent_event<T>(&mut self) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.add_resource(Events::<T>::persistent())
+            .add_system_to_stage(stage::EVENT_UPDATE, Events::<T>::update_system.system())
+    }
+
+    /// Adds a [State] resource with the given initial state. Systems registered for each state run in a
+    /// dedicated stage after [stage::UPDATE], which is also the only place queued transitions are applied.
+    pub fn add_state<T>(&mut self, initial: T) -> &mut Self
+    where
+        T: Clone + Eq + Hash + Send + Sync + 'static,
+    {
+        self.add_state_after(stage::UPDATE, initial)
+    }
+
+    /// Like [AppBuilder::add_state], but the state's stage is added right after the `target` stage
+    pub fn add_state_after<T>(&mut self, target: &'static str, initial: T) -> &mut Self
+    where
+        T: Clone + Eq + Hash + Send + Sync + 'static,
+    {
+        let stage_name = std::any::type_name::<State<T>>();
+        self.add_resource(State::new(initial))
+            .add_resource(StateDriver::<T>::default())
+            .add_stage_after(target, stage_name)
+            .add_system_to_stage(stage_name, StateDriver::<T>::run.thread_local_system())
+    }
+
+    /// Adds a system that runs once each time `state` is entered
+    pub fn on_state_enter<T>(&mut self, state: T, system: Box<dyn System>) -> &mut Self
+    where
+        T: Clone + Eq + Hash + Send + Sync + 'static,
+    {
+        self.state_driver::<T>().add_enter_system(state, system);
+        self
+    }
+
+    /// Adds a system that runs every update while `state` is the current state
+    pub fn on_state_update<T>(&mut self, state: T, system: Box<dyn System>) -> &mut Self
+    where
+        T: Clone + Eq + Hash + Send + Sync + 'static,
+    {
+        self.state_driver::<T>().add_update_system(state, system);
+        self
+    }
+
+    /// Adds a system that runs once each time `state` is exited
+    pub fn on_state_exit<T>(&mut self, state: T, system: Box<dyn System>) -> &mut Self
+    where
+        T: Clone + Eq + Hash + Send + Sync + 'static,
+    {
+        self.state_driver::<T>().add_exit_system(state, system);
+        self
+    }
+
+    fn state_driver<T>(&mut self) -> bevy_ecs::RefMut<'_, StateDriver<T>>
+    where
+        T: Clone + Eq + Hash + Send + Sync + 'static,
+    {
+        self.app
+            .resources
+            .get_mut::<StateDriver<T>>()
+            .unwrap_or_else(|| {
+                panic!(
+                    "State does not exist: {}. Call add_state first",
+                    std::any::type_name::<T>()
+                )
+            })
+    }
+
     pub fn add_resource<T>(&mut self, resource: T) -> &mut Self
     where
         T: Send + Sync + 'static,
@@ -215,6 +305,55 @@ impl AppBuilder {
         self
     }
 
+    /// Runs the systems of each stage one at a time in a stable order, so that updates don't depend
+    /// on thread scheduling. See [ParallelExecutor](bevy_ecs::ParallelExecutor) for the order
+    ///
+    /// This applies to every executor of the app, including those of states and sub-apps.
+    pub fn set_deterministic(&mut self, deterministic: bool) -> &mut Self {
+        self.app.set_deterministic(deterministic);
+        self
+    }
+
+    /// Includes `T` components in the [WorldHash](crate::WorldHash) computed after each update
+    pub fn register_hashed_component<T>(&mut self) -> &mut Self
+    where
+        T: Component + Hash,
+    {
+        if !self.app.resources.contains::<ComponentHashRegistry>() {
+            self.app.resources.insert(ComponentHashRegistry::default());
+        }
+        self.app
+            .resources
+            .get_mut::<ComponentHashRegistry>()
+            .unwrap()
+            .register::<T>();
+        self
+    }
+
+    /// Adds `app` as a sub-app with its own world and schedule, which is updated after this app each
+    /// time it updates. `extract` is first given this app's world and resources to copy the data the
+    /// sub-app needs into it. See [SubApp]
+    pub fn add_sub_app(
+        &mut self,
+        label: &'static str,
+        app: App,
+        extract: impl Fn(&mut World, &mut Resources, &mut App) + 'static,
+    ) -> &mut Self {
+        if self.app.sub_app(label).is_some() {
+            panic!("Sub-app already exists: {}", label);
+        }
+        let mut app = app;
+        if self.app.is_deterministic() {
+            app.set_deterministic(true);
+        }
+        self.app.sub_apps.push(SubApp {
+            label,
+            app,
+            extract: Box::new(extract),
+        });
+        self
+    }
+
     pub fn set_runner(&mut self, run_fn: impl Fn(App) + 'static) -> &mut Self {
         self.app.runner = Box::new(run_fn);
         self
@@ -223,16 +362,88 @@ impl AppBuilder {
     pub fn load_plugin(&mut self, path: &str) -> &mut Self {
         let (_lib, plugin) = dynamically_load_plugin(path);
         log::debug!("loaded plugin: {}", plugin.name());
-        plugin.build(self);
+        self.build_plugin(plugin.as_ref().type_id(), plugin.as_ref());
         self
     }
 
+    /// Builds `plugin` into the App
+    ///
+    /// Panics if a plugin of the same type was already added, or if one of the dependencies
+    /// declared by [Plugin::dependencies] is missing.
     pub fn add_plugin<T>(&mut self, plugin: T) -> &mut Self
     where
         T: Plugin,
     {
         log::debug!("added plugin: {}", plugin.name());
+        self.build_plugin(TypeId::of::<T>(), &plugin);
+        self
+    }
+
+    /// Whether a `T` plugin has been added
+    pub fn has_plugin<T: Plugin>(&self) -> bool {
+        self.plugins.contains(&TypeId::of::<T>())
+    }
+
+    pub(crate) fn build_plugin(&mut self, ty: TypeId, plugin: &dyn Plugin) {
+        if !self.plugins.insert(ty) {
+            panic!("Plugin already added: {}", plugin.name());
+        }
+
+        let mut dependencies = PluginDependencies::default();
+        plugin.dependencies(&mut dependencies);
+        for (dependency, dependency_name) in dependencies.plugins {
+            if !self.plugins.contains(&dependency) {
+                panic!(
+                    "Plugin {} depends on {}, which must be added before it",
+                    plugin.name(),
+                    dependency_name
+                );
+            }
+        }
+        for (contains, resource_name) in dependencies.resources {
+            if !contains(&self.app.resources) {
+                panic!(
+                    "Plugin {} depends on the resource {}, which must be added before it",
+                    plugin.name(),
+                    resource_name
+                );
+            }
+        }
+
+        // plugins can spawn tasks while they are built
+        self.app.insert_task_pools();
+
         plugin.build(self);
+    }
+
+    /// Adds the plugins of `group`, in order
+    pub fn add_plugins<T: PluginGroup>(&mut self, mut group: T) -> &mut Self {
+        let mut plugin_group_builder = PluginGroupBuilder::default();
+        group.build(&mut plugin_group_builder);
+        plugin_group_builder.finish(self);
+        self
+    }
+
+    /// Adds the plugins of `group` after `func` has had a chance to disable, replace or insert
+    /// plugins
+    ///
+    /// ## Example
+    /// ```ignore
+    /// app.add_plugins_with(DefaultPlugins, |group| {
+    ///     group
+    ///         .disable::<AudioPlugin>()
+    ///         .replace::<WinitPlugin, _>(MyRunnerPlugin)
+    /// });
+    /// ```
+    pub fn add_plugins_with<T, F>(&mut self, mut group: T, func: F) -> &mut Self
+    where
+        T: PluginGroup,
+        F: FnOnce(&mut PluginGroupBuilder) -> &mut PluginGroupBuilder,
+    {
+        let mut plugin_group_builder = PluginGroupBuilder::default();
+        group.build(&mut plugin_group_builder);
+        func(&mut plugin_group_builder);
+        plugin_group_builder.finish(self);
         self
     }
 }
@@ -1,5 +1,11 @@
 use bevy_ecs::ResMut;
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
 
 #[derive(Debug)]
 struct EventInstance<T> {
@@ -55,6 +61,15 @@ enum State {
 ///
 /// An alternative call pattern would be to call [Events::update] manually across frames to control when events are cleared. However
 /// this complicates consumption
+///
+/// [EventReader::missed] reports how many events a reader dropped this way.
+///
+/// # Persistent events
+///
+/// [Events::persistent] creates a collection that keeps events until every registered reader has read them, no matter how
+/// many updates pass. Readers are registered by [Events::register_reader], or when they first read the collection, which
+/// includes readers created with `Default` such as `Local<EventReader<T>>`. Registered readers stop holding events back once
+/// they are dropped.
 #[derive(Debug)]
 pub struct Events<T> {
     events_a: Vec<EventInstance<T>>,
@@ -63,6 +78,8 @@ pub struct Events<T> {
     b_start_event_count: usize,
     event_count: usize,
     state: State,
+    persistent: bool,
+    registered_readers: Mutex<Vec<Arc<AtomicUsize>>>,
 }
 
 impl<T> Default for Events<T> {
@@ -74,6 +91,8 @@ impl<T> Default for Events<T> {
             events_a: Vec::new(),
             events_b: Vec::new(),
             state: State::A,
+            persistent: false,
+            registered_readers: Mutex::new(Vec::new()),
         }
     }
 }
@@ -85,6 +104,8 @@ fn map_instance_event<T>(event_instance: &EventInstance<T>) -> &T {
 /// Reads events of type `T` in order and tracks which events have already been read.
 pub struct EventReader<T> {
     last_event_count: usize,
+    /// shares the read position of readers registered with [Events::register_reader]
+    registration: Option<Arc<AtomicUsize>>,
     _marker: PhantomData<T>,
 }
 
@@ -92,12 +113,21 @@ impl<T> Default for EventReader<T> {
     fn default() -> Self {
         Self {
             last_event_count: 0,
+            registration: None,
             _marker: PhantomData::default(),
         }
     }
 }
 
 impl<T> EventReader<T> {
+    /// The number of events that were sent since this reader last read `events`, but were dropped by [Events::update]
+    /// before it could read them. Call this before reading.
+    pub fn missed(&self, events: &Events<T>) -> usize {
+        events
+            .oldest_event_count()
+            .saturating_sub(self.last_event_count)
+    }
+
     /// Iterates over the events this EventReader has not seen yet. This updates the EventReader's
     /// event counter, which means subsequent event reads will not include events that happened before now.
     pub fn iter<'a>(&mut self, events: &'a Events<T>) -> impl DoubleEndedIterator<Item = &'a T> {
@@ -114,6 +144,11 @@ impl<T> EventReader<T> {
             0
         };
         self.last_event_count = events.event_count;
+        if let Some(registration) = &self.registration {
+            registration.store(self.last_event_count, Ordering::Release);
+        } else if events.persistent {
+            self.registration = Some(events.track_reader(self.last_event_count));
+        }
         match events.state {
             State::A => events
                 .events_b
@@ -169,7 +204,46 @@ impl<T> EventReader<T> {
     }
 }
 
+impl<T> Events<T> {
+    /// Starts holding back events from `last_event_count` on, until the returned registration is dropped
+    fn track_reader(&self, last_event_count: usize) -> Arc<AtomicUsize> {
+        let registration = Arc::new(AtomicUsize::new(last_event_count));
+        self.registered_readers
+            .lock()
+            .unwrap()
+            .push(registration.clone());
+        registration
+    }
+
+    /// The event count of the oldest event still in the event buffers, or of the next event if they are empty
+    fn oldest_event_count(&self) -> usize {
+        match self.state {
+            State::A => self.b_start_event_count,
+            State::B => self.a_start_event_count,
+        }
+    }
+}
+
 impl<T: bevy_ecs::Resource> Events<T> {
+    /// Creates a collection that keeps events until every reader registered with [Events::register_reader] has read them
+    pub fn persistent() -> Self {
+        Self {
+            persistent: true,
+            ..Default::default()
+        }
+    }
+
+    /// Gets a new [EventReader] that reads all events already in the event buffers. If this collection is
+    /// [persistent](Events::persistent), events are kept until this reader has read them or is dropped.
+    pub fn register_reader(&mut self) -> EventReader<T> {
+        let oldest_event_count = self.oldest_event_count();
+        EventReader {
+            last_event_count: oldest_event_count,
+            registration: Some(self.track_reader(oldest_event_count)),
+            _marker: PhantomData,
+        }
+    }
+
     /// "Sends" an `event` by writing it to the current event buffer. [EventReader]s can then read the event.
     pub fn send(&mut self, event: T) {
         let event_instance = EventInstance {
@@ -189,6 +263,7 @@ impl<T: bevy_ecs::Resource> Events<T> {
     pub fn get_reader(&self) -> EventReader<T> {
         EventReader {
             last_event_count: 0,
+            registration: None,
             _marker: PhantomData,
         }
     }
@@ -197,12 +272,20 @@ impl<T: bevy_ecs::Resource> Events<T> {
     pub fn get_reader_current(&self) -> EventReader<T> {
         EventReader {
             last_event_count: self.event_count,
+            registration: None,
             _marker: PhantomData,
         }
     }
 
     /// Swaps the event buffers and clears the oldest event buffer. In general, this should be called once per frame/update.
+    ///
+    /// If this collection is [persistent](Events::persistent), events in the oldest buffer that a registered reader has not
+    /// read yet are kept.
     pub fn update(&mut self) {
+        if self.persistent {
+            self.keep_unread_events();
+        }
+
         match self.state {
             State::A => {
                 self.events_b = Vec::new();
@@ -217,6 +300,37 @@ impl<T: bevy_ecs::Resource> Events<T> {
         }
     }
 
+    /// Moves the events of the oldest buffer that registered readers haven't read to the front of the current buffer
+    fn keep_unread_events(&mut self) {
+        // readers that were dropped no longer hold events back
+        let registered_readers = self.registered_readers.get_mut().unwrap();
+        registered_readers.retain(|registration| Arc::strong_count(registration) > 1);
+        let first_unread = registered_readers
+            .iter()
+            .map(|registration| registration.load(Ordering::Acquire))
+            .min()
+            .unwrap_or(self.event_count);
+
+        let (oldest, current, current_start_event_count) = match self.state {
+            State::A => (
+                &mut self.events_b,
+                &mut self.events_a,
+                &mut self.a_start_event_count,
+            ),
+            State::B => (
+                &mut self.events_a,
+                &mut self.events_b,
+                &mut self.b_start_event_count,
+            ),
+        };
+        oldest.retain(|event| event.event_count >= first_unread);
+        if let Some(first) = oldest.first() {
+            *current_start_event_count = first.event_count;
+            oldest.append(current);
+            std::mem::swap(oldest, current);
+        }
+    }
+
     /// A system that calls [Events::update] once per frame.
     pub fn update_system(mut events: ResMut<Self>) {
         events.update();
@@ -366,6 +480,109 @@ mod tests {
         );
     }
 
+    #[test]
+    fn missed_events() {
+        let mut events = Events::<TestEvent>::default();
+        let mut reader = events.get_reader();
+
+        events.send(TestEvent { i: 0 });
+        events.update();
+        events.send(TestEvent { i: 1 });
+        assert_eq!(reader.missed(&events), 0);
+
+        events.update();
+        events.send(TestEvent { i: 2 });
+        assert_eq!(reader.missed(&events), 1);
+        assert_eq!(
+            get_events(&events, &mut reader),
+            vec![TestEvent { i: 1 }, TestEvent { i: 2 }]
+        );
+        assert_eq!(reader.missed(&events), 0);
+
+        events.update();
+        events.update();
+        assert_eq!(
+            reader.missed(&events),
+            0,
+            "events that were read are not missed"
+        );
+    }
+
+    #[test]
+    fn persistent_events() {
+        let mut events = Events::<TestEvent>::persistent();
+        let mut reader = events.register_reader();
+        let dropped_reader = events.register_reader();
+        let mut unregistered_reader = events.get_reader();
+
+        events.send(TestEvent { i: 0 });
+        events.send(TestEvent { i: 1 });
+        for _ in 0..3 {
+            events.update();
+        }
+        events.send(TestEvent { i: 2 });
+
+        assert_eq!(unregistered_reader.missed(&events), 0);
+        assert_eq!(
+            get_events(&events, &mut reader),
+            vec![TestEvent { i: 0 }, TestEvent { i: 1 }, TestEvent { i: 2 }],
+            "registered readers receive events sent any number of updates ago"
+        );
+
+        events.send(TestEvent { i: 3 });
+        drop(dropped_reader);
+        events.update();
+        events.update();
+        assert_eq!(get_events(&events, &mut reader), vec![TestEvent { i: 3 }]);
+        assert_eq!(
+            unregistered_reader.missed(&events),
+            3,
+            "events are dropped once all registered readers read them"
+        );
+        assert_eq!(
+            get_events(&events, &mut unregistered_reader),
+            vec![TestEvent { i: 3 }]
+        );
+    }
+
+    #[test]
+    fn persistent_events_with_local_reader() {
+        use bevy_ecs::{IntoQuerySystem, Local, Res, ResMut, Resources, Schedule, World};
+
+        fn read_events(
+            mut reader: Local<EventReader<TestEvent>>,
+            events: Res<Events<TestEvent>>,
+            mut received: ResMut<Vec<TestEvent>>,
+        ) {
+            received.extend(reader.iter(&events).copied());
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Events::<TestEvent>::persistent());
+        resources.insert(Vec::<TestEvent>::new());
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", read_events.system());
+        schedule.initialize(&mut resources);
+        schedule.run(&mut world, &mut resources);
+
+        {
+            let mut events = resources.get_mut::<Events<TestEvent>>().unwrap();
+            events.send(TestEvent { i: 0 });
+            for _ in 0..3 {
+                events.update();
+            }
+            events.send(TestEvent { i: 1 });
+        }
+        schedule.run(&mut world, &mut resources);
+        assert_eq!(
+            *resources.get::<Vec<TestEvent>>().unwrap(),
+            vec![TestEvent { i: 0 }, TestEvent { i: 1 }],
+            "local readers hold events back once they have read"
+        );
+    }
+
     fn get_events(
         events: &Events<TestEvent>,
         reader: &mut EventReader<TestEvent>,
@@ -7,21 +7,28 @@ mod app;
 mod app_builder;
 mod event;
 mod plugin;
+mod plugin_group;
 mod schedule_runner;
+mod state;
 
 pub use app::*;
 pub use app_builder::*;
 pub use bevy_derive::DynamicPlugin;
 pub use event::*;
 pub use plugin::*;
+pub use plugin_group::*;
 pub use schedule_runner::*;
+pub use state::*;
 
 pub mod prelude {
     pub use crate::{
         app::App,
         app_builder::AppBuilder,
         event::{EventReader, Events},
-        plugin::Plugin,
-        stage, DynamicPlugin,
+        plugin::{Plugin, PluginDependencies},
+        plugin_group::{PluginGroup, PluginGroupBuilder},
+        stage,
+        state::State,
+        DynamicPlugin,
     };
 }
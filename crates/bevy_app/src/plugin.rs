@@ -1,6 +1,7 @@
 use crate::AppBuilder;
+use bevy_ecs::{Resource, Resources};
 use libloading::{Library, Symbol};
-use std::any::Any;
+use std::any::{type_name, Any, TypeId};
 
 /// A collection of Bevy App logic and configuration
 ///
@@ -10,6 +11,33 @@ pub trait Plugin: Any + Send + Sync {
     fn name(&self) -> &str {
         std::any::type_name::<Self>()
     }
+
+    /// Declares the plugins and resources that must be added to the App before this plugin. They
+    /// are checked by [AppBuilder::add_plugin]
+    fn dependencies(&self, _dependencies: &mut PluginDependencies) {}
+}
+
+/// The plugins and resources a [Plugin] requires, see [Plugin::dependencies]
+#[derive(Default)]
+pub struct PluginDependencies {
+    pub(crate) plugins: Vec<(TypeId, &'static str)>,
+    pub(crate) resources: Vec<(fn(&Resources) -> bool, &'static str)>,
+}
+
+impl PluginDependencies {
+    /// Requires the `T` plugin
+    pub fn plugin<T: Plugin>(&mut self) -> &mut Self {
+        self.plugins.push((TypeId::of::<T>(), type_name::<T>()));
+        self
+    }
+
+    /// Requires a `T` resource, for dependencies that can be provided by more than one plugin or by
+    /// the App itself
+    pub fn resource<T: Resource>(&mut self) -> &mut Self {
+        self.resources
+            .push((|resources| resources.contains::<T>(), type_name::<T>()));
+        self
+    }
 }
 
 pub type CreatePlugin = unsafe fn() -> *mut dyn Plugin;
@@ -24,3 +52,71 @@ pub fn dynamically_load_plugin(path: &str) -> (Library, Box<dyn Plugin>) {
         (lib, plugin)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Plugin, PluginDependencies};
+    use crate::AppBuilder;
+
+    struct Settings;
+
+    struct BasePlugin;
+
+    impl Plugin for BasePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_resource(Settings);
+        }
+    }
+
+    struct DependentPlugin;
+
+    impl Plugin for DependentPlugin {
+        fn build(&self, _app: &mut AppBuilder) {}
+
+        fn dependencies(&self, dependencies: &mut PluginDependencies) {
+            dependencies.plugin::<BasePlugin>().resource::<Settings>();
+        }
+    }
+
+    struct SettingsPlugin;
+
+    impl Plugin for SettingsPlugin {
+        fn build(&self, _app: &mut AppBuilder) {}
+
+        fn dependencies(&self, dependencies: &mut PluginDependencies) {
+            dependencies.resource::<Settings>();
+        }
+    }
+
+    #[test]
+    fn dependencies() {
+        let mut app = AppBuilder::empty();
+        app.add_plugin(BasePlugin).add_plugin(DependentPlugin);
+        assert!(app.has_plugin::<BasePlugin>() && app.has_plugin::<DependentPlugin>());
+
+        // resources can be provided without a plugin
+        AppBuilder::empty()
+            .add_resource(Settings)
+            .add_plugin(SettingsPlugin);
+    }
+
+    #[test]
+    #[should_panic(expected = "depends on bevy_app::plugin::tests::BasePlugin")]
+    fn missing_plugin_dependency() {
+        AppBuilder::empty().add_plugin(DependentPlugin);
+    }
+
+    #[test]
+    #[should_panic(expected = "depends on the resource bevy_app::plugin::tests::Settings")]
+    fn missing_resource_dependency() {
+        AppBuilder::empty().add_plugin(SettingsPlugin);
+    }
+
+    #[test]
+    #[should_panic(expected = "Plugin already added: bevy_app::plugin::tests::BasePlugin")]
+    fn plugin_added_twice() {
+        AppBuilder::empty()
+            .add_plugin(BasePlugin)
+            .add_plugin(BasePlugin);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{AppBuilder, Plugin};
+use std::{any::TypeId, collections::HashMap};
+
+/// A list of [Plugin]s that are added to an App together, such as the default plugins
+///
+/// Groups are added with [AppBuilder::add_plugins], or with [AppBuilder::add_plugins_with] to
+/// disable, replace or insert plugins first.
+pub trait PluginGroup {
+    fn build(&mut self, group: &mut PluginGroupBuilder);
+}
+
+struct PluginEntry {
+    plugin: Box<dyn Plugin>,
+    enabled: bool,
+}
+
+impl PluginEntry {
+    fn new<T: Plugin>(plugin: T) -> Self {
+        Self {
+            plugin: Box::new(plugin),
+            enabled: true,
+        }
+    }
+}
+
+/// The plugins of a [PluginGroup], identified by type and kept in the order they are added in
+#[derive(Default)]
+pub struct PluginGroupBuilder {
+    plugins: HashMap<TypeId, PluginEntry>,
+    order: Vec<TypeId>,
+}
+
+impl PluginGroupBuilder {
+    /// Adds `plugin` to the end of the group. If the group already has a `T` plugin, it is replaced
+    /// in place instead
+    pub fn add<T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        let ty = TypeId::of::<T>();
+        if self.plugins.insert(ty, PluginEntry::new(plugin)).is_none() {
+            self.order.push(ty);
+        }
+        self
+    }
+
+    /// Adds `plugin` right before the `Target` plugin, moving it if the group already has it
+    pub fn add_before<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        let index = self.index_of::<Target>();
+        self.insert(index, plugin)
+    }
+
+    /// Adds `plugin` right after the `Target` plugin, moving it if the group already has it
+    pub fn add_after<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        let index = self.index_of::<Target>() + 1;
+        self.insert(index, plugin)
+    }
+
+    /// Puts `plugin` in the place of the `Target` plugin, which is removed from the group
+    pub fn replace<Target: Plugin, T: Plugin>(&mut self, plugin: T) -> &mut Self {
+        let index = self.index_of::<Target>();
+        self.remove::<Target>();
+        self.insert(index, plugin)
+    }
+
+    /// Adds the `T` plugin when the group is added to an App. Plugins are enabled when added
+    pub fn enable<T: Plugin>(&mut self) -> &mut Self {
+        self.entry_mut::<T>().enabled = true;
+        self
+    }
+
+    /// Skips the `T` plugin when the group is added to an App
+    pub fn disable<T: Plugin>(&mut self) -> &mut Self {
+        self.entry_mut::<T>().enabled = false;
+        self
+    }
+
+    /// Whether the group has a `T` plugin, enabled or not
+    pub fn contains<T: Plugin>(&self) -> bool {
+        self.plugins.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Adds the enabled plugins to `app`, in order
+    pub fn finish(self, app: &mut AppBuilder) {
+        let mut plugins = self.plugins;
+        for ty in self.order {
+            let entry = plugins.remove(&ty).unwrap();
+            if entry.enabled {
+                log::debug!("added plugin: {}", entry.plugin.name());
+                app.build_plugin(ty, entry.plugin.as_ref());
+            }
+        }
+    }
+
+    /// Inserts `plugin` at `index` of the current order. If the group already has a `T` plugin, it
+    /// is moved, and `index` is shifted to account for its removal
+    fn insert<T: Plugin>(&mut self, mut index: usize, plugin: T) -> &mut Self {
+        if let Some(position) = self.remove::<T>() {
+            if position < index {
+                index -= 1;
+            }
+        }
+        let ty = TypeId::of::<T>();
+        self.plugins.insert(ty, PluginEntry::new(plugin));
+        self.order.insert(index, ty);
+        self
+    }
+
+    /// Removes the `T` plugin from the group, returning the position it had in the order
+    fn remove<T: Plugin>(&mut self) -> Option<usize> {
+        let ty = TypeId::of::<T>();
+        self.plugins.remove(&ty)?;
+        let position = self.order.iter().position(|x| *x == ty).unwrap();
+        self.order.remove(position);
+        Some(position)
+    }
+
+    fn index_of<T: Plugin>(&self) -> usize {
+        let ty = TypeId::of::<T>();
+        self.order
+            .iter()
+            .position(|x| *x == ty)
+            .unwrap_or_else(|| panic!("Plugin does not exist: {}", std::any::type_name::<T>()))
+    }
+
+    fn entry_mut<T: Plugin>(&mut self) -> &mut PluginEntry {
+        self.plugins
+            .get_mut(&TypeId::of::<T>())
+            .unwrap_or_else(|| panic!("Plugin does not exist: {}", std::any::type_name::<T>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PluginGroup, PluginGroupBuilder};
+    use crate::{AppBuilder, Plugin};
+
+    macro_rules! impl_test_plugin {
+        ($name: ident) => {
+            struct $name;
+
+            impl Plugin for $name {
+                fn build(&self, app: &mut AppBuilder) {
+                    if !app.resources().contains::<Vec<&'static str>>() {
+                        app.add_resource(Vec::<&'static str>::new());
+                    }
+                    app.resources_mut()
+                        .get_mut::<Vec<&'static str>>()
+                        .unwrap()
+                        .push(stringify!($name));
+                }
+            }
+        };
+    }
+
+    impl_test_plugin!(A);
+    impl_test_plugin!(B);
+    impl_test_plugin!(C);
+    impl_test_plugin!(D);
+    impl_test_plugin!(E);
+
+    struct TestPlugins;
+
+    impl PluginGroup for TestPlugins {
+        fn build(&mut self, group: &mut PluginGroupBuilder) {
+            group.add(A).add(B).add(C);
+        }
+    }
+
+    fn added_plugins(app: &AppBuilder) -> Vec<&'static str> {
+        app.resources()
+            .get::<Vec<&'static str>>()
+            .map(|added| (*added).clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn plugin_group() {
+        let mut app = AppBuilder::empty();
+        app.add_plugins(TestPlugins);
+        assert_eq!(added_plugins(&app), vec!["A", "B", "C"]);
+
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| {
+            group
+                .disable::<B>()
+                .add_before::<A, _>(D)
+                .replace::<C, _>(E)
+                .add_after::<D, _>(C)
+        });
+        assert_eq!(added_plugins(&app), vec!["D", "C", "A", "E"]);
+
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| {
+            group.disable::<A>().disable::<B>().enable::<B>()
+        });
+        assert_eq!(added_plugins(&app), vec!["B", "C"]);
+    }
+
+    #[test]
+    fn plugin_group_same_target() {
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| group.add_before::<B, _>(B));
+        assert_eq!(added_plugins(&app), vec!["A", "B", "C"]);
+
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| group.add_after::<B, _>(B));
+        assert_eq!(added_plugins(&app), vec!["A", "B", "C"]);
+
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| group.replace::<C, _>(C));
+        assert_eq!(added_plugins(&app), vec!["A", "B", "C"]);
+
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| {
+            group.add_after::<C, _>(A).add_before::<A, _>(B)
+        });
+        assert_eq!(added_plugins(&app), vec!["C", "B", "A"]);
+
+        let mut app = AppBuilder::empty();
+        app.add_plugins_with(TestPlugins, |group| group.replace::<B, _>(A));
+        assert_eq!(added_plugins(&app), vec!["A", "C"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Plugin does not exist")]
+    fn missing_plugin() {
+        AppBuilder::empty().add_plugins_with(TestPlugins, |group| group.disable::<D>());
+    }
+}
@@ -0,0 +1,405 @@
+use crate::stage;
+use bevy_ecs::{ParallelExecutor, Resources, Schedule, System, World};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// A state machine stored as a resource. Systems can be registered to run when a given state is entered,
+/// while it is active, and when it is exited. See [AppBuilder::add_state](crate::AppBuilder::add_state).
+///
+/// Transitions are queued using [State::set_next] and applied the next time the state's stage runs, so
+/// the current state never changes while other stages are running. The state's stage is the only place
+/// transitions happen. It comes right after [stage::UPDATE] by default, see
+/// [AppBuilder::add_state_after](crate::AppBuilder::add_state_after) to put it elsewhere.
+///
+/// At most [State::MAX_TRANSITIONS_PER_UPDATE] transitions are applied each time the stage runs. Transitions
+/// that enter and exit systems keep queuing beyond that are applied on the next run.
+///
+/// ## Example
+/// ```
+/// use bevy_app::prelude::*;
+/// use bevy_ecs::prelude::*;
+///
+/// #[derive(Clone, Eq, PartialEq, Hash)]
+/// enum AppState {
+///     Menu,
+///     InGame,
+/// }
+///
+/// fn start_game(mut state: ResMut<State<AppState>>) {
+///     state.set_next(AppState::InGame);
+/// }
+///
+/// App::build()
+///     .add_state(AppState::Menu)
+///     .on_state_update(AppState::Menu, start_game.system());
+/// ```
+#[derive(Debug)]
+pub struct State<T> {
+    current: T,
+    previous: Option<T>,
+    next: Option<T>,
+}
+
+impl<T> State<T> {
+    /// The number of transitions applied each time the state's stage runs, at most
+    pub const MAX_TRANSITIONS_PER_UPDATE: usize = 16;
+
+    pub fn new(initial: T) -> Self {
+        Self {
+            current: initial,
+            previous: None,
+            next: None,
+        }
+    }
+
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+
+    /// The state that was active before the last transition
+    pub fn previous(&self) -> Option<&T> {
+        self.previous.as_ref()
+    }
+
+    /// The state that will become active on the next transition, if one is queued
+    pub fn next(&self) -> Option<&T> {
+        self.next.as_ref()
+    }
+
+    /// Queues a transition to `next`, replacing any transition that is already queued
+    pub fn set_next(&mut self, next: T) {
+        self.next = Some(next);
+    }
+}
+
+pub(crate) struct StateSchedule {
+    schedule: Schedule,
+    executor: ParallelExecutor,
+}
+
+impl Default for StateSchedule {
+    fn default() -> Self {
+        let mut schedule = Schedule::default();
+        schedule.add_stage(stage::UPDATE);
+        Self {
+            schedule,
+            // trackers are cleared by the app's executor once per update
+            executor: ParallelExecutor::without_tracker_clears(),
+        }
+    }
+}
+
+impl StateSchedule {
+    fn run(&mut self, world: &mut World, resources: &mut Resources) {
+        self.schedule.initialize(resources);
+        self.executor.run(&mut self.schedule, world, resources);
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StateSchedules {
+    enter: StateSchedule,
+    update: StateSchedule,
+    exit: StateSchedule,
+}
+
+/// Stores the schedules of each value of a [State]
+pub(crate) struct StateDriver<T> {
+    schedules: HashMap<T, StateSchedules>,
+    entered_initial_state: bool,
+}
+
+impl<T> Default for StateDriver<T> {
+    fn default() -> Self {
+        Self {
+            schedules: Default::default(),
+            entered_initial_state: false,
+        }
+    }
+}
+
+impl<T> StateDriver<T>
+where
+    T: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    pub(crate) fn add_enter_system(&mut self, state: T, system: Box<dyn System>) {
+        self.state_schedules(state)
+            .enter
+            .schedule
+            .add_system_to_stage(stage::UPDATE, system);
+    }
+
+    pub(crate) fn add_update_system(&mut self, state: T, system: Box<dyn System>) {
+        self.state_schedules(state)
+            .update
+            .schedule
+            .add_system_to_stage(stage::UPDATE, system);
+    }
+
+    pub(crate) fn add_exit_system(&mut self, state: T, system: Box<dyn System>) {
+        self.state_schedules(state)
+            .exit
+            .schedule
+            .add_system_to_stage(stage::UPDATE, system);
+    }
+
+    fn state_schedules(&mut self, state: T) -> &mut StateSchedules {
+        self.schedules.entry(state).or_default()
+    }
+
+    /// Applies queued transitions, then runs the update schedule of the current state. Enter and exit systems
+    /// may queue further transitions, which are applied before the update schedule runs.
+    pub(crate) fn run(world: &mut World, resources: &mut Resources) {
+        // the driver is taken out of resources so its schedules can borrow them mutably
+        let mut driver = std::mem::take(
+            &mut *resources
+                .get_mut::<StateDriver<T>>()
+                .expect("State driver resource does not exist"),
+        );
+
+        // put the driver back even if a state system panics, so the state keeps its schedules
+        let result =
+            panic::catch_unwind(AssertUnwindSafe(|| driver.run_schedules(world, resources)));
+        *resources.get_mut::<StateDriver<T>>().unwrap() = driver;
+        if let Err(payload) = result {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    fn run_schedules(&mut self, world: &mut World, resources: &mut Resources) {
+        let mut transitions = 0;
+        loop {
+            let (exiting, entering) = {
+                let mut state = resources
+                    .get_mut::<State<T>>()
+                    .expect("State resource does not exist");
+                if !self.entered_initial_state {
+                    self.entered_initial_state = true;
+                    (None, state.current.clone())
+                } else if state.next.is_none() {
+                    break;
+                } else if transitions == State::<T>::MAX_TRANSITIONS_PER_UPDATE {
+                    log::warn!(
+                        "{} transitioned {} times in one update, the queued transition is applied on the next update",
+                        std::any::type_name::<State<T>>(),
+                        transitions
+                    );
+                    break;
+                } else {
+                    transitions += 1;
+                    let next = state.next.take().unwrap();
+                    let previous = std::mem::replace(&mut state.current, next.clone());
+                    state.previous = Some(previous.clone());
+                    (Some(previous), next)
+                }
+            };
+
+            if let Some(exiting) = exiting {
+                if let Some(schedules) = self.schedules.get_mut(&exiting) {
+                    schedules.exit.run(world, resources);
+                }
+            }
+
+            if let Some(schedules) = self.schedules.get_mut(&entering) {
+                schedules.enter.run(world, resources);
+            }
+        }
+
+        let current = resources.get::<State<T>>().unwrap().current.clone();
+        if let Some(schedules) = self.schedules.get_mut(&current) {
+            schedules.update.run(world, resources);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{State, StateDriver};
+    use crate::{stage, App};
+    use bevy_ecs::{IntoQuerySystem, ResMut};
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        thread::ThreadId,
+    };
+
+    #[derive(Clone, Eq, PartialEq, Hash, Debug)]
+    enum AppState {
+        Menu,
+        InGame,
+    }
+
+    #[test]
+    fn transitions_per_update_are_capped() {
+        fn enter_menu(mut entered: ResMut<usize>, mut state: ResMut<State<AppState>>) {
+            *entered += 1;
+            state.set_next(AppState::InGame);
+        }
+
+        fn enter_game(mut entered: ResMut<usize>, mut state: ResMut<State<AppState>>) {
+            *entered += 1;
+            state.set_next(AppState::Menu);
+        }
+
+        let mut app = App::build();
+        app.add_resource(0usize)
+            .add_state(AppState::Menu)
+            .on_state_enter(AppState::Menu, enter_menu.system())
+            .on_state_enter(AppState::InGame, enter_game.system());
+
+        // the initial state is entered, then each transition enters another state
+        let max = State::<AppState>::MAX_TRANSITIONS_PER_UPDATE;
+        app.app.update();
+        assert_eq!(*app.resources().get::<usize>().unwrap(), 1 + max);
+        assert!(app
+            .resources()
+            .get::<State<AppState>>()
+            .unwrap()
+            .next()
+            .is_some());
+
+        app.app.update();
+        assert_eq!(*app.resources().get::<usize>().unwrap(), 1 + 2 * max);
+    }
+
+    #[test]
+    fn state_driver_survives_panicking_system() {
+        fn menu_update() {
+            panic!("menu update failed");
+        }
+
+        fn in_game_enter(mut log: ResMut<Vec<&'static str>>) {
+            log.push("in_game_enter");
+        }
+
+        let mut app = App::build();
+        app.set_deterministic(true)
+            .add_resource(Vec::<&'static str>::new())
+            .add_state(AppState::Menu)
+            .on_state_update(AppState::Menu, menu_update.system())
+            .on_state_enter(AppState::InGame, in_game_enter.system());
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| app.app.update()));
+        assert!(result.is_err());
+
+        app.resources_mut()
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .set_next(AppState::InGame);
+        // the app's own schedule is poisoned by the panic, so the driver is run directly
+        let app = &mut app.app;
+        StateDriver::<AppState>::run(&mut app.world, &mut app.resources);
+        assert_eq!(
+            *app.resources.get::<Vec<&'static str>>().unwrap(),
+            vec!["in_game_enter"]
+        );
+    }
+
+    #[test]
+    fn state_after_stage() {
+        fn record_state(mut log: ResMut<Vec<&'static str>>) {
+            log.push("state");
+        }
+
+        fn record_update(mut log: ResMut<Vec<&'static str>>) {
+            log.push("update");
+        }
+
+        let mut app = App::build();
+        app.add_resource(Vec::<&'static str>::new())
+            .add_state_after(stage::PRE_UPDATE, AppState::Menu)
+            .on_state_update(AppState::Menu, record_state.system())
+            .add_system(record_update.system());
+
+        app.app.update();
+        assert_eq!(
+            *app.resources().get::<Vec<&'static str>>().unwrap(),
+            vec!["state", "update"]
+        );
+    }
+
+    #[test]
+    fn deterministic_state_systems() {
+        fn record_thread(mut threads: ResMut<Vec<ThreadId>>) {
+            threads.push(std::thread::current().id());
+        }
+
+        fn start_game(mut state: ResMut<State<AppState>>) {
+            state.set_next(AppState::InGame);
+        }
+
+        let mut app = App::build();
+        app.set_deterministic(true)
+            .add_resource(Vec::<ThreadId>::new())
+            .add_state(AppState::Menu)
+            .on_state_enter(AppState::Menu, record_thread.system())
+            .on_state_update(AppState::Menu, start_game.system())
+            .on_state_exit(AppState::Menu, record_thread.system())
+            .on_state_update(AppState::InGame, record_thread.system());
+
+        app.app.update();
+        app.app.update();
+
+        // deterministic executors run systems on the calling thread
+        assert_eq!(
+            *app.resources().get::<Vec<ThreadId>>().unwrap(),
+            vec![std::thread::current().id(); 3]
+        );
+    }
+
+    #[test]
+    fn state_transitions() {
+        fn menu_enter(mut log: ResMut<Vec<&'static str>>) {
+            log.push("menu_enter");
+        }
+
+        fn menu_update(mut log: ResMut<Vec<&'static str>>, mut state: ResMut<State<AppState>>) {
+            log.push("menu_update");
+            state.set_next(AppState::InGame);
+        }
+
+        fn menu_exit(mut log: ResMut<Vec<&'static str>>) {
+            log.push("menu_exit");
+        }
+
+        fn in_game_enter(mut log: ResMut<Vec<&'static str>>) {
+            log.push("in_game_enter");
+        }
+
+        fn in_game_update(mut log: ResMut<Vec<&'static str>>) {
+            log.push("in_game_update");
+        }
+
+        let mut app = App::build();
+        app.add_resource(Vec::<&'static str>::new())
+            .add_state(AppState::Menu)
+            .on_state_enter(AppState::Menu, menu_enter.system())
+            .on_state_update(AppState::Menu, menu_update.system())
+            .on_state_exit(AppState::Menu, menu_exit.system())
+            .on_state_enter(AppState::InGame, in_game_enter.system())
+            .on_state_update(AppState::InGame, in_game_update.system());
+
+        app.app.update();
+        assert_eq!(
+            *app.resources().get::<Vec<&'static str>>().unwrap(),
+            vec!["menu_enter", "menu_update"]
+        );
+
+        app.app.update();
+        let state = app.resources().get::<State<AppState>>().unwrap();
+        assert_eq!(state.current(), &AppState::InGame);
+        assert_eq!(state.previous(), Some(&AppState::Menu));
+        assert_eq!(
+            *app.resources().get::<Vec<&'static str>>().unwrap(),
+            vec![
+                "menu_enter",
+                "menu_update",
+                "menu_exit",
+                "in_game_enter",
+                "in_game_update"
+            ]
+        );
+    }
+}
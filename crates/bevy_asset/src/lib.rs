@@ -22,7 +22,7 @@ pub mod prelude {
     pub use crate::{AddAsset, AssetEvent, AssetServer, Assets, Handle};
 }
 
-use bevy_app::{prelude::Plugin, AppBuilder};
+use bevy_app::{prelude::Plugin, AppBuilder, PluginDependencies};
 use bevy_ecs::IntoQuerySystem;
 use bevy_type_registry::RegisterType;
 
@@ -44,4 +44,8 @@ impl Plugin for AssetPlugin {
             AssetServer::filesystem_watcher_system.system(),
         );
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_type_registry::TypeRegistryPlugin>();
+    }
 }
@@ -23,4 +23,8 @@ impl Plugin for AudioPlugin {
             .add_asset_loader::<AudioSource, Mp3Loader>()
             .add_system_to_stage(stage::POST_UPDATE, play_queued_audio_system.system());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_asset::AssetPlugin>();
+    }
 }
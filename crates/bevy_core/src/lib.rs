@@ -9,7 +9,9 @@ pub use label::*;
 pub use time::*;
 
 pub mod prelude {
-    pub use crate::{EntityLabels, Labels, Time, Timer};
+    pub use crate::{
+        EntityLabels, FixedTimestep, FixedTimestepState, FixedTimesteps, Labels, Time, Timer,
+    };
 }
 
 use bevy_app::prelude::*;
@@ -36,4 +38,8 @@ impl Plugin for CorePlugin {
             .add_system_to_stage(stage::FIRST, timer_system.system())
             .add_system_to_stage(stage::PRE_UPDATE, entity_labels_system.system());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_type_registry::TypeRegistryPlugin>();
+    }
 }
@@ -0,0 +1,268 @@
+use crate::time::Time;
+use bevy_ecs::{Resources, RunCriteria, ShouldRun, World};
+use std::{borrow::Cow, collections::HashMap};
+
+/// Describes a labeled [FixedTimestep]. It is updated in [FixedTimesteps] right before each run of the stage that
+/// uses the timestep.
+#[derive(Debug, Copy, Clone)]
+pub struct FixedTimestepState {
+    /// The stable delta, in seconds, between runs of the stage
+    pub step: f64,
+    /// Time that has passed but has not been consumed by a step yet
+    pub accumulator: f64,
+}
+
+impl FixedTimestepState {
+    /// The fraction of a step that has accumulated since the last run. Useful for interpolating between steps.
+    pub fn overstep_percentage(&self) -> f64 {
+        self.accumulator / self.step
+    }
+}
+
+/// The states of the [FixedTimestep]s that have a label, keyed by that label. This resource is added by the first
+/// labeled timestep that runs.
+#[derive(Debug, Default)]
+pub struct FixedTimesteps {
+    states: HashMap<Cow<'static, str>, FixedTimestepState>,
+}
+
+impl FixedTimesteps {
+    /// The state of the timestep with the given label, once it has run
+    pub fn get(&self, label: &str) -> Option<&FixedTimestepState> {
+        self.states.get(label)
+    }
+}
+
+/// Run criteria that runs a stage once for every `step` seconds that have passed according to [Time].
+/// Depending on how much time has passed, the stage will run zero or more times per schedule run.
+///
+/// The stage runs at most [FixedTimestep::max_steps_per_frame] times per schedule run. When it falls further
+/// behind, for example after a long frame, the time it can't catch up on is dropped, so the stage doesn't slow
+/// down each following frame trying to catch up.
+///
+/// Systems of the stage can read the timestep's [FixedTimestepState] from [FixedTimesteps] if it is given a label
+/// with [FixedTimestep::with_label]. Each timestep needs its own label.
+///
+/// ## Example
+/// ```
+/// use bevy_app::prelude::*;
+/// use bevy_core::{FixedTimestep, FixedTimesteps};
+/// use bevy_ecs::prelude::*;
+///
+/// fn fixed_update(timesteps: Res<FixedTimesteps>) {
+///     let step = timesteps.get("fixed_update").unwrap().step;
+/// }
+///
+/// App::build()
+///     .add_stage_after(stage::UPDATE, "fixed_update")
+///     .set_stage_run_criteria(
+///         "fixed_update",
+///         FixedTimestep::steps_per_second(60.0).with_label("fixed_update"),
+///     )
+///     .add_system_to_stage("fixed_update", fixed_update.system());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    label: Option<Cow<'static, str>>,
+    step: f64,
+    accumulator: f64,
+    max_steps_per_frame: u32,
+    steps_this_frame: u32,
+    looping: bool,
+}
+
+impl FixedTimestep {
+    /// The default limit of steps per schedule run
+    pub const DEFAULT_MAX_STEPS_PER_FRAME: u32 = 8;
+
+    pub fn step(step: f64) -> Self {
+        Self {
+            label: None,
+            step,
+            accumulator: 0.0,
+            max_steps_per_frame: Self::DEFAULT_MAX_STEPS_PER_FRAME,
+            steps_this_frame: 0,
+            looping: false,
+        }
+    }
+
+    pub fn steps_per_second(rate: f64) -> Self {
+        Self::step(1.0 / rate)
+    }
+
+    /// Stores the state of this timestep in [FixedTimesteps] under `label`
+    pub fn with_label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Limits how many times the stage runs per schedule run. Must be at least 1.
+    pub fn max_steps_per_frame(mut self, max_steps_per_frame: u32) -> Self {
+        assert!(
+            max_steps_per_frame > 0,
+            "FixedTimestep must be able to run at least once per frame"
+        );
+        self.max_steps_per_frame = max_steps_per_frame;
+        self
+    }
+}
+
+impl RunCriteria for FixedTimestep {
+    fn should_run(&mut self, _world: &mut World, resources: &mut Resources) -> ShouldRun {
+        // only accumulate time on the first check of each schedule run
+        if !self.looping {
+            let time = resources
+                .get::<Time>()
+                .expect("FixedTimestep requires the Time resource, which is added by CorePlugin");
+            self.accumulator += time.delta_seconds_f64;
+            self.steps_this_frame = 0;
+        }
+
+        if self.accumulator >= self.step && self.steps_this_frame == self.max_steps_per_frame {
+            // drop the whole steps that are left, keeping the overstep
+            self.accumulator %= self.step;
+            self.looping = false;
+            ShouldRun::No
+        } else if self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            self.steps_this_frame += 1;
+            self.looping = true;
+
+            if let Some(label) = &self.label {
+                let state = FixedTimestepState {
+                    step: self.step,
+                    accumulator: self.accumulator,
+                };
+                if !resources.contains::<FixedTimesteps>() {
+                    resources.insert(FixedTimesteps::default());
+                }
+                resources
+                    .get_mut::<FixedTimesteps>()
+                    .unwrap()
+                    .states
+                    .insert(label.clone(), state);
+            }
+
+            ShouldRun::YesAndLoop
+        } else {
+            self.looping = false;
+            ShouldRun::No
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedTimestep, FixedTimesteps};
+    use crate::time::Time;
+    use bevy_ecs::{Resources, RunCriteria, ShouldRun, World};
+
+    /// Advances time by `delta` seconds and returns how many times the stage runs this frame
+    fn run_frame(
+        timestep: &mut FixedTimestep,
+        world: &mut World,
+        resources: &mut Resources,
+        delta: f64,
+    ) -> u32 {
+        resources.get_mut::<Time>().unwrap().delta_seconds_f64 = delta;
+        let mut runs = 0;
+        while timestep.should_run(world, resources) == ShouldRun::YesAndLoop {
+            runs += 1;
+        }
+        runs
+    }
+
+    fn setup() -> (World, Resources) {
+        let mut resources = Resources::default();
+        resources.insert(Time::default());
+        (World::default(), resources)
+    }
+
+    #[test]
+    fn accumulates_time_across_frames() {
+        let (mut world, mut resources) = setup();
+        let mut timestep = FixedTimestep::step(0.5);
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 0.25),
+            0
+        );
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 0.25),
+            1
+        );
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 1.25),
+            2
+        );
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 0.25),
+            1
+        );
+    }
+
+    #[test]
+    fn overstep() {
+        let (mut world, mut resources) = setup();
+        let mut timestep = FixedTimestep::step(0.5).with_label("fixed");
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 1.25),
+            2
+        );
+        let state = *resources
+            .get::<FixedTimesteps>()
+            .unwrap()
+            .get("fixed")
+            .unwrap();
+        assert_eq!(state.step, 0.5);
+        assert_eq!(state.accumulator, 0.25);
+        assert_eq!(state.overstep_percentage(), 0.5);
+    }
+
+    #[test]
+    fn separate_labeled_states() {
+        let (mut world, mut resources) = setup();
+        let mut slow = FixedTimestep::step(0.5).with_label("slow");
+        let mut fast = FixedTimestep::step(0.1).with_label("fast");
+        let mut unlabeled = FixedTimestep::step(0.2);
+        assert_eq!(run_frame(&mut slow, &mut world, &mut resources, 0.75), 1);
+        assert_eq!(run_frame(&mut fast, &mut world, &mut resources, 0.75), 7);
+        assert_eq!(
+            run_frame(&mut unlabeled, &mut world, &mut resources, 0.75),
+            3
+        );
+
+        let timesteps = resources.get::<FixedTimesteps>().unwrap();
+        let slow = timesteps.get("slow").unwrap();
+        assert_eq!(slow.step, 0.5);
+        assert_eq!(slow.accumulator, 0.25);
+        let fast = timesteps.get("fast").unwrap();
+        assert_eq!(fast.step, 0.1);
+        assert!((fast.accumulator - 0.05).abs() < 1e-9);
+        assert!(timesteps.get("unlabeled").is_none());
+    }
+
+    #[test]
+    fn max_steps_per_frame() {
+        let (mut world, mut resources) = setup();
+        let mut timestep = FixedTimestep::step(0.5).max_steps_per_frame(2);
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 5.25),
+            2
+        );
+        // the steps that were left behind are dropped, but not the overstep
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 0.25),
+            1
+        );
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 0.25),
+            0
+        );
+
+        let mut timestep = FixedTimestep::step(0.5);
+        assert_eq!(
+            run_frame(&mut timestep, &mut world, &mut resources, 100.0),
+            FixedTimestep::DEFAULT_MAX_STEPS_PER_FRAME
+        );
+    }
+}
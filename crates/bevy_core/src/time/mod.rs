@@ -1,5 +1,7 @@
+mod fixed_timestep;
 mod time;
 mod timer;
 
+pub use fixed_timestep::*;
 pub use time::*;
 pub use timer::*;
@@ -12,6 +12,10 @@ impl Plugin for FrameTimeDiagnosticsPlugin {
         app.add_startup_system(Self::setup_system.system())
             .add_system(Self::diagnostic_system.system());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.resource::<Diagnostics>().resource::<Time>();
+    }
 }
 
 impl FrameTimeDiagnosticsPlugin {
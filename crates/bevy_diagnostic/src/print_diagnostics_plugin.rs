@@ -43,6 +43,10 @@ impl Plugin for PrintDiagnosticsPlugin {
             app.add_system_to_stage(stage::POST_UPDATE, Self::print_diagnostics_system.system());
         }
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.resource::<Diagnostics>().resource::<Time>();
+    }
 }
 
 impl PrintDiagnosticsPlugin {
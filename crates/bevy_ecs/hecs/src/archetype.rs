@@ -29,7 +29,9 @@ use core::{
 
 use hashbrown::HashMap;
 
-use crate::{borrow::AtomicBorrow, query::Fetch, Access, Component, Query};
+use crate::{
+    borrow::AtomicBorrow, query::Fetch, sparse_set::SparseSet, Access, Component, Entity, Query,
+};
 
 /// A collection of entities having the same component types
 ///
@@ -37,14 +39,16 @@ use crate::{borrow::AtomicBorrow, query::Fetch, Access, Component, Query};
 /// go through the `World`.
 pub struct Archetype {
     types: Vec<TypeInfo>,
-    state: HashMap<TypeId, TypeState>,
+    state: HashMap<ComponentId, TypeState>,
     len: u32,
-    entities: Box<[u32]>,
+    entities: Box<[Entity]>,
     // UnsafeCell allows unique references into `data` to be constructed while shared references
     // containing the `Archetype` exist
     data: UnsafeCell<NonNull<u8>>,
     data_size: usize,
     grow_size: u32,
+    // The world's sparse sets, in which any entity of this archetype may have a component
+    sparse_sets: HashMap<ComponentId, SparseSetState>,
 }
 
 impl Archetype {
@@ -71,9 +75,24 @@ impl Archetype {
             data: UnsafeCell::new(NonNull::dangling()),
             data_size: 0,
             grow_size,
+            sparse_sets: HashMap::default(),
         }
     }
 
+    pub(crate) fn add_sparse_set(&mut self, ty: ComponentId, set: NonNull<SparseSet>) {
+        self.sparse_sets.insert(
+            ty,
+            SparseSetState {
+                set,
+                borrow: AtomicBorrow::new(),
+            },
+        );
+    }
+
+    pub(crate) fn remove_sparse_set(&mut self, ty: ComponentId) {
+        self.sparse_sets.remove(&ty);
+    }
+
     pub(crate) fn clear(&mut self) {
         for ty in &self.types {
             for index in 0..self.len {
@@ -89,98 +108,110 @@ impl Archetype {
         self.len = 0;
     }
 
-    #[allow(missing_docs)]
+    /// Whether entities of this archetype can have a `T` component
+    ///
+    /// Components stored in sparse sets can be had by any archetype, so only some of its entities
+    /// may actually have one.
     #[inline]
     pub fn has<T: Component>(&self) -> bool {
-        self.has_dynamic(TypeId::of::<T>())
+        self.can_have(ComponentId::of::<T>())
+    }
+
+    /// Like `has`, for a component identified by `id`
+    #[inline]
+    pub fn can_have(&self, id: ComponentId) -> bool {
+        self.has_dynamic(id) || self.sparse_sets.contains_key(&id)
+    }
+
+    /// The size of the component `id`, if entities of this archetype can have it
+    pub(crate) fn component_size(&self, id: ComponentId) -> Option<usize> {
+        match self.types.iter().find(|ty| ty.id == id) {
+            Some(ty) => Some(ty.layout.size()),
+            None => self
+                .sparse_sets
+                .get(&id)
+                .map(|state| unsafe { state.set.as_ref() }.type_info().layout().size()),
+        }
     }
 
-    pub(crate) fn has_dynamic(&self, id: TypeId) -> bool {
+    /// Whether `id` is stored in this archetype's table, meaning every entity of the archetype has it
+    pub(crate) fn has_dynamic(&self, id: ComponentId) -> bool {
         self.state.contains_key(&id)
     }
 
-    #[allow(missing_docs)]
+    /// A cursor over the `T` components of this archetype's entities, starting at `offset`
+    ///
+    /// # Safety
+    /// `offset` must be in bounds
     #[inline]
-    pub fn get<T: Component>(&self) -> Option<NonNull<T>> {
-        let state = self.state.get(&TypeId::of::<T>())?;
-        Some(unsafe {
-            NonNull::new_unchecked(
-                (*self.data.get()).as_ptr().add(state.offset).cast::<T>() as *mut T
-            )
-        })
+    pub(crate) unsafe fn column<T: Component>(&self, offset: usize) -> Option<Column<T>> {
+        if let Some((components, ticks)) = self.get_with_ticks::<T>() {
+            Some(Column::Table {
+                components: NonNull::new_unchecked(components.as_ptr().add(offset)),
+                ticks: NonNull::new_unchecked(ticks.as_ptr().add(offset)),
+            })
+        } else {
+            let state = self.sparse_sets.get(&ComponentId::of::<T>())?;
+            Some(Column::Sparse {
+                entities: NonNull::new_unchecked(self.entities().as_ptr().add(offset)),
+                set: state.set,
+            })
+        }
     }
 
     #[allow(missing_docs)]
     #[inline]
-    pub fn get_with_added<T: Component>(&self) -> Option<(NonNull<T>, NonNull<bool>)> {
-        let state = self.state.get(&TypeId::of::<T>())?;
+    pub fn get<T: Component>(&self) -> Option<NonNull<T>> {
+        let state = self.state.get(&ComponentId::of::<T>())?;
         Some(unsafe {
-            (
-                NonNull::new_unchecked(
-                    (*self.data.get()).as_ptr().add(state.offset).cast::<T>() as *mut T
-                ),
-                NonNull::new_unchecked(state.added_entities.as_ptr() as *mut bool),
+            NonNull::new_unchecked(
+                (*self.data.get()).as_ptr().add(state.offset).cast::<T>() as *mut T
             )
         })
     }
 
     #[allow(missing_docs)]
     #[inline]
-    pub fn get_with_mutated<T: Component>(&self) -> Option<(NonNull<T>, NonNull<bool>)> {
-        let state = self.state.get(&TypeId::of::<T>())?;
+    pub fn get_with_ticks<T: Component>(&self) -> Option<(NonNull<T>, NonNull<ComponentTicks>)> {
+        let state = self.state.get(&ComponentId::of::<T>())?;
         Some(unsafe {
             (
                 NonNull::new_unchecked(
                     (*self.data.get()).as_ptr().add(state.offset).cast::<T>() as *mut T
                 ),
-                NonNull::new_unchecked(state.mutated_entities.as_ptr() as *mut bool),
+                NonNull::new_unchecked(state.component_ticks.as_ptr() as *mut ComponentTicks),
             )
         })
     }
 
     #[allow(missing_docs)]
     #[inline]
-    pub fn get_with_added_and_mutated<T: Component>(
-        &self,
-    ) -> Option<(NonNull<T>, NonNull<bool>, NonNull<bool>)> {
-        let state = self.state.get(&TypeId::of::<T>())?;
+    pub fn get_ticks<T: Component>(&self) -> Option<NonNull<ComponentTicks>> {
+        let state = self.state.get(&ComponentId::of::<T>())?;
         Some(unsafe {
-            (
-                NonNull::new_unchecked(
-                    (*self.data.get()).as_ptr().add(state.offset).cast::<T>() as *mut T
-                ),
-                NonNull::new_unchecked(state.added_entities.as_ptr() as *mut bool),
-                NonNull::new_unchecked(state.mutated_entities.as_ptr() as *mut bool),
-            )
+            NonNull::new_unchecked(state.component_ticks.as_ptr() as *mut ComponentTicks)
         })
     }
 
     #[allow(missing_docs)]
-    #[inline]
-    pub fn get_mutated<T: Component>(&self) -> Option<NonNull<bool>> {
-        let state = self.state.get(&TypeId::of::<T>())?;
-        Some(unsafe { NonNull::new_unchecked(state.mutated_entities.as_ptr() as *mut bool) })
-    }
-
-    #[allow(missing_docs)]
-    #[inline]
-    pub fn get_added<T: Component>(&self) -> Option<NonNull<bool>> {
-        let state = self.state.get(&TypeId::of::<T>())?;
-        Some(unsafe { NonNull::new_unchecked(state.added_entities.as_ptr() as *mut bool) })
+    pub fn get_type_state_mut(&mut self, ty: ComponentId) -> Option<&mut TypeState> {
+        self.state.get_mut(&ty)
     }
 
-    #[allow(missing_docs)]
-    pub fn get_type_state_mut(&mut self, ty: TypeId) -> Option<&mut TypeState> {
-        self.state.get_mut(&ty)
+    /// The borrow state of `id`, whether it is stored in this archetype or in a sparse set
+    fn borrow_state(&self, id: ComponentId) -> Option<&AtomicBorrow> {
+        match self.state.get(&id) {
+            Some(state) => Some(&state.borrow),
+            None => self.sparse_sets.get(&id).map(|state| &state.borrow),
+        }
     }
 
     #[allow(missing_docs)]
     #[inline]
     pub fn borrow<T: Component>(&self) {
         if self
-            .state
-            .get(&TypeId::of::<T>())
-            .map_or(false, |x| !x.borrow.borrow())
+            .borrow_state(ComponentId::of::<T>())
+            .map_or(false, |x| !x.borrow())
         {
             panic!("{} already borrowed uniquely", type_name::<T>());
         }
@@ -190,9 +221,8 @@ impl Archetype {
     #[inline]
     pub fn borrow_mut<T: Component>(&self) {
         if self
-            .state
-            .get(&TypeId::of::<T>())
-            .map_or(false, |x| !x.borrow.borrow_mut())
+            .borrow_state(ComponentId::of::<T>())
+            .map_or(false, |x| !x.borrow_mut())
         {
             panic!("{} already borrowed", type_name::<T>());
         }
@@ -201,16 +231,40 @@ impl Archetype {
     #[allow(missing_docs)]
     #[inline]
     pub fn release<T: Component>(&self) {
-        if let Some(x) = self.state.get(&TypeId::of::<T>()) {
-            x.borrow.release();
-        }
+        self.release_dynamic(ComponentId::of::<T>());
     }
 
     #[allow(missing_docs)]
     #[inline]
     pub fn release_mut<T: Component>(&self) {
-        if let Some(x) = self.state.get(&TypeId::of::<T>()) {
-            x.borrow.release_mut();
+        self.release_mut_dynamic(ComponentId::of::<T>());
+    }
+
+    #[allow(missing_docs)]
+    pub fn borrow_dynamic(&self, id: ComponentId) {
+        if self.borrow_state(id).map_or(false, |x| !x.borrow()) {
+            panic!("{:?} already borrowed uniquely", id);
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn borrow_mut_dynamic(&self, id: ComponentId) {
+        if self.borrow_state(id).map_or(false, |x| !x.borrow_mut()) {
+            panic!("{:?} already borrowed", id);
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn release_dynamic(&self, id: ComponentId) {
+        if let Some(x) = self.borrow_state(id) {
+            x.release();
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub fn release_mut_dynamic(&self, id: ComponentId) {
+        if let Some(x) = self.borrow_state(id) {
+            x.release_mut();
         }
     }
 
@@ -221,16 +275,16 @@ impl Archetype {
     }
 
     #[allow(missing_docs)]
-    pub fn iter_entities(&self) -> impl Iterator<Item = &u32> {
+    pub fn iter_entities(&self) -> impl Iterator<Item = &Entity> {
         self.entities.iter().take(self.len as usize)
     }
 
     #[inline]
-    pub(crate) fn entities(&self) -> NonNull<u32> {
+    pub(crate) fn entities(&self) -> NonNull<Entity> {
         unsafe { NonNull::new_unchecked(self.entities.as_ptr() as *mut _) }
     }
 
-    pub(crate) fn entity_id(&self, index: u32) -> u32 {
+    pub(crate) fn get_entity(&self, index: u32) -> Entity {
         self.entities[index as usize]
     }
 
@@ -242,7 +296,7 @@ impl Archetype {
     /// `index` must be in-bounds
     pub(crate) unsafe fn get_dynamic(
         &self,
-        ty: TypeId,
+        ty: ComponentId,
         size: usize,
         index: u32,
     ) -> Option<NonNull<u8>> {
@@ -255,13 +309,53 @@ impl Archetype {
         ))
     }
 
+    /// The `ty` components of every entity, and their ticks
+    pub(crate) fn column_dynamic(
+        &self,
+        ty: ComponentId,
+    ) -> Option<(NonNull<u8>, &[ComponentTicks])> {
+        let state = self.state.get(&ty)?;
+        Some(unsafe {
+            (
+                NonNull::new_unchecked((*self.data.get()).as_ptr().add(state.offset)),
+                &state.component_ticks[..self.len as usize],
+            )
+        })
+    }
+
+    /// The component `ty`, of the given size, and its ticks for the entity at `index`, whether the
+    /// component is stored in this archetype or in a sparse set
+    ///
+    /// `index` must be in-bounds
+    pub(crate) unsafe fn get_dynamic_with_ticks(
+        &self,
+        ty: ComponentId,
+        size: usize,
+        index: u32,
+    ) -> Option<(NonNull<u8>, NonNull<ComponentTicks>)> {
+        match self.state.get(&ty) {
+            Some(state) => Some((
+                self.get_dynamic(ty, size, index)?,
+                NonNull::new_unchecked(
+                    state.component_ticks.as_ptr().add(index as usize) as *mut ComponentTicks
+                ),
+            )),
+            None => self
+                .sparse_sets
+                .get(&ty)?
+                .set
+                .as_ref()
+                .get(self.get_entity(index)),
+        }
+    }
+
     /// Every type must be written immediately after this call
-    pub unsafe fn allocate(&mut self, id: u32) -> u32 {
+    pub unsafe fn allocate(&mut self, entity: Entity) -> u32 {
         if self.len as usize == self.entities.len() {
             self.grow(self.len.max(self.grow_size));
         }
 
-        self.entities[self.len as usize] = id;
+        self.entities[self.len as usize] = entity;
         self.len += 1;
         self.len - 1
     }
@@ -276,10 +370,13 @@ impl Archetype {
         self.entities.len() as u32
     }
 
-    #[allow(missing_docs)]
-    pub fn clear_trackers(&mut self) {
+    /// Clamps component ticks that are too old to be compared against `change_tick`. See
+    /// `World::check_change_ticks`.
+    pub fn check_change_ticks(&mut self, change_tick: u32) {
         for type_state in self.state.values_mut() {
-            type_state.clear_trackers();
+            for ticks in type_state.component_ticks[..self.len as usize].iter_mut() {
+                ticks.check_ticks(change_tick);
+            }
         }
     }
 
@@ -287,13 +384,14 @@ impl Archetype {
         unsafe {
             let old_count = self.len as usize;
             let count = old_count + increment as usize;
-            let mut new_entities = vec![!0; count].into_boxed_slice();
+            let mut new_entities = vec![Entity::from_bits(!0); count].into_boxed_slice();
             new_entities[0..old_count].copy_from_slice(&self.entities[0..old_count]);
             self.entities = new_entities;
 
             for type_state in self.state.values_mut() {
-                type_state.mutated_entities.resize_with(count, || false);
-                type_state.added_entities.resize_with(count, || false);
+                type_state
+                    .component_ticks
+                    .resize_with(count, ComponentTicks::default);
             }
 
             let old_data_size = mem::replace(&mut self.data_size, 0);
@@ -333,8 +431,8 @@ impl Archetype {
         }
     }
 
-    /// Returns the ID of the entity moved into `index`, if any
-    pub(crate) unsafe fn remove(&mut self, index: u32) -> Option<u32> {
+    /// Returns the entity moved into `index`, if any
+    pub(crate) unsafe fn remove(&mut self, index: u32) -> Option<Entity> {
         let last = self.len - 1;
         for ty in &self.types {
             let removed = self
@@ -343,7 +441,6 @@ impl Archetype {
                 .as_ptr();
             (ty.drop)(removed);
             if index != last {
-                // TODO: copy component tracker state here
                 ptr::copy_nonoverlapping(
                     self.get_dynamic(ty.id, ty.layout.size(), last)
                         .unwrap()
@@ -353,10 +450,8 @@ impl Archetype {
                 );
 
                 let type_state = self.state.get_mut(&ty.id).unwrap();
-                type_state.mutated_entities[index as usize] =
-                    type_state.mutated_entities[last as usize];
-                type_state.added_entities[index as usize] =
-                    type_state.added_entities[last as usize];
+                type_state.component_ticks[index as usize] =
+                    type_state.component_ticks[last as usize];
             }
         }
         self.len = last;
@@ -368,22 +463,20 @@ impl Archetype {
         }
     }
 
-    /// Returns the ID of the entity moved into `index`, if any
+    /// Returns the entity moved into `index`, if any
     pub(crate) unsafe fn move_to(
         &mut self,
         index: u32,
-        mut f: impl FnMut(*mut u8, TypeId, usize, bool, bool),
-    ) -> Option<u32> {
+        mut f: impl FnMut(*mut u8, ComponentId, usize, ComponentTicks),
+    ) -> Option<Entity> {
         let last = self.len - 1;
         for ty in &self.types {
             let moved = self
                 .get_dynamic(ty.id, ty.layout.size(), index)
                 .unwrap()
                 .as_ptr();
-            let type_state = self.state.get(&ty.id).unwrap();
-            let is_added = type_state.added_entities[index as usize];
-            let is_mutated = type_state.mutated_entities[index as usize];
-            f(moved, ty.id(), ty.layout().size(), is_added, is_mutated);
+            let ticks = self.state.get(&ty.id).unwrap().component_ticks[index as usize];
+            f(moved, ty.id(), ty.layout().size(), ticks);
             if index != last {
                 ptr::copy_nonoverlapping(
                     self.get_dynamic(ty.id, ty.layout.size(), last)
@@ -393,10 +486,8 @@ impl Archetype {
                     ty.layout.size(),
                 );
                 let type_state = self.state.get_mut(&ty.id).unwrap();
-                type_state.added_entities[index as usize] =
-                    type_state.added_entities[last as usize];
-                type_state.mutated_entities[index as usize] =
-                    type_state.mutated_entities[last as usize];
+                type_state.component_ticks[index as usize] =
+                    type_state.component_ticks[last as usize];
             }
         }
         self.len -= 1;
@@ -408,18 +499,19 @@ impl Archetype {
         }
     }
 
-    #[allow(missing_docs)]
+    /// Writes a component to `index`. If `added_tick` is set, the component is tracked as newly
+    /// added at that tick, otherwise its ticks are left unchanged.
     pub unsafe fn put_dynamic(
         &mut self,
         component: *mut u8,
-        ty: TypeId,
+        ty: ComponentId,
         size: usize,
         index: u32,
-        added: bool,
+        added_tick: Option<u32>,
     ) {
         let state = self.state.get_mut(&ty).unwrap();
-        if added {
-            state.added_entities[index as usize] = true;
+        if let Some(added_tick) = added_tick {
+            state.component_ticks[index as usize] = ComponentTicks::new(added_tick);
         }
         let ptr = (*self.data.get())
             .as_ptr()
@@ -454,8 +546,7 @@ impl Drop for Archetype {
 pub struct TypeState {
     offset: usize,
     borrow: AtomicBorrow,
-    pub mutated_entities: Vec<bool>,
-    pub added_entities: Vec<bool>,
+    pub component_ticks: Vec<ComponentTicks>,
 }
 
 impl TypeState {
@@ -463,26 +554,194 @@ impl TypeState {
         Self {
             offset: 0,
             borrow: AtomicBorrow::new(),
-            mutated_entities: Vec::new(),
-            added_entities: Vec::new(),
+            component_ticks: Vec::new(),
         }
     }
+}
+
+/// A sparse set shared with an archetype, borrowed separately for the archetype's entities
+struct SparseSetState {
+    set: NonNull<SparseSet>,
+    borrow: AtomicBorrow,
+}
+
+/// A cursor over the components of a type for consecutive entities of an archetype
+pub(crate) enum Column<T> {
+    /// Components stored in the archetype, which every entity has
+    Table {
+        components: NonNull<T>,
+        ticks: NonNull<ComponentTicks>,
+    },
+    /// Components stored in a sparse set, which entities may not have
+    Sparse {
+        entities: NonNull<Entity>,
+        set: NonNull<SparseSet>,
+    },
+}
+
+impl<T> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Column<T> {}
 
-    fn clear_trackers(&mut self) {
-        for mutated in self.mutated_entities.iter_mut() {
-            *mutated = false;
+impl<T: Component> Column<T> {
+    /// The component and ticks of the entity `offset` entities after the cursor, or `None` if that
+    /// entity doesn't have a component
+    ///
+    /// # Safety
+    /// `offset` must be in bounds of the archetype
+    #[inline]
+    pub unsafe fn get(&self, offset: usize) -> Option<(NonNull<T>, NonNull<ComponentTicks>)> {
+        match *self {
+            Column::Table { components, ticks } => Some((
+                NonNull::new_unchecked(components.as_ptr().add(offset)),
+                NonNull::new_unchecked(ticks.as_ptr().add(offset)),
+            )),
+            Column::Sparse { entities, set } => set
+                .as_ref()
+                .get(*entities.as_ptr().add(offset))
+                .map(|(component, ticks)| (component.cast::<T>(), ticks)),
         }
+    }
+
+    /// The component and ticks of the entity at the cursor, which must have a component
+    ///
+    /// # Safety
+    /// The cursor must be in bounds of the archetype
+    #[inline]
+    pub unsafe fn current(&self) -> (NonNull<T>, NonNull<ComponentTicks>) {
+        match self.get(0) {
+            Some(current) => current,
+            None => panic!("{} is missing", type_name::<T>()),
+        }
+    }
+
+    /// Moves the cursor to the next entity
+    ///
+    /// # Safety
+    /// The cursor must not move further than one past the end of the archetype
+    #[inline]
+    pub unsafe fn advance(&mut self) {
+        match self {
+            Column::Table { components, ticks } => {
+                *components = NonNull::new_unchecked(components.as_ptr().add(1));
+                *ticks = NonNull::new_unchecked(ticks.as_ptr().add(1));
+            }
+            Column::Sparse { entities, .. } => {
+                *entities = NonNull::new_unchecked(entities.as_ptr().add(1));
+            }
+        }
+    }
+
+    /// Whether the components are stored in a sparse set
+    #[inline]
+    pub fn is_sparse(&self) -> bool {
+        match self {
+            Column::Table { .. } => false,
+            Column::Sparse { .. } => true,
+        }
+    }
+}
+
+/// How many ticks may pass between calls to `World::check_change_ticks` before ticks risk
+/// wrapping around
+pub const CHECK_TICK_THRESHOLD: u32 = 518_400_000;
+
+/// The maximum age of a change tick. Older ticks are clamped to this age, so changes older than
+/// this may be missed.
+pub const MAX_CHANGE_AGE: u32 = u32::MAX - (2 * CHECK_TICK_THRESHOLD - 1);
 
-        for added in self.added_entities.iter_mut() {
-            *added = false;
+/// The `World` change ticks at which a component was added and last mutated
+///
+/// Ticks are compared relative to the current change tick, so they stay meaningful when the
+/// change tick wraps around, as long as they are kept within `MAX_CHANGE_AGE`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ComponentTicks {
+    #[allow(missing_docs)]
+    pub added: u32,
+    #[allow(missing_docs)]
+    pub mutated: u32,
+}
+
+impl ComponentTicks {
+    /// Ticks for a component added at `change_tick` that has not been mutated since
+    pub fn new(change_tick: u32) -> Self {
+        Self {
+            added: change_tick,
+            // a mutated tick of `change_tick - MAX_CHANGE_AGE` is older than any system
+            mutated: change_tick.wrapping_sub(MAX_CHANGE_AGE),
         }
     }
+
+    /// Whether the component was added after `last_change_tick`
+    #[inline]
+    pub fn is_added(&self, last_change_tick: u32, change_tick: u32) -> bool {
+        is_tick_newer(self.added, last_change_tick, change_tick)
+    }
+
+    /// Whether the component was mutated after `last_change_tick`
+    #[inline]
+    pub fn is_mutated(&self, last_change_tick: u32, change_tick: u32) -> bool {
+        is_tick_newer(self.mutated, last_change_tick, change_tick)
+    }
+
+    pub(crate) fn check_ticks(&mut self, change_tick: u32) {
+        check_tick(&mut self.added, change_tick);
+        check_tick(&mut self.mutated, change_tick);
+    }
+}
+
+/// Whether `tick` happened after `last_change_tick`, where both are at most `change_tick`
+#[inline]
+fn is_tick_newer(tick: u32, last_change_tick: u32, change_tick: u32) -> bool {
+    let ticks_since_change = change_tick.wrapping_sub(tick);
+    let ticks_since_last = change_tick.wrapping_sub(last_change_tick);
+    ticks_since_last > ticks_since_change
+}
+
+/// Clamps `tick` so that it is at most `MAX_CHANGE_AGE` ticks older than `change_tick`
+///
+/// Systems call this on the tick they last ran at, so that systems which have not run for a long
+/// time do not mistake old changes for new ones.
+pub fn check_tick(tick: &mut u32, change_tick: u32) {
+    if change_tick.wrapping_sub(*tick) > MAX_CHANGE_AGE {
+        *tick = change_tick.wrapping_sub(MAX_CHANGE_AGE);
+    }
+}
+
+/// Identifies a component type, either a Rust type or one defined at runtime
+///
+/// Runtime component types are registered with `World::register_component`, which assigns them an
+/// `ExternalId`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ComponentId {
+    /// A Rust type
+    RustTypeId(TypeId),
+    /// A component type defined at runtime
+    ExternalId(u64),
+}
+
+impl ComponentId {
+    /// The id of the Rust type `T`
+    #[inline]
+    pub fn of<T: 'static>() -> Self {
+        ComponentId::RustTypeId(TypeId::of::<T>())
+    }
+}
+
+impl From<TypeId> for ComponentId {
+    fn from(id: TypeId) -> Self {
+        ComponentId::RustTypeId(id)
+    }
 }
 
 /// Metadata required to store a component
 #[derive(Debug, Copy, Clone)]
 pub struct TypeInfo {
-    id: TypeId,
+    id: ComponentId,
     layout: Layout,
     drop: unsafe fn(*mut u8),
 }
@@ -495,15 +754,26 @@ impl TypeInfo {
         }
 
         Self {
-            id: TypeId::of::<T>(),
+            id: ComponentId::of::<T>(),
             layout: Layout::new::<T>(),
             drop: drop_ptr::<T>,
         }
     }
 
+    /// Metadata for a component type defined at runtime, whose values are dropped in place by `drop`
+    ///
+    /// Only created by `World::register_component`, which assigns a unique id to each descriptor.
+    pub(crate) fn external(id: u64, layout: Layout, drop: unsafe fn(*mut u8)) -> Self {
+        Self {
+            id: ComponentId::ExternalId(id),
+            layout,
+            drop,
+        }
+    }
+
     #[allow(missing_docs)]
     #[inline]
-    pub fn id(&self) -> TypeId {
+    pub fn id(&self) -> ComponentId {
         self.id
     }
 
@@ -525,7 +795,7 @@ impl PartialOrd for TypeInfo {
 }
 
 impl Ord for TypeInfo {
-    /// Order by alignment, descending. Ties broken with TypeId.
+    /// Order by alignment, descending. Ties broken with ComponentId.
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.layout
             .align()
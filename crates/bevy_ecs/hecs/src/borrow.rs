@@ -21,7 +21,10 @@ use core::{
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use crate::{archetype::Archetype, Component, MissingComponent};
+use crate::{
+    archetype::{Archetype, ComponentTicks},
+    Component, Entity, MissingComponent,
+};
 
 pub struct AtomicBorrow(AtomicUsize);
 
@@ -74,13 +77,10 @@ pub struct Ref<'a, T: Component> {
 impl<'a, T: Component> Ref<'a, T> {
     #[allow(missing_docs)]
     pub unsafe fn new(archetype: &'a Archetype, index: u32) -> Result<Self, MissingComponent> {
-        let target = NonNull::new_unchecked(
-            archetype
-                .get::<T>()
-                .ok_or_else(MissingComponent::new::<T>)?
-                .as_ptr()
-                .add(index as usize),
-        );
+        let (target, _) = archetype
+            .column::<T>(index as usize)
+            .and_then(|column| column.get(0))
+            .ok_or_else(MissingComponent::new::<T>)?;
         archetype.borrow::<T>();
         Ok(Self { archetype, target })
     }
@@ -116,29 +116,27 @@ where
 pub struct RefMut<'a, T: Component> {
     archetype: &'a Archetype,
     target: NonNull<T>,
-    modified: &'a mut bool,
+    ticks: &'a mut ComponentTicks,
+    change_tick: u32,
 }
 
 impl<'a, T: Component> RefMut<'a, T> {
-    #[allow(missing_docs)]
-    pub unsafe fn new(archetype: &'a Archetype, index: u32) -> Result<Self, MissingComponent> {
-        let target = NonNull::new_unchecked(
-            archetype
-                .get::<T>()
-                .ok_or_else(MissingComponent::new::<T>)?
-                .as_ptr()
-                .add(index as usize),
-        );
+    /// Mutations through the returned reference are recorded at `change_tick`
+    pub unsafe fn new(
+        archetype: &'a Archetype,
+        index: u32,
+        change_tick: u32,
+    ) -> Result<Self, MissingComponent> {
+        let (target, ticks) = archetype
+            .column::<T>(index as usize)
+            .and_then(|column| column.get(0))
+            .ok_or_else(MissingComponent::new::<T>)?;
         archetype.borrow_mut::<T>();
-        let modified = archetype
-            .get_mutated::<T>()
-            .unwrap()
-            .as_ptr()
-            .add(index as usize);
         Ok(Self {
             archetype,
             target,
-            modified: &mut *modified,
+            ticks: &mut *ticks.as_ptr(),
+            change_tick,
         })
     }
 }
@@ -162,7 +160,7 @@ impl<'a, T: Component> Deref for RefMut<'a, T> {
 
 impl<'a, T: Component> DerefMut for RefMut<'a, T> {
     fn deref_mut(&mut self) -> &mut T {
-        *self.modified = true;
+        self.ticks.mutated = self.change_tick;
         unsafe { self.target.as_mut() }
     }
 }
@@ -179,24 +177,23 @@ where
 /// Handle to an entity with any component types
 #[derive(Copy, Clone)]
 pub struct EntityRef<'a> {
-    archetype: Option<&'a Archetype>,
+    archetype: &'a Archetype,
     index: u32,
+    change_tick: u32,
 }
 
 impl<'a> EntityRef<'a> {
-    /// Construct a `Ref` for an entity with no components
-    pub(crate) fn empty() -> Self {
+    pub(crate) unsafe fn new(archetype: &'a Archetype, index: u32, change_tick: u32) -> Self {
         Self {
-            archetype: None,
-            index: 0,
+            archetype,
+            index,
+            change_tick,
         }
     }
 
-    pub(crate) unsafe fn new(archetype: &'a Archetype, index: u32) -> Self {
-        Self {
-            archetype: Some(archetype),
-            index,
-        }
+    /// The entity this handle refers to
+    pub fn entity(&self) -> Entity {
+        unsafe { *self.archetype.entities().as_ptr().add(self.index as usize) }
     }
 
     /// Borrow the component of type `T`, if it exists
@@ -204,14 +201,14 @@ impl<'a> EntityRef<'a> {
     /// Panics if the component is already uniquely borrowed from another entity with the same
     /// components.
     pub fn get<T: Component>(&self) -> Option<Ref<'a, T>> {
-        Some(unsafe { Ref::new(self.archetype?, self.index).ok()? })
+        Some(unsafe { Ref::new(self.archetype, self.index).ok()? })
     }
 
     /// Uniquely borrow the component of type `T`, if it exists
     ///
     /// Panics if the component is already borrowed from another entity with the same components.
     pub fn get_mut<T: Component>(&self) -> Option<RefMut<'a, T>> {
-        Some(unsafe { RefMut::new(self.archetype?, self.index).ok()? })
+        Some(unsafe { RefMut::new(self.archetype, self.index, self.change_tick).ok()? })
     }
 }
 
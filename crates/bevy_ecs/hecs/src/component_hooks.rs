@@ -0,0 +1,70 @@
+// modified by Bevy contributors
+
+use crate::{
+    alloc::{sync::Arc, vec::Vec},
+    Entity, World,
+};
+
+/// A callback that runs when a component changes on an entity
+pub type ComponentHook = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+/// Callbacks that run when components of a given type are added to, inserted on, or removed from
+/// an entity
+///
+/// Obtained from `World::component_hooks`. Hooks run for every way of changing components,
+/// including spawning, batch spawning, inserting, removing, despawning and clearing the world.
+///
+/// # Example
+/// ```
+/// # use bevy_hecs::*;
+/// struct Name(&'static str);
+///
+/// let mut world = World::new();
+/// world
+///     .component_hooks::<Name>()
+///     .on_add(|world, entity| {
+///         let name = world.get::<Name>(entity).unwrap();
+///         assert_eq!(name.0, "player");
+///     });
+/// world.spawn((Name("player"),));
+/// ```
+#[derive(Default, Clone)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Vec<ComponentHook>,
+    pub(crate) on_insert: Vec<ComponentHook>,
+    pub(crate) on_remove: Vec<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// Runs `hook` after the component is added to an entity that did not have it
+    pub fn on_add(
+        &mut self,
+        hook: impl Fn(&mut World, Entity) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_add.push(Arc::new(hook));
+        self
+    }
+
+    /// Runs `hook` after the component is added to an entity or replaced with a new value. When the
+    /// component is added, `on_add` hooks run first.
+    pub fn on_insert(
+        &mut self,
+        hook: impl Fn(&mut World, Entity) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_insert.push(Arc::new(hook));
+        self
+    }
+
+    /// Runs `hook` before the component is removed from an entity, including when the entity is
+    /// despawned, so the component can still be read
+    ///
+    /// Hooks should not remove the component or despawn the entity themselves, as that makes the
+    /// removal that triggered them fail.
+    pub fn on_remove(
+        &mut self,
+        hook: impl Fn(&mut World, Entity) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_remove.push(Arc::new(hook));
+        self
+    }
+}
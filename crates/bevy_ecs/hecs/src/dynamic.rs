@@ -0,0 +1,187 @@
+// modified by Bevy contributors
+
+use crate::{
+    alloc::{alloc::Layout, string::String, vec::Vec},
+    archetype::{Archetype, ComponentId},
+    Entity,
+};
+use core::slice;
+
+/// Describes a component type defined at runtime, such as by a scripting language
+///
+/// Registering a descriptor with `World::register_component` yields the `TypeInfo` used to add
+/// components of the type with `EntityBuilder::add_dynamic`.
+#[derive(Debug, Clone)]
+pub struct ComponentDescriptor {
+    name: String,
+    layout: Layout,
+    drop: Option<unsafe fn(*mut u8)>,
+}
+
+impl ComponentDescriptor {
+    /// A component type called `name` whose values have `layout` and need no dropping
+    pub fn new(name: impl Into<String>, layout: Layout) -> Self {
+        Self {
+            name: name.into(),
+            layout,
+            drop: None,
+        }
+    }
+
+    /// Drop values of this type in place with `drop`
+    ///
+    /// # Safety
+    /// `drop` must be sound to call on a pointer to any value of this type, aligned to its layout
+    pub unsafe fn with_drop(mut self, drop: unsafe fn(*mut u8)) -> Self {
+        self.drop = Some(drop);
+        self
+    }
+
+    #[allow(missing_docs)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[allow(missing_docs)]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub(crate) fn drop_fn(&self) -> unsafe fn(*mut u8) {
+        unsafe fn drop_nothing(_: *mut u8) {}
+        self.drop.unwrap_or(drop_nothing)
+    }
+}
+
+/// A query for components identified by `ComponentId`, for use with `World::query_dynamic`
+///
+/// Matches the entities that have every component read or written by the query.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicQuery {
+    read: Vec<ComponentId>,
+    write: Vec<ComponentId>,
+}
+
+impl DynamicQuery {
+    /// A query that matches every entity
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the component `id`. Components are returned in the order they were added.
+    pub fn read(mut self, id: ComponentId) -> Self {
+        self.read.push(id);
+        self
+    }
+
+    /// Write the component `id`, marking it mutated. Components are returned in the order they
+    /// were added.
+    pub fn write(mut self, id: ComponentId) -> Self {
+        self.write.push(id);
+        self
+    }
+
+    fn matches(&self, archetype: &Archetype) -> bool {
+        self.read
+            .iter()
+            .chain(self.write.iter())
+            .all(|&id| archetype.can_have(id))
+    }
+}
+
+/// The components of an entity matched by a `DynamicQuery`, as bytes
+pub struct DynamicItem<'a> {
+    #[allow(missing_docs)]
+    pub entity: Entity,
+    /// The components read by the query, in order
+    pub read: Vec<&'a [u8]>,
+    /// The components written by the query, in order
+    pub write: Vec<&'a mut [u8]>,
+}
+
+/// A borrow of a `World` sufficient to execute a `DynamicQuery`
+///
+/// Obtained from `World::query_dynamic`. Borrows the queried components when constructed, and
+/// releases them when dropped.
+pub struct DynamicQueryBorrow<'w> {
+    archetypes: &'w [Archetype],
+    query: DynamicQuery,
+    change_tick: u32,
+}
+
+impl<'w> DynamicQueryBorrow<'w> {
+    pub(crate) fn new(archetypes: &'w [Archetype], query: DynamicQuery, change_tick: u32) -> Self {
+        for archetype in archetypes.iter().filter(|x| query.matches(x)) {
+            for &id in &query.read {
+                archetype.borrow_dynamic(id);
+            }
+            for &id in &query.write {
+                archetype.borrow_mut_dynamic(id);
+            }
+        }
+        Self {
+            archetypes,
+            query,
+            change_tick,
+        }
+    }
+
+    /// Iterate over the matched entities
+    pub fn iter<'q>(&'q mut self) -> impl Iterator<Item = DynamicItem<'q>> + 'q {
+        let query = &self.query;
+        let change_tick = self.change_tick;
+        self.archetypes
+            .iter()
+            .filter(move |archetype| query.matches(archetype))
+            .flat_map(move |archetype| {
+                let read = query
+                    .read
+                    .iter()
+                    .map(|&id| (id, archetype.component_size(id).unwrap()))
+                    .collect::<Vec<_>>();
+                let write = query
+                    .write
+                    .iter()
+                    .map(|&id| (id, archetype.component_size(id).unwrap()))
+                    .collect::<Vec<_>>();
+                (0..archetype.len()).filter_map(move |index| unsafe {
+                    let mut item = DynamicItem {
+                        entity: archetype.get_entity(index),
+                        read: Vec::with_capacity(read.len()),
+                        write: Vec::with_capacity(write.len()),
+                    };
+                    for &(id, size) in &read {
+                        let (component, _) = archetype.get_dynamic_with_ticks(id, size, index)?;
+                        item.read
+                            .push(slice::from_raw_parts(component.as_ptr(), size));
+                    }
+                    let mut ticks = Vec::with_capacity(write.len());
+                    for &(id, size) in &write {
+                        let (component, component_ticks) =
+                            archetype.get_dynamic_with_ticks(id, size, index)?;
+                        item.write
+                            .push(slice::from_raw_parts_mut(component.as_ptr(), size));
+                        ticks.push(component_ticks);
+                    }
+                    // only mark components mutated once the entity is known to match
+                    for mut component_ticks in ticks {
+                        component_ticks.as_mut().mutated = change_tick;
+                    }
+                    Some(item)
+                })
+            })
+    }
+}
+
+impl Drop for DynamicQueryBorrow<'_> {
+    fn drop(&mut self) {
+        for archetype in self.archetypes.iter().filter(|x| self.query.matches(x)) {
+            for &id in &self.query.read {
+                archetype.release_dynamic(id);
+            }
+            for &id in &self.query.write {
+                archetype.release_mut_dynamic(id);
+            }
+        }
+    }
+}
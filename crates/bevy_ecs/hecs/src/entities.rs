@@ -1,26 +1,55 @@
 // modified by Bevy contributors
 
-use core::fmt;
-use hashbrown::HashMap;
+use crate::alloc::{sync::Arc, vec::Vec};
+use core::{
+    convert::TryFrom,
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
 #[cfg(feature = "std")]
 use std::error::Error;
 
 /// Lightweight unique ID of an entity
 ///
 /// Obtained from `World::spawn`. Can be stored to refer to an entity in the future.
+///
+/// Each entity is an index into the world's entity storage plus a generation. When an entity is
+/// despawned its index is reused by later spawns with an incremented generation, so handles to the
+/// despawned entity are rejected rather than aliasing the new one.
 #[derive(Debug, Clone, Copy, Hash, Eq, Ord, PartialEq, PartialOrd)]
-pub struct Entity(u32);
+pub struct Entity {
+    pub(crate) generation: u32,
+    pub(crate) id: u32,
+}
 
 impl Entity {
-    #[allow(missing_docs)]
-    pub fn new() -> Self {
-        Self(rand::random::<u32>())
-    }
-
-    #[allow(missing_docs)]
+    /// Creates an entity reference with the given id and a generation of 0
+    ///
+    /// Useful for reconstructing entities from ids that were stored without their generation, such
+    /// as those written by `Entity::id`.
     #[inline]
     pub fn from_id(id: u32) -> Self {
-        Self(id)
+        Self { id, generation: 0 }
+    }
+
+    /// Convert to a form convenient for passing outside of rust
+    ///
+    /// Only useful for identifying entities within the same instance of an application. Do not use
+    /// for serialization between runs.
+    ///
+    /// No particular structure is guaranteed for the returned bits.
+    pub fn to_bits(self) -> u64 {
+        u64::from(self.generation) << 32 | u64::from(self.id)
+    }
+
+    /// Reconstruct an `Entity` previously destructured with `to_bits`
+    ///
+    /// Only useful when applied to results from `to_bits` in the same instance of an application.
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            generation: (bits >> 32) as u32,
+            id: bits as u32,
+        }
     }
 
     /// Extract a transiently unique identifier
@@ -30,58 +59,311 @@ impl Entity {
     /// specific snapshot of the world, such as when serializing.
     #[inline]
     pub fn id(self) -> u32 {
-        self.0
+        self.id
+    }
+
+    /// The number of times this entity's id has been freed before it was allocated to this entity
+    #[inline]
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+/// Reserves entity ids from a `World` without borrowing it
+///
+/// Obtained from `World::get_entity_reserver`. Reserved entities are always fresh ids that have
+/// never been allocated before. They are added to the world as entities without components the
+/// next time it is flushed, which happens implicitly whenever the world is mutated.
+#[derive(Clone, Default)]
+pub struct EntityReserver {
+    // Every id below this value has been handed out, either by `Entities` or by a reserver
+    next_id: Arc<AtomicU32>,
+}
+
+impl EntityReserver {
+    /// Reserve an entity id that is guaranteed not to be in use by the world
+    pub fn reserve_entity(&self) -> Entity {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        assert_ne!(id, u32::MAX, "too many entities");
+        Entity { id, generation: 0 }
     }
 }
 
 #[derive(Default)]
 pub(crate) struct Entities {
-    pub entity_locations: HashMap<Entity, Location>,
+    pub meta: Vec<EntityMeta>,
+    // Ids that are free for reuse by `alloc`. While an id is free, the `index` of its location
+    // holds its position in this list.
+    pending: Vec<u32>,
+    // Ids below `meta.len()` that were reserved while `meta` grew, and still need to be flushed
+    reserved: Vec<u32>,
+    reserver: EntityReserver,
+    // Number of live (flushed) entities
+    len: u32,
 }
 
 impl Entities {
+    /// Allocate an entity ID. The location of the entity must be written immediately afterwards.
+    pub fn alloc(&mut self) -> Entity {
+        self.len += 1;
+        if let Some(id) = self.pending.pop() {
+            Entity {
+                generation: self.meta[id as usize].generation,
+                id,
+            }
+        } else {
+            let id = self.reserver.next_id.fetch_add(1, Ordering::Relaxed);
+            assert_ne!(id, u32::MAX, "too many entities");
+            // Any ids skipped over here were handed out by a reserver in the meantime
+            self.reserved.extend(self.meta.len() as u32..id);
+            self.meta.resize(id as usize + 1, EntityMeta::EMPTY);
+            Entity { generation: 0, id }
+        }
+    }
+
+    /// Allocate a specific entity ID, overwriting its generation
+    ///
+    /// Returns the location of the entity currently using the given ID, if any. The location of the
+    /// entity must be written immediately afterwards.
+    ///
+    /// Panics if the ID was handed out by an `EntityReserver` and has not been flushed yet.
+    pub fn alloc_at(&mut self, entity: Entity) -> Option<Location> {
+        let index = entity.id as usize;
+        let location = if index >= self.meta.len() {
+            let previous_next_id = self
+                .reserver
+                .next_id
+                .fetch_max(entity.id + 1, Ordering::Relaxed);
+            assert!(
+                entity.id >= previous_next_id,
+                "entity id {} is reserved and has not been flushed",
+                entity.id
+            );
+            let start = self.meta.len() as u32;
+            self.meta.resize(index + 1, EntityMeta::EMPTY);
+            for id in start..entity.id {
+                if id < previous_next_id {
+                    self.reserved.push(id);
+                } else {
+                    self.push_pending(id);
+                }
+            }
+            self.len += 1;
+            None
+        } else if self.meta[index].location.is_valid() {
+            Some(self.meta[index].location)
+        } else {
+            let pending = self.meta[index].location.index;
+            assert_ne!(
+                pending,
+                u32::MAX,
+                "entity id {} is reserved and has not been flushed",
+                entity.id
+            );
+            self.pending.swap_remove(pending as usize);
+            if let Some(&moved) = self.pending.get(pending as usize) {
+                self.meta[moved as usize].location.index = pending;
+            }
+            self.len += 1;
+            None
+        };
+        self.meta[index].generation = entity.generation;
+        location
+    }
+
+    fn push_pending(&mut self, id: u32) {
+        self.meta[id as usize].location.index = self.pending.len() as u32;
+        self.pending.push(id);
+    }
+
     /// Destroy an entity, allowing it to be reused
     ///
     /// Must not be called on reserved entities prior to `flush`.
     pub fn free(&mut self, entity: Entity) -> Result<Location, NoSuchEntity> {
-        if let Some(location) = self.entity_locations.remove(&entity) {
-            Ok(location)
-        } else {
-            Err(NoSuchEntity)
-        }
+        let location = self.get(entity)?;
+        let meta = &mut self.meta[entity.id as usize];
+        meta.generation = meta.generation.wrapping_add(1);
+        meta.location = EntityMeta::EMPTY.location;
+        self.push_pending(entity.id);
+        self.len -= 1;
+        Ok(location)
     }
 
-    /// Ensure `n` at least allocations can succeed without reallocating
+    /// Ensure at least `additional` allocations can succeed without reallocating
     pub fn reserve(&mut self, additional: u32) {
-        self.entity_locations.reserve(additional as usize)
+        let freelist = self.pending.len() as u32;
+        if additional > freelist {
+            self.meta.reserve((additional - freelist) as usize);
+        }
     }
 
+    /// Whether `entity` refers to a live entity. Reserved entities are not live until flushed.
     pub fn contains(&self, entity: Entity) -> bool {
-        self.entity_locations.contains_key(&entity)
+        self.get(entity).is_ok()
     }
 
+    /// Free every live entity, preserving generations so that existing handles stay invalid
     pub fn clear(&mut self) {
-        self.entity_locations.clear();
+        for (id, meta) in self.meta.iter_mut().enumerate() {
+            if meta.location.is_valid() {
+                meta.generation = meta.generation.wrapping_add(1);
+                meta.location = Location {
+                    index: self.pending.len() as u32,
+                    ..EntityMeta::EMPTY.location
+                };
+                self.pending.push(id as u32);
+            }
+        }
+        self.len = 0;
     }
 
     /// Access the location storage of an entity
     pub fn get_mut(&mut self, entity: Entity) -> Result<&mut Location, NoSuchEntity> {
-        self.entity_locations
-            .get_mut(&entity)
-            .ok_or_else(|| NoSuchEntity)
+        match self.meta.get_mut(entity.id as usize) {
+            Some(meta) if meta.generation == entity.generation && meta.location.is_valid() => {
+                Ok(&mut meta.location)
+            }
+            _ => Err(NoSuchEntity),
+        }
     }
 
-    /// Access the location storage of an entity
-    pub fn insert(&mut self, entity: Entity, location: Location) {
-        self.entity_locations.insert(entity, location);
+    /// Fails for reserved entities that have not been flushed yet
+    pub fn get(&self, entity: Entity) -> Result<Location, NoSuchEntity> {
+        match self.meta.get(entity.id as usize) {
+            Some(meta) if meta.generation == entity.generation && meta.location.is_valid() => {
+                Ok(meta.location)
+            }
+            _ => Err(NoSuchEntity),
+        }
     }
 
-    pub fn get(&self, entity: Entity) -> Result<Location, NoSuchEntity> {
-        self.entity_locations
-            .get(&entity)
-            .cloned()
-            .ok_or_else(|| NoSuchEntity)
+    /// The live entity using `id`, whatever its generation
+    pub fn resolve_id(&self, id: u32) -> Option<Entity> {
+        self.meta
+            .get(id as usize)
+            .filter(|meta| meta.location.is_valid())
+            .map(|meta| Entity {
+                id,
+                generation: meta.generation,
+            })
+    }
+
+    /// Returns a handle that can reserve entity ids from any thread
+    pub fn get_reserver(&self) -> EntityReserver {
+        self.reserver.clone()
+    }
+
+    /// Whether any reserved entities are waiting to be flushed
+    pub fn needs_flush(&self) -> bool {
+        !self.reserved.is_empty()
+            || self.reserver.next_id.load(Ordering::Relaxed) as usize > self.meta.len()
+    }
+
+    /// Allocates space for entities previously reserved with `EntityReserver::reserve_entity`,
+    /// then initializes each one using the supplied function.
+    pub fn flush(&mut self, mut init: impl FnMut(Entity, &mut Location)) {
+        let end = self.reserver.next_id.load(Ordering::Relaxed);
+        let start = u32::try_from(self.meta.len()).expect("too many entities");
+        if end > start {
+            self.meta.resize(end as usize, EntityMeta::EMPTY);
+        }
+        let reserved = core::mem::take(&mut self.reserved);
+        for id in reserved.into_iter().chain(start..end) {
+            let meta = &mut self.meta[id as usize];
+            init(
+                Entity {
+                    id,
+                    generation: meta.generation,
+                },
+                &mut meta.location,
+            );
+            self.len += 1;
+        }
+    }
+
+    /// Number of live entities
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Save the allocator state, so that `restore` makes the same free ids available again
+    pub fn snapshot(&self) -> EntitiesSnapshot {
+        EntitiesSnapshot {
+            meta: self.meta.clone(),
+            pending: self.pending.clone(),
+            reserved: self.reserved.clone(),
+            next_id: self.reserver.next_id.load(Ordering::Relaxed),
+            len: self.len,
+        }
     }
+
+    /// Return the allocator to a saved state. The location of every entity that was live when the
+    /// snapshot was taken must be written afterwards.
+    ///
+    /// Existing `EntityReserver`s keep working, and ids handed out after the snapshot was taken are
+    /// never reserved again. Reservations that have not been flushed yet are still flushed. The ids
+    /// of entities allocated since the snapshot become free, with a newer generation than any
+    /// handle to them.
+    pub fn restore(&mut self, snapshot: &EntitiesSnapshot) {
+        let next_id = self
+            .reserver
+            .next_id
+            .fetch_max(snapshot.next_id, Ordering::Relaxed)
+            .max(snapshot.next_id);
+        let mut freed = Vec::new();
+        let mut reserved = (snapshot.meta.len() as u32..snapshot.next_id).collect::<Vec<_>>();
+        for id in snapshot.next_id..next_id {
+            match self.meta.get(id as usize) {
+                Some(meta) if !self.reserved.contains(&id) => {
+                    freed.push((id, meta.generation.wrapping_add(1)))
+                }
+                _ => reserved.push(id),
+            }
+        }
+
+        self.meta.clone_from(&snapshot.meta);
+        self.meta.resize(next_id as usize, EntityMeta::EMPTY);
+        self.reserved.clone_from(&snapshot.reserved);
+        self.reserved.extend(reserved);
+        self.len = snapshot.len;
+
+        // the saved free ids are reused first, in the same order as before
+        self.pending.clear();
+        for &(id, generation) in freed.iter() {
+            self.meta[id as usize].generation = generation;
+            self.pending.push(id);
+        }
+        self.pending.extend_from_slice(&snapshot.pending);
+        for (index, &id) in self.pending.iter().enumerate() {
+            self.meta[id as usize].location.index = index as u32;
+        }
+    }
+}
+
+/// The state of an `Entities` allocator, see `Entities::snapshot`
+#[derive(Clone)]
+pub(crate) struct EntitiesSnapshot {
+    meta: Vec<EntityMeta>,
+    pending: Vec<u32>,
+    reserved: Vec<u32>,
+    next_id: u32,
+    len: u32,
+}
+
+#[derive(Copy, Clone)]
+pub(crate) struct EntityMeta {
+    pub generation: u32,
+    pub location: Location,
+}
+
+impl EntityMeta {
+    const EMPTY: EntityMeta = EntityMeta {
+        generation: 0,
+        location: Location {
+            archetype: u32::MAX,
+            index: u32::MAX,
+        },
+    };
 }
 
 #[derive(Copy, Clone)]
@@ -91,6 +373,13 @@ pub struct Location {
     pub index: u32,
 }
 
+impl Location {
+    /// Free and reserved-but-unflushed ids have no location
+    fn is_valid(&self) -> bool {
+        self.archetype != u32::MAX
+    }
+}
+
 /// Error indicating that no entity with a particular ID exists
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct NoSuchEntity;
@@ -21,14 +21,16 @@ use crate::alloc::{
     vec::Vec,
 };
 use core::{
-    any::TypeId,
     mem::{self, MaybeUninit},
     ptr,
 };
 
 use hashbrown::HashSet;
 
-use crate::{archetype::TypeInfo, Component, DynamicBundle};
+use crate::{
+    archetype::{ComponentId, TypeInfo},
+    Component, DynamicBundle,
+};
 
 /// Helper for incrementally constructing a bundle of components with dynamic component types
 ///
@@ -47,8 +49,8 @@ pub struct EntityBuilder {
     storage: Box<[MaybeUninit<u8>]>,
     cursor: usize,
     info: Vec<(TypeInfo, usize)>,
-    ids: Vec<TypeId>,
-    id_set: HashSet<TypeId>,
+    ids: Vec<ComponentId>,
+    id_set: HashSet<ComponentId>,
 }
 
 impl EntityBuilder {
@@ -65,7 +67,7 @@ impl EntityBuilder {
 
     /// Add `component` to the entity
     pub fn add<T: Component>(&mut self, component: T) -> &mut Self {
-        if !self.id_set.insert(TypeId::of::<T>()) {
+        if !self.id_set.insert(ComponentId::of::<T>()) {
             return self;
         }
         let end = self.cursor + mem::size_of::<T>();
@@ -86,6 +88,38 @@ impl EntityBuilder {
         self
     }
 
+    /// Add a component of the type described by `ty`, such as one registered with
+    /// `World::register_component`, from its bytes
+    ///
+    /// If the entity already has a component of this type, `component` is ignored and will not be
+    /// dropped.
+    ///
+    /// # Safety
+    /// `component` must be a valid value of the type, which is moved into the builder: it will be
+    /// dropped by the world or the builder, so it must not be dropped elsewhere.
+    pub unsafe fn add_dynamic(&mut self, ty: TypeInfo, component: &[u8]) -> &mut Self {
+        assert_eq!(
+            component.len(),
+            ty.layout().size(),
+            "component size does not match its type"
+        );
+        if !self.id_set.insert(ty.id()) {
+            return self;
+        }
+        let end = self.cursor + component.len();
+        if end > self.storage.len() {
+            self.grow(end);
+        }
+        ptr::copy_nonoverlapping(
+            component.as_ptr(),
+            self.storage.as_mut_ptr().add(self.cursor).cast::<u8>(),
+            component.len(),
+        );
+        self.info.push((ty, self.cursor));
+        self.cursor += component.len();
+        self
+    }
+
     fn grow(&mut self, min_size: usize) {
         let new_len = min_size.next_power_of_two().max(64);
         let mut new_storage = vec![MaybeUninit::uninit(); new_len].into_boxed_slice();
@@ -166,7 +200,7 @@ pub struct BuiltEntity<'a> {
 }
 
 impl DynamicBundle for BuiltEntity<'_> {
-    fn with_ids<T>(&self, f: impl FnOnce(&[TypeId]) -> T) -> T {
+    fn with_ids<T>(&self, f: impl FnOnce(&[ComponentId]) -> T) -> T {
         f(&self.builder.ids)
     }
 
@@ -175,7 +209,7 @@ impl DynamicBundle for BuiltEntity<'_> {
         self.builder.info.iter().map(|x| x.0).collect()
     }
 
-    unsafe fn put(self, mut f: impl FnMut(*mut u8, TypeId, usize) -> bool) {
+    unsafe fn put(self, mut f: impl FnMut(*mut u8, ComponentId, usize) -> bool) {
         for (ty, offset) in self.builder.info.drain(..) {
             let ptr = self.builder.storage.as_mut_ptr().add(offset).cast();
             if !f(ptr, ty.id(), ty.layout().size()) {
@@ -0,0 +1,94 @@
+// modified by Bevy contributors
+
+use crate::Entity;
+use core::fmt;
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+/// Maps the entities of one world to the entities standing in for them in another, such as when a
+/// saved world is loaded
+#[derive(Debug, Clone, Default)]
+pub struct EntityMap {
+    map: HashMap<Entity, Entity>,
+}
+
+impl EntityMap {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `from` to `to`, returning the entity `from` was previously mapped to
+    pub fn insert(&mut self, from: Entity, to: Entity) -> Option<Entity> {
+        self.map.insert(from, to)
+    }
+
+    /// The entity `entity` is mapped to
+    pub fn get(&self, entity: Entity) -> Result<Entity, MapEntitiesError> {
+        self.map
+            .get(&entity)
+            .copied()
+            .ok_or(MapEntitiesError::EntityNotFound(entity))
+    }
+
+    /// The mapped entities, in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.map.keys().copied()
+    }
+
+    /// The entities mapped to, in no particular order
+    pub fn values(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.map.values().copied()
+    }
+
+    #[allow(missing_docs)]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+/// Components that refer to other entities, whose references must be updated when they are moved
+/// to another world
+///
+/// # Example
+/// ```
+/// # use bevy_hecs::*;
+/// struct Target(Entity);
+///
+/// impl MapEntities for Target {
+///     fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+///         self.0 = entity_map.get(self.0)?;
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait MapEntities {
+    /// Replace every entity referred to with the entity it is mapped to in `entity_map`
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError>;
+}
+
+/// Error indicating that entities could not be mapped
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MapEntitiesError {
+    /// A referred to entity is not in the `EntityMap`
+    EntityNotFound(Entity),
+}
+
+impl fmt::Display for MapEntitiesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapEntitiesError::EntityNotFound(entity) => {
+                write!(f, "entity {} is not mapped", entity.id())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MapEntitiesError {}
@@ -0,0 +1,286 @@
+// modified by Bevy contributors
+
+use core::any::{type_name, TypeId};
+
+use crate::{
+    archetype::{Archetype, Column, ComponentId, ComponentTicks},
+    Access, Added, Changed, Component, Mutated, With, Without,
+};
+
+/// A condition that entities must meet to be visited by a query, without fetching any components
+///
+/// Filters are `With<T>`, `Without<T>`, `Added<T>`, `Mutated<T>`, `Changed<T>`, tuples of filters (all
+/// must match), and `Or` of a tuple of filters (any must match).
+pub trait QueryFilter {
+    #[doc(hidden)]
+    type EntityFilter: EntityFilter;
+
+    /// How this filter will access `archetype`, or `None` if no entity in `archetype` can match
+    fn access(archetype: &Archetype) -> Option<Access>;
+
+    /// Calls `f` with the type id, type name and access of each component this filter reads in
+    /// `archetype`
+    fn component_access(_archetype: &Archetype, _f: &mut dyn FnMut(TypeId, &'static str, Access)) {}
+
+    /// Construct an `EntityFilter` for `archetype`, or `None` if no entity in `archetype` can match
+    ///
+    /// Changes are detected relative to `last_change_tick`.
+    fn get_entity_filter(
+        archetype: &Archetype,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self::EntityFilter>;
+}
+
+/// Decides whether individual entities of an archetype match a `QueryFilter`
+pub trait EntityFilter {
+    /// Whether the entity at `offset` in the archetype matches
+    ///
+    /// # Safety
+    /// `offset` must be in bounds of the archetype this filter was constructed for
+    unsafe fn matches_entity(&self, offset: usize) -> bool;
+}
+
+impl<F: EntityFilter> EntityFilter for Option<F> {
+    #[inline]
+    unsafe fn matches_entity(&self, offset: usize) -> bool {
+        self.as_ref()
+            .map_or(true, |filter| filter.matches_entity(offset))
+    }
+}
+
+/// Matches entities by whether they have a component stored in a sparse set
+#[doc(hidden)]
+pub struct SparseSetFilter<T> {
+    column: Column<T>,
+    with: bool,
+}
+
+impl<T: Component> EntityFilter for SparseSetFilter<T> {
+    #[inline]
+    unsafe fn matches_entity(&self, offset: usize) -> bool {
+        self.column.get(offset).is_some() == self.with
+    }
+}
+
+impl<T: Component> QueryFilter for With<T> {
+    type EntityFilter = Option<SparseSetFilter<T>>;
+
+    fn access(archetype: &Archetype) -> Option<Access> {
+        if archetype.has::<T>() {
+            Some(Access::Iterate)
+        } else {
+            None
+        }
+    }
+
+    fn get_entity_filter(
+        archetype: &Archetype,
+        _last_change_tick: u32,
+        _change_tick: u32,
+    ) -> Option<Self::EntityFilter> {
+        let column = unsafe { archetype.column::<T>(0)? };
+        Some(if column.is_sparse() {
+            Some(SparseSetFilter { column, with: true })
+        } else {
+            None
+        })
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    type EntityFilter = Option<SparseSetFilter<T>>;
+
+    fn access(archetype: &Archetype) -> Option<Access> {
+        if archetype.has_dynamic(ComponentId::of::<T>()) {
+            None
+        } else {
+            Some(Access::Iterate)
+        }
+    }
+
+    fn get_entity_filter(
+        archetype: &Archetype,
+        _last_change_tick: u32,
+        _change_tick: u32,
+    ) -> Option<Self::EntityFilter> {
+        match unsafe { archetype.column::<T>(0) } {
+            Some(column) if column.is_sparse() => Some(Some(SparseSetFilter {
+                column,
+                with: false,
+            })),
+            Some(_) => None,
+            None => Some(None),
+        }
+    }
+}
+
+/// Matches entities whose component ticks pass a check
+#[doc(hidden)]
+pub struct ComponentTicksFilter<T> {
+    column: Column<T>,
+    last_change_tick: u32,
+    change_tick: u32,
+    matches: fn(&ComponentTicks, u32, u32) -> bool,
+}
+
+impl<T: Component> EntityFilter for ComponentTicksFilter<T> {
+    #[inline]
+    unsafe fn matches_entity(&self, offset: usize) -> bool {
+        self.column.get(offset).map_or(false, |(_, ticks)| {
+            (self.matches)(ticks.as_ref(), self.last_change_tick, self.change_tick)
+        })
+    }
+}
+
+macro_rules! impl_change_filter {
+    ($name: ident, $matches: expr) => {
+        impl<'a, T: Component> QueryFilter for $name<'a, T> {
+            type EntityFilter = ComponentTicksFilter<T>;
+
+            fn access(archetype: &Archetype) -> Option<Access> {
+                // component ticks are written alongside the component, so they are read like it
+                if archetype.has::<T>() {
+                    Some(Access::Read)
+                } else {
+                    None
+                }
+            }
+
+            fn component_access(
+                archetype: &Archetype,
+                f: &mut dyn FnMut(TypeId, &'static str, Access),
+            ) {
+                if let Some(access) = Self::access(archetype) {
+                    f(TypeId::of::<T>(), type_name::<T>(), access);
+                }
+            }
+
+            fn get_entity_filter(
+                archetype: &Archetype,
+                last_change_tick: u32,
+                change_tick: u32,
+            ) -> Option<Self::EntityFilter> {
+                unsafe { archetype.column::<T>(0) }.map(|column| ComponentTicksFilter {
+                    column,
+                    last_change_tick,
+                    change_tick,
+                    matches: $matches,
+                })
+            }
+        }
+    };
+}
+
+impl_change_filter!(Added, |ticks, last_change_tick, change_tick| ticks
+    .is_added(last_change_tick, change_tick));
+impl_change_filter!(Mutated, |ticks, last_change_tick, change_tick| ticks
+    .is_mutated(last_change_tick, change_tick));
+impl_change_filter!(Changed, |ticks, last_change_tick, change_tick| ticks
+    .is_added(last_change_tick, change_tick)
+    || ticks.is_mutated(last_change_tick, change_tick));
+
+/// Filter that matches entities matching any of the filters in the tuple `T`
+///
+/// # Example
+/// ```
+/// # use bevy_hecs::*;
+/// # struct Position;
+/// # struct Velocity;
+/// // matches entities whose position or velocity changed
+/// type Moved<'a> = Or<(Mutated<'a, Position>, Mutated<'a, Velocity>)>;
+/// ```
+pub struct Or<T>(pub T);
+
+/// Entity filter of an `Or`, holding the entity filters of the archetypes' matching members
+#[doc(hidden)]
+pub struct OrEntityFilter<T>(T);
+
+macro_rules! impl_filter_tuple {
+    ($($name: ident),*) => {
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            type EntityFilter = ($($name::EntityFilter,)*);
+
+            #[allow(unused_variables, unused_mut)]
+            fn access(archetype: &Archetype) -> Option<Access> {
+                let mut access = Access::Iterate;
+                $(
+                    access = access.max($name::access(archetype)?);
+                )*
+                Some(access)
+            }
+
+            #[allow(unused_variables)]
+            fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+                if Self::access(archetype).is_some() {
+                    $($name::component_access(archetype, f);)*
+                }
+            }
+
+            #[allow(unused_variables)]
+            fn get_entity_filter(
+                archetype: &Archetype,
+                last_change_tick: u32,
+                change_tick: u32,
+            ) -> Option<Self::EntityFilter> {
+                Some(($($name::get_entity_filter(archetype, last_change_tick, change_tick)?,)*))
+            }
+        }
+
+        impl<$($name: EntityFilter),*> EntityFilter for ($($name,)*) {
+            #[allow(unused_variables)]
+            #[inline]
+            unsafe fn matches_entity(&self, offset: usize) -> bool {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = self;
+                true $(&& $name.matches_entity(offset))*
+            }
+        }
+
+        impl<$($name: QueryFilter),*> QueryFilter for Or<($($name,)*)> {
+            type EntityFilter = OrEntityFilter<($(Option<$name::EntityFilter>,)*)>;
+
+            #[allow(unused_variables, unused_mut)]
+            fn access(archetype: &Archetype) -> Option<Access> {
+                let mut access = None;
+                $(
+                    access = access.max($name::access(archetype));
+                )*
+                access
+            }
+
+            #[allow(unused_variables)]
+            fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+                $($name::component_access(archetype, f);)*
+            }
+
+            #[allow(unused_variables, non_snake_case)]
+            fn get_entity_filter(
+                archetype: &Archetype,
+                last_change_tick: u32,
+                change_tick: u32,
+            ) -> Option<Self::EntityFilter> {
+                $(
+                    let $name = $name::get_entity_filter(archetype, last_change_tick, change_tick);
+                )*
+                if false $(|| $name.is_some())* {
+                    Some(OrEntityFilter(($($name,)*)))
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl<$($name: EntityFilter),*> EntityFilter for OrEntityFilter<($(Option<$name>,)*)> {
+            #[allow(unused_variables)]
+            #[inline]
+            unsafe fn matches_entity(&self, offset: usize) -> bool {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = &self.0;
+                false $(|| $name.as_ref().map_or(false, |filter| filter.matches_entity(offset)))*
+            }
+        }
+    };
+}
+
+smaller_tuples_too!(impl_filter_tuple, H, G, F, E, D, C, B, A);
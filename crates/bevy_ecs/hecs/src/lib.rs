@@ -67,28 +67,45 @@ macro_rules! smaller_tuples_too {
 mod archetype;
 mod borrow;
 mod bundle;
+mod component_hooks;
+mod dynamic;
 mod entities;
 mod entity_builder;
+mod entity_map;
+mod filter;
 mod query;
 mod query_one;
-#[cfg(feature = "serde")]
+mod removed_components;
+#[cfg(feature = "serialize")]
 mod serde;
+mod snapshot;
+mod sparse_set;
 mod world;
 
-pub use archetype::Archetype;
+pub use archetype::{
+    check_tick, Archetype, ComponentId, ComponentTicks, TypeInfo, CHECK_TICK_THRESHOLD,
+    MAX_CHANGE_AGE,
+};
 pub use borrow::{EntityRef, Ref, RefMut};
 pub use bundle::{Bundle, DynamicBundle, MissingComponent};
-pub use entities::{Entity, Location, NoSuchEntity};
+pub use component_hooks::{ComponentHook, ComponentHooks};
+pub use dynamic::{ComponentDescriptor, DynamicItem, DynamicQuery, DynamicQueryBorrow};
+pub use entities::{Entity, EntityReserver, Location, NoSuchEntity};
 pub use entity_builder::{BuiltEntity, EntityBuilder};
+pub use entity_map::{EntityMap, MapEntities, MapEntitiesError};
+pub use filter::{EntityFilter, Or, QueryFilter};
 pub use query::{
     Access, Added, BatchedIter, Changed, Mut, Mutated, Query, QueryBorrow, QueryIter, With, Without,
 };
 pub use query_one::QueryOne;
+pub use removed_components::RemovedComponentsReader;
+#[cfg(feature = "serialize")]
+pub use serde::{SerdeRegistry, SerializeWorld};
+pub use snapshot::{SnapshotRegistry, UnregisteredComponent, WorldSnapshot};
+pub use sparse_set::StorageType;
 pub use world::{ArchetypesGeneration, Component, ComponentError, Iter, SpawnBatchIter, World};
 
 // Unstable implementation details needed by the macros
-#[doc(hidden)]
-pub use archetype::TypeInfo;
 #[cfg(feature = "macros")]
 #[doc(hidden)]
 pub use lazy_static;
@@ -15,12 +15,16 @@
 // modified by Bevy contributors
 
 use core::{
+    any::{type_name, TypeId},
     marker::PhantomData,
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
 
-use crate::{archetype::Archetype, Component, Entity};
+use crate::{
+    archetype::{Archetype, Column, ComponentId, ComponentTicks},
+    Component, Entity,
+};
 
 /// A collection of component types to fetch from a `World`
 pub trait Query {
@@ -36,13 +40,25 @@ pub trait Fetch<'a>: Sized {
     /// How this query will access `archetype`, if at all
     fn access(archetype: &Archetype) -> Option<Access>;
 
+    /// Calls `f` with the type id, type name and access of each component this query accesses in
+    /// `archetype`
+    fn component_access(_archetype: &Archetype, _f: &mut dyn FnMut(TypeId, &'static str, Access)) {}
+
     /// Acquire dynamic borrows from `archetype`
     fn borrow(archetype: &Archetype);
     /// Construct a `Fetch` for `archetype` if it should be traversed
     ///
+    /// Changes are detected relative to `last_change_tick`, and mutations are recorded at
+    /// `change_tick`.
+    ///
     /// # Safety
     /// `offset` must be in bounds of `archetype`
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self>;
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self>;
     /// Release dynamic borrows acquired by `borrow`
     fn release(archetype: &Archetype);
 
@@ -51,6 +67,20 @@ pub trait Fetch<'a>: Sized {
         false
     }
 
+    /// if this returns true, the current entity lacks a component stored in a sparse set, so its
+    /// item can not be accessed and must be skipped with `skip`
+    unsafe fn is_missing(&self) -> bool {
+        false
+    }
+
+    /// Advance past the current item without accessing it
+    ///
+    /// # Safety
+    /// Bounds-checking must be performed externally
+    unsafe fn skip(&mut self) {
+        let _ = self.next();
+    }
+
     /// Access the next item in this archetype without bounds checking
     ///
     /// # Safety
@@ -73,7 +103,7 @@ pub enum Access {
 }
 
 #[derive(Copy, Clone, Debug)]
-pub struct EntityFetch(NonNull<u32>);
+pub struct EntityFetch(NonNull<Entity>);
 
 impl Query for Entity {
     type Fetch = EntityFetch;
@@ -91,7 +121,12 @@ impl<'a> Fetch<'a> for EntityFetch {
     fn borrow(_archetype: &Archetype) {}
 
     #[inline]
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        _last_change_tick: u32,
+        _change_tick: u32,
+    ) -> Option<Self> {
         Some(EntityFetch(NonNull::new_unchecked(
             archetype.entities().as_ptr().add(offset),
         )))
@@ -102,9 +137,9 @@ impl<'a> Fetch<'a> for EntityFetch {
 
     #[inline]
     unsafe fn next(&mut self) -> Self::Item {
-        let id = self.0.as_ptr();
-        self.0 = NonNull::new_unchecked(id.add(1));
-        Entity::from_id(*id)
+        let entity = self.0.as_ptr();
+        self.0 = NonNull::new_unchecked(entity.add(1));
+        *entity
     }
 }
 
@@ -113,7 +148,7 @@ impl<'a, T: Component> Query for &'a T {
 }
 
 #[doc(hidden)]
-pub struct FetchRead<T>(NonNull<T>);
+pub struct FetchRead<T>(Column<T>);
 
 impl<'a, T: Component> Fetch<'a> for FetchRead<T> {
     type Item = &'a T;
@@ -126,25 +161,44 @@ impl<'a, T: Component> Fetch<'a> for FetchRead<T> {
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if let Some(access) = Self::access(archetype) {
+            f(TypeId::of::<T>(), type_name::<T>(), access);
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         archetype.borrow::<T>();
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
-        archetype
-            .get::<T>()
-            .map(|x| Self(NonNull::new_unchecked(x.as_ptr().add(offset))))
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        _last_change_tick: u32,
+        _change_tick: u32,
+    ) -> Option<Self> {
+        archetype.column::<T>(offset).map(Self)
     }
 
     fn release(archetype: &Archetype) {
         archetype.release::<T>();
     }
 
+    #[inline]
+    unsafe fn is_missing(&self) -> bool {
+        self.0.get(0).is_none()
+    }
+
+    #[inline]
+    unsafe fn skip(&mut self) {
+        self.0.advance();
+    }
+
     #[inline]
     unsafe fn next(&mut self) -> &'a T {
-        let x = self.0.as_ptr();
-        self.0 = NonNull::new_unchecked(x.add(1));
-        &*x
+        let (component, _) = self.0.current();
+        self.0.advance();
+        &*component.as_ptr()
     }
 }
 
@@ -159,7 +213,8 @@ impl<T: Query> Query for Option<T> {
 /// Unique borrow of an entity's component
 pub struct Mut<'a, T: Component> {
     value: &'a mut T,
-    mutated: &'a mut bool,
+    ticks: &'a mut ComponentTicks,
+    change_tick: u32,
 }
 
 unsafe impl<T: Component> Send for Mut<'_, T> {}
@@ -177,7 +232,7 @@ impl<'a, T: Component> Deref for Mut<'a, T> {
 impl<'a, T: Component> DerefMut for Mut<'a, T> {
     #[inline]
     fn deref_mut(&mut self) -> &mut T {
-        *self.mutated = true;
+        self.ticks.mutated = self.change_tick;
         self.value
     }
 }
@@ -192,7 +247,7 @@ impl<'a, T: Component> Query for Mut<'a, T> {
     type Fetch = FetchMut<T>;
 }
 #[doc(hidden)]
-pub struct FetchMut<T>(NonNull<T>, NonNull<bool>);
+pub struct FetchMut<T>(Column<T>, u32);
 
 impl<'a, T: Component> Fetch<'a> for FetchMut<T> {
     type Item = Mut<'a, T>;
@@ -205,34 +260,49 @@ impl<'a, T: Component> Fetch<'a> for FetchMut<T> {
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if let Some(access) = Self::access(archetype) {
+            f(TypeId::of::<T>(), type_name::<T>(), access);
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         archetype.borrow_mut::<T>();
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        _last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
         archetype
-            .get_with_mutated::<T>()
-            .map(|(components, mutated)| {
-                Self(
-                    NonNull::new_unchecked(components.as_ptr().add(offset)),
-                    NonNull::new_unchecked(mutated.as_ptr().add(offset)),
-                )
-            })
+            .column::<T>(offset)
+            .map(|column| Self(column, change_tick))
     }
 
     fn release(archetype: &Archetype) {
         archetype.release_mut::<T>();
     }
 
+    #[inline]
+    unsafe fn is_missing(&self) -> bool {
+        self.0.get(0).is_none()
+    }
+
+    #[inline]
+    unsafe fn skip(&mut self) {
+        self.0.advance();
+    }
+
     #[inline]
     unsafe fn next(&mut self) -> Mut<'a, T> {
-        let component = self.0.as_ptr();
-        let mutated = self.1.as_ptr();
-        self.0 = NonNull::new_unchecked(component.add(1));
-        self.1 = NonNull::new_unchecked(mutated.add(1));
+        let (component, ticks) = self.0.current();
+        self.0.advance();
         Mut {
-            value: &mut *component,
-            mutated: &mut *mutated,
+            value: &mut *component.as_ptr(),
+            ticks: &mut *ticks.as_ptr(),
+            change_tick: self.1,
         }
     }
 }
@@ -256,7 +326,7 @@ impl<'a, T: Component> Query for Mutated<'a, T> {
 }
 
 #[doc(hidden)]
-pub struct FetchMutated<T>(NonNull<T>, NonNull<bool>);
+pub struct FetchMutated<T>(Column<T>, u32, u32);
 
 impl<'a, T: Component> Fetch<'a> for FetchMutated<T> {
     type Item = Mutated<'a, T>;
@@ -269,19 +339,25 @@ impl<'a, T: Component> Fetch<'a> for FetchMutated<T> {
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if let Some(access) = Self::access(archetype) {
+            f(TypeId::of::<T>(), type_name::<T>(), access);
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         archetype.borrow::<T>();
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
         archetype
-            .get_with_mutated::<T>()
-            .map(|(components, mutated)| {
-                Self(
-                    NonNull::new_unchecked(components.as_ptr().add(offset)),
-                    NonNull::new_unchecked(mutated.as_ptr().add(offset)),
-                )
-            })
+            .column::<T>(offset)
+            .map(|column| Self(column, last_change_tick, change_tick))
     }
 
     fn release(archetype: &Archetype) {
@@ -290,15 +366,26 @@ impl<'a, T: Component> Fetch<'a> for FetchMutated<T> {
 
     unsafe fn should_skip(&self) -> bool {
         // skip if the current item wasn't mutated
-        !*self.1.as_ref()
+        !self.0.current().1.as_ref().is_mutated(self.1, self.2)
+    }
+
+    #[inline]
+    unsafe fn is_missing(&self) -> bool {
+        self.0.get(0).is_none()
+    }
+
+    #[inline]
+    unsafe fn skip(&mut self) {
+        self.0.advance();
     }
 
     #[inline]
     unsafe fn next(&mut self) -> Self::Item {
-        self.1 = NonNull::new_unchecked(self.1.as_ptr().add(1));
-        let value = self.0.as_ptr();
-        self.0 = NonNull::new_unchecked(value.add(1));
-        Mutated { value: &*value }
+        let (value, _) = self.0.current();
+        self.0.advance();
+        Mutated {
+            value: &*value.as_ptr(),
+        }
     }
 }
 
@@ -321,7 +408,7 @@ impl<'a, T: Component> Query for Added<'a, T> {
 }
 
 #[doc(hidden)]
-pub struct FetchAdded<T>(NonNull<T>, NonNull<bool>);
+pub struct FetchAdded<T>(Column<T>, u32, u32);
 
 impl<'a, T: Component> Fetch<'a> for FetchAdded<T> {
     type Item = Added<'a, T>;
@@ -334,17 +421,25 @@ impl<'a, T: Component> Fetch<'a> for FetchAdded<T> {
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if let Some(access) = Self::access(archetype) {
+            f(TypeId::of::<T>(), type_name::<T>(), access);
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         archetype.borrow::<T>();
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
-        archetype.get_with_added::<T>().map(|(components, added)| {
-            Self(
-                NonNull::new_unchecked(components.as_ptr().add(offset)),
-                NonNull::new_unchecked(added.as_ptr().add(offset)),
-            )
-        })
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
+        archetype
+            .column::<T>(offset)
+            .map(|column| Self(column, last_change_tick, change_tick))
     }
 
     fn release(archetype: &Archetype) {
@@ -353,15 +448,26 @@ impl<'a, T: Component> Fetch<'a> for FetchAdded<T> {
 
     unsafe fn should_skip(&self) -> bool {
         // skip if the current item wasn't added
-        !*self.1.as_ref()
+        !self.0.current().1.as_ref().is_added(self.1, self.2)
+    }
+
+    #[inline]
+    unsafe fn is_missing(&self) -> bool {
+        self.0.get(0).is_none()
+    }
+
+    #[inline]
+    unsafe fn skip(&mut self) {
+        self.0.advance();
     }
 
     #[inline]
     unsafe fn next(&mut self) -> Self::Item {
-        self.1 = NonNull::new_unchecked(self.1.as_ptr().add(1));
-        let value = self.0.as_ptr();
-        self.0 = NonNull::new_unchecked(value.add(1));
-        Added { value: &*value }
+        let (value, _) = self.0.current();
+        self.0.advance();
+        Added {
+            value: &*value.as_ptr(),
+        }
     }
 }
 
@@ -384,7 +490,7 @@ impl<'a, T: Component> Query for Changed<'a, T> {
 }
 
 #[doc(hidden)]
-pub struct FetchChanged<T>(NonNull<T>, NonNull<bool>, NonNull<bool>);
+pub struct FetchChanged<T>(Column<T>, u32, u32);
 
 impl<'a, T: Component> Fetch<'a> for FetchChanged<T> {
     type Item = Changed<'a, T>;
@@ -397,20 +503,25 @@ impl<'a, T: Component> Fetch<'a> for FetchChanged<T> {
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if let Some(access) = Self::access(archetype) {
+            f(TypeId::of::<T>(), type_name::<T>(), access);
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         archetype.borrow::<T>();
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
         archetype
-            .get_with_added_and_mutated::<T>()
-            .map(|(components, added, mutated)| {
-                Self(
-                    NonNull::new_unchecked(components.as_ptr().add(offset)),
-                    NonNull::new_unchecked(added.as_ptr().add(offset)),
-                    NonNull::new_unchecked(mutated.as_ptr().add(offset)),
-                )
-            })
+            .column::<T>(offset)
+            .map(|column| Self(column, last_change_tick, change_tick))
     }
 
     fn release(archetype: &Archetype) {
@@ -419,16 +530,27 @@ impl<'a, T: Component> Fetch<'a> for FetchChanged<T> {
 
     unsafe fn should_skip(&self) -> bool {
         // skip if the current item wasn't added or mutated
-        !*self.1.as_ref() && !self.2.as_ref()
+        let ticks = self.0.current().1.as_ref();
+        !ticks.is_added(self.1, self.2) && !ticks.is_mutated(self.1, self.2)
+    }
+
+    #[inline]
+    unsafe fn is_missing(&self) -> bool {
+        self.0.get(0).is_none()
+    }
+
+    #[inline]
+    unsafe fn skip(&mut self) {
+        self.0.advance();
     }
 
     #[inline]
     unsafe fn next(&mut self) -> Self::Item {
-        self.1 = NonNull::new_unchecked(self.1.as_ptr().add(1));
-        self.2 = NonNull::new_unchecked(self.2.as_ptr().add(1));
-        let value = self.0.as_ptr();
-        self.0 = NonNull::new_unchecked(value.add(1));
-        Changed { value: &*value }
+        let (value, _) = self.0.current();
+        self.0.advance();
+        Changed {
+            value: &*value.as_ptr(),
+        }
     }
 }
 
@@ -442,12 +564,26 @@ impl<'a, T: Fetch<'a>> Fetch<'a> for TryFetch<T> {
         Some(T::access(archetype).unwrap_or(Access::Iterate))
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        T::component_access(archetype, f)
+    }
+
     fn borrow(archetype: &Archetype) {
         T::borrow(archetype)
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
-        Some(Self(T::get(archetype, offset)))
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
+        Some(Self(T::get(
+            archetype,
+            offset,
+            last_change_tick,
+            change_tick,
+        )))
     }
 
     fn release(archetype: &Archetype) {
@@ -455,17 +591,31 @@ impl<'a, T: Fetch<'a>> Fetch<'a> for TryFetch<T> {
     }
 
     unsafe fn next(&mut self) -> Option<T::Item> {
-        Some(self.0.as_mut()?.next())
+        let fetch = self.0.as_mut()?;
+        if fetch.is_missing() {
+            fetch.skip();
+            None
+        } else {
+            Some(fetch.next())
+        }
     }
 
     unsafe fn should_skip(&self) -> bool {
-        self.0.as_ref().map_or(false, |fetch| fetch.should_skip())
+        self.0
+            .as_ref()
+            .map_or(false, |fetch| !fetch.is_missing() && fetch.should_skip())
+    }
+
+    unsafe fn skip(&mut self) {
+        if let Some(fetch) = self.0.as_mut() {
+            fetch.skip();
+        }
     }
 }
 
 /// Query transformer skipping entities that have a `T` component
 ///
-/// See also `QueryBorrow::without`.
+/// `Without<T>` can also be used as a `QueryFilter`. See also `QueryBorrow::without`.
 ///
 /// # Example
 /// ```
@@ -480,35 +630,51 @@ impl<'a, T: Fetch<'a>> Fetch<'a> for TryFetch<T> {
 ///     .collect::<Vec<_>>();
 /// assert_eq!(entities, &[(c, 42)]);
 /// ```
-pub struct Without<T, Q>(PhantomData<(Q, fn(T))>);
+pub struct Without<T, Q = ()>(PhantomData<(Q, fn(T))>);
 
 impl<T: Component, Q: Query> Query for Without<T, Q> {
     type Fetch = FetchWithout<T, Q::Fetch>;
 }
 
 #[doc(hidden)]
-pub struct FetchWithout<T, F>(F, PhantomData<fn(T)>);
+pub struct FetchWithout<T, F>(F, Option<Column<T>>);
 
 impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWithout<T, F> {
     type Item = F::Item;
 
     fn access(archetype: &Archetype) -> Option<Access> {
-        if archetype.has::<T>() {
+        if archetype.has_dynamic(ComponentId::of::<T>()) {
             None
         } else {
             F::access(archetype)
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if Self::access(archetype).is_some() {
+            F::component_access(archetype, f)
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         F::borrow(archetype)
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
-        if archetype.has::<T>() {
-            return None;
-        }
-        Some(Self(F::get(archetype, offset)?, PhantomData))
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
+        // entities may or may not have components stored in sparse sets
+        let column = match archetype.column::<T>(offset) {
+            Some(column) if !column.is_sparse() => return None,
+            column => column,
+        };
+        Some(Self(
+            F::get(archetype, offset, last_change_tick, change_tick)?,
+            column,
+        ))
     }
 
     fn release(archetype: &Archetype) {
@@ -516,17 +682,31 @@ impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWithout<T, F> {
     }
 
     unsafe fn next(&mut self) -> F::Item {
+        if let Some(column) = &mut self.1 {
+            column.advance();
+        }
         self.0.next()
     }
 
     unsafe fn should_skip(&self) -> bool {
         self.0.should_skip()
     }
+
+    unsafe fn is_missing(&self) -> bool {
+        self.1.map_or(false, |column| column.get(0).is_some()) || self.0.is_missing()
+    }
+
+    unsafe fn skip(&mut self) {
+        if let Some(column) = &mut self.1 {
+            column.advance();
+        }
+        self.0.skip();
+    }
 }
 
 /// Query transformer skipping entities that do not have a `T` component
 ///
-/// See also `QueryBorrow::with`.
+/// `With<T>` can also be used as a `QueryFilter`. See also `QueryBorrow::with`.
 ///
 /// # Example
 /// ```
@@ -543,14 +723,14 @@ impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWithout<T, F> {
 /// assert!(entities.contains(&(a, 123)));
 /// assert!(entities.contains(&(b, 456)));
 /// ```
-pub struct With<T, Q>(PhantomData<(Q, fn(T))>);
+pub struct With<T, Q = ()>(PhantomData<(Q, fn(T))>);
 
 impl<T: Component, Q: Query> Query for With<T, Q> {
     type Fetch = FetchWith<T, Q::Fetch>;
 }
 
 #[doc(hidden)]
-pub struct FetchWith<T, F>(F, PhantomData<fn(T)>);
+pub struct FetchWith<T, F>(F, Option<Column<T>>);
 
 impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWith<T, F> {
     type Item = F::Item;
@@ -563,15 +743,28 @@ impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWith<T, F> {
         }
     }
 
+    fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+        if Self::access(archetype).is_some() {
+            F::component_access(archetype, f)
+        }
+    }
+
     fn borrow(archetype: &Archetype) {
         F::borrow(archetype)
     }
 
-    unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
-        if !archetype.has::<T>() {
-            return None;
-        }
-        Some(Self(F::get(archetype, offset)?, PhantomData))
+    unsafe fn get(
+        archetype: &'a Archetype,
+        offset: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<Self> {
+        // entities may or may not have components stored in sparse sets
+        let column = Some(archetype.column::<T>(offset)?).filter(Column::is_sparse);
+        Some(Self(
+            F::get(archetype, offset, last_change_tick, change_tick)?,
+            column,
+        ))
     }
 
     fn release(archetype: &Archetype) {
@@ -579,12 +772,26 @@ impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWith<T, F> {
     }
 
     unsafe fn next(&mut self) -> F::Item {
+        if let Some(column) = &mut self.1 {
+            column.advance();
+        }
         self.0.next()
     }
 
     unsafe fn should_skip(&self) -> bool {
         self.0.should_skip()
     }
+
+    unsafe fn is_missing(&self) -> bool {
+        self.1.map_or(false, |column| column.get(0).is_none()) || self.0.is_missing()
+    }
+
+    unsafe fn skip(&mut self) {
+        if let Some(column) = &mut self.1 {
+            column.advance();
+        }
+        self.0.skip();
+    }
 }
 
 /// A borrow of a `World` sufficient to execute the query `Q`
@@ -593,14 +800,22 @@ impl<'a, T: Component, F: Fetch<'a>> Fetch<'a> for FetchWith<T, F> {
 pub struct QueryBorrow<'w, Q: Query> {
     archetypes: &'w [Archetype],
     borrowed: bool,
+    last_change_tick: u32,
+    change_tick: u32,
     _marker: PhantomData<Q>,
 }
 
 impl<'w, Q: Query> QueryBorrow<'w, Q> {
-    pub(crate) fn new(archetypes: &'w [Archetype]) -> Self {
+    pub(crate) fn new(
+        archetypes: &'w [Archetype],
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self {
         Self {
             archetypes,
             borrowed: false,
+            last_change_tick,
+            change_tick,
             _marker: PhantomData,
         }
     }
@@ -698,6 +913,8 @@ impl<'w, Q: Query> QueryBorrow<'w, Q> {
         let x = QueryBorrow {
             archetypes: self.archetypes,
             borrowed: self.borrowed,
+            last_change_tick: self.last_change_tick,
+            change_tick: self.change_tick,
             _marker: PhantomData,
         };
         // Ensure `Drop` won't fire redundantly
@@ -751,7 +968,13 @@ impl<'q, 'w, Q: Query> Iterator for QueryIter<'q, 'w, Q> {
                     let archetype = self.borrow.archetypes.get(self.archetype_index as usize)?;
                     self.archetype_index += 1;
                     unsafe {
-                        self.iter = Q::Fetch::get(archetype, 0).map(|fetch| ChunkIter {
+                        self.iter = Q::Fetch::get(
+                            archetype,
+                            0,
+                            self.borrow.last_change_tick,
+                            self.borrow.change_tick,
+                        )
+                        .map(|fetch| ChunkIter {
                             fetch,
                             len: archetype.len(),
                         });
@@ -800,9 +1023,9 @@ impl<Q: Query> ChunkIter<Q> {
             }
 
             self.len -= 1;
-            if self.fetch.should_skip() {
+            if self.fetch.is_missing() || self.fetch.should_skip() {
                 // we still need to progress the iterator
-                let _ = self.fetch.next();
+                self.fetch.skip();
                 continue;
             }
 
@@ -834,7 +1057,14 @@ impl<'q, 'w, Q: Query> Iterator for BatchedIter<'q, 'w, Q> {
                 self.batch = 0;
                 continue;
             }
-            if let Some(fetch) = unsafe { Q::Fetch::get(archetype, offset as usize) } {
+            if let Some(fetch) = unsafe {
+                Q::Fetch::get(
+                    archetype,
+                    offset as usize,
+                    self.borrow.last_change_tick,
+                    self.borrow.change_tick,
+                )
+            } {
                 self.batch += 1;
                 return Some(Batch {
                     _marker: PhantomData,
@@ -887,13 +1117,20 @@ macro_rules! tuple_impl {
                 Some(access)
             }
 
+            #[allow(unused_variables)]
+            fn component_access(archetype: &Archetype, f: &mut dyn FnMut(TypeId, &'static str, Access)) {
+                if Self::access(archetype).is_some() {
+                    $($name::component_access(archetype, f);)*
+                }
+            }
+
             #[allow(unused_variables)]
             fn borrow(archetype: &Archetype) {
                 $($name::borrow(archetype);)*
             }
             #[allow(unused_variables)]
-            unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
-                Some(($($name::get(archetype, offset)?,)*))
+            unsafe fn get(archetype: &'a Archetype, offset: usize, last_change_tick: u32, change_tick: u32) -> Option<Self> {
+                Some(($($name::get(archetype, offset, last_change_tick, change_tick)?,)*))
             }
             #[allow(unused_variables)]
             fn release(archetype: &Archetype) {
@@ -912,6 +1149,19 @@ macro_rules! tuple_impl {
                 let ($($name,)*) = self;
                 $($name.should_skip()||)* false
             }
+
+            unsafe fn is_missing(&self) -> bool {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = self;
+                $($name.is_missing()||)* false
+            }
+
+            #[allow(unused_variables)]
+            unsafe fn skip(&mut self) {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = self;
+                $($name.skip();)*
+            }
         }
 
         impl<$($name: Query),*> Query for ($($name,)*) {
@@ -12,6 +12,8 @@ pub struct QueryOne<'a, Q: Query> {
     archetype: &'a Archetype,
     index: u32,
     borrowed: bool,
+    last_change_tick: u32,
+    change_tick: u32,
     _marker: PhantomData<Q>,
 }
 
@@ -21,11 +23,18 @@ impl<'a, Q: Query> QueryOne<'a, Q> {
     /// # Safety
     ///
     /// `index` must be in-bounds for `archetype`
-    pub(crate) unsafe fn new(archetype: &'a Archetype, index: u32) -> Self {
+    pub(crate) unsafe fn new(
+        archetype: &'a Archetype,
+        index: u32,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self {
         Self {
             archetype,
             index,
             borrowed: false,
+            last_change_tick,
+            change_tick,
             _marker: PhantomData,
         }
     }
@@ -41,7 +50,15 @@ impl<'a, Q: Query> QueryOne<'a, Q> {
             panic!("called QueryOnce::get twice; construct a new query instead");
         }
         unsafe {
-            let mut fetch = Q::Fetch::get(self.archetype, self.index as usize)?;
+            let mut fetch = Q::Fetch::get(
+                self.archetype,
+                self.index as usize,
+                self.last_change_tick,
+                self.change_tick,
+            )?;
+            if fetch.is_missing() {
+                return None;
+            }
             self.borrowed = true;
             Q::Fetch::borrow(self.archetype);
             Some(fetch.next())
@@ -68,6 +85,8 @@ impl<'a, Q: Query> QueryOne<'a, Q> {
             archetype: self.archetype,
             index: self.index,
             borrowed: self.borrowed,
+            last_change_tick: self.last_change_tick,
+            change_tick: self.change_tick,
             _marker: PhantomData,
         };
         // Ensure `Drop` won't fire redundantly
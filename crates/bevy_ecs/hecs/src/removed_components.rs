@@ -0,0 +1,94 @@
+// modified by Bevy contributors
+
+use crate::{alloc::vec::Vec, Component, ComponentId, Entity, World};
+use core::{marker::PhantomData, mem};
+
+/// The entities that had a component of a given type removed, either explicitly or by being
+/// despawned. Removals are double buffered, so each one stays readable until the second call to
+/// `World::clear_trackers` after it happened.
+#[derive(Debug, Default)]
+pub(crate) struct RemovedComponents {
+    previous: Vec<Entity>,
+    current: Vec<Entity>,
+    // Number of removals ever recorded
+    event_count: usize,
+}
+
+impl RemovedComponents {
+    pub fn push(&mut self, entity: Entity) {
+        self.current.push(entity);
+        self.event_count += 1;
+    }
+
+    pub fn extend(&mut self, entities: impl Iterator<Item = Entity>) {
+        for entity in entities {
+            self.push(entity);
+        }
+    }
+
+    /// Removals recorded since the last call to `update`
+    pub fn current(&self) -> &[Entity] {
+        &self.current
+    }
+
+    /// Drops the oldest buffer of removals
+    pub fn update(&mut self) {
+        mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    fn iter_since(&self, last_event_count: usize) -> impl Iterator<Item = Entity> + '_ {
+        let start_event_count = self.event_count - self.previous.len() - self.current.len();
+        self.previous
+            .iter()
+            .chain(self.current.iter())
+            .skip(last_event_count.saturating_sub(start_event_count))
+            .copied()
+    }
+}
+
+/// Reads the entities that had a `C` component removed, including despawned entities, and tracks
+/// which removals have already been read
+///
+/// Removals are dropped after two calls to `World::clear_trackers`, so a reader should read at
+/// least once in between to see all of them.
+///
+/// # Example
+/// ```
+/// # use bevy_hecs::*;
+/// let mut world = World::new();
+/// let mut reader = RemovedComponentsReader::<i32>::default();
+/// let a = world.spawn((123, true));
+/// world.remove_one::<i32>(a).unwrap();
+/// world.clear_trackers();
+/// assert_eq!(reader.iter(&world).collect::<Vec<_>>(), &[a]);
+/// assert_eq!(reader.iter(&world).count(), 0);
+/// ```
+pub struct RemovedComponentsReader<C> {
+    last_event_count: usize,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for RemovedComponentsReader<C> {
+    fn default() -> Self {
+        Self {
+            last_event_count: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C: Component> RemovedComponentsReader<C> {
+    /// Iterates over the removals this reader has not seen yet. Subsequent reads will not include
+    /// removals that happened before now.
+    pub fn iter<'a>(&mut self, world: &'a World) -> impl Iterator<Item = Entity> + 'a {
+        let removed = world.removed_components.get(&ComponentId::of::<C>());
+        let last_event_count = mem::replace(
+            &mut self.last_event_count,
+            removed.map_or(0, |removed| removed.event_count),
+        );
+        removed
+            .into_iter()
+            .flat_map(move |removed| removed.iter_since(last_event_count))
+    }
+}
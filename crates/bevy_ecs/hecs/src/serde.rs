@@ -1,13 +1,453 @@
 // modified by Bevy contributors
 
-use crate::entities::Entity;
-use serde::{Serialize, Serializer};
+use crate::{
+    alloc::{string::String, vec::Vec},
+    entities::Entity,
+    entity_map::{EntityMap, MapEntities, MapEntitiesError},
+    Component, EntityBuilder, World,
+};
+use core::fmt;
+use hashbrown::HashMap;
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq, SerializeTuple},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
+/// Entities are serialized as a tuple of their id and generation
 impl Serialize for Entity {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_u32(self.id())
+        (self.id, self.generation).serialize(serializer)
+    }
+}
+
+/// Deserialized entities refer to entities of the serialized world and should be mapped to live
+/// entities with an `EntityMap`
+impl<'de> Deserialize<'de> for Entity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (id, generation) = <(u32, u32)>::deserialize(deserializer)?;
+        Ok(Entity { id, generation })
+    }
+}
+
+/// The component types that are saved when serializing a `World`
+///
+/// Components are identified by the name they are registered with, so worlds can only be loaded
+/// by a registry using the same names. Components of unregistered types are skipped.
+///
+/// # Example
+/// ```
+/// # use bevy_hecs::*;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct Position(f32, f32);
+///
+/// let mut registry = SerdeRegistry::new();
+/// registry.register::<Position>("Position");
+///
+/// let mut world = World::new();
+/// world.spawn((Position(1.0, 2.0),));
+/// let saved = serde_json::to_string(&registry.serialize_world(&world)).unwrap();
+///
+/// let mut loaded = World::new();
+/// let entity_map = registry
+///     .deserialize_world(&mut loaded, &mut serde_json::Deserializer::from_str(&saved))
+///     .unwrap();
+/// assert_eq!(entity_map.len(), 1);
+/// assert_eq!(loaded.query::<&Position>().iter().count(), 1);
+/// ```
+#[derive(Default)]
+pub struct SerdeRegistry {
+    registrations: Vec<SerdeRegistration>,
+    indices: HashMap<String, usize>,
+}
+
+struct SerdeRegistration {
+    name: String,
+    has: fn(&World, Entity) -> bool,
+    serialize: fn(&World, Entity, &mut dyn FnMut(&dyn erased_serde::Serialize)),
+    deserialize: fn(
+        &mut dyn erased_serde::Deserializer,
+        &mut EntityBuilder,
+    ) -> Result<(), erased_serde::Error>,
+    map_entities: Option<fn(&mut World, &EntityMap) -> Result<(), MapEntitiesError>>,
+}
+
+impl SerdeRegistry {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save `T` components, identified by `name`
+    pub fn register<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        self.add_registration::<T>(name.into(), None)
+    }
+
+    /// Save `T` components, identified by `name`, and map the entities they refer to when loading
+    pub fn register_mapped<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned + MapEntities,
+    {
+        self.add_registration::<T>(name.into(), Some(World::map_entities::<T>))
+    }
+
+    fn add_registration<T>(
+        &mut self,
+        name: String,
+        map_entities: Option<fn(&mut World, &EntityMap) -> Result<(), MapEntitiesError>>,
+    ) -> &mut Self
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        if self.indices.contains_key(&name) {
+            panic!("a component is already registered as {}", name);
+        }
+        self.indices.insert(name.clone(), self.registrations.len());
+        self.registrations.push(SerdeRegistration {
+            name,
+            has: |world, entity| world.get::<T>(entity).is_ok(),
+            serialize: serialize_component::<T>,
+            deserialize: deserialize_component::<T>,
+            map_entities,
+        });
+        self
+    }
+
+    /// A serializable view of every entity in `world` and their registered components
+    pub fn serialize_world<'a>(&'a self, world: &'a World) -> SerializeWorld<'a> {
+        self.serialize_entities(world, world.iter().map(|(entity, _)| entity))
+    }
+
+    /// A serializable view of `entities` and their registered components. Serializing fails if an
+    /// entity does not exist.
+    pub fn serialize_entities<'a>(
+        &'a self,
+        world: &'a World,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> SerializeWorld<'a> {
+        SerializeWorld {
+            registry: self,
+            world,
+            entities: entities.into_iter().collect(),
+        }
+    }
+
+    /// Spawn the entities saved with `serialize_world` or `serialize_entities` into `world`,
+    /// returning the map from the saved entities to the spawned ones
+    ///
+    /// Entities referred to by components registered with `register_mapped` are mapped to the
+    /// spawned entities, which fails if they were not saved. The world keeps any entities that were
+    /// spawned before an error.
+    pub fn deserialize_world<'de, D>(
+        &self,
+        world: &mut World,
+        deserializer: D,
+    ) -> Result<EntityMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut entity_map = EntityMap::new();
+        deserializer.deserialize_seq(WorldVisitor {
+            registry: self,
+            world,
+            entity_map: &mut entity_map,
+        })?;
+        for registration in &self.registrations {
+            if let Some(map_entities) = registration.map_entities {
+                map_entities(world, &entity_map).map_err(de::Error::custom)?;
+            }
+        }
+        Ok(entity_map)
+    }
+}
+
+fn serialize_component<T: Component + Serialize>(
+    world: &World,
+    entity: Entity,
+    f: &mut dyn FnMut(&dyn erased_serde::Serialize),
+) {
+    if let Ok(component) = world.get::<T>(entity) {
+        f(&*component);
+    }
+}
+
+fn deserialize_component<T: Component + DeserializeOwned>(
+    deserializer: &mut dyn erased_serde::Deserializer,
+    builder: &mut EntityBuilder,
+) -> Result<(), erased_serde::Error> {
+    builder.add(erased_serde::deserialize::<T>(deserializer)?);
+    Ok(())
+}
+
+/// Serializes entities and their registered components, see `SerdeRegistry::serialize_world`
+///
+/// Worlds are serialized as a sequence of entities, each a tuple of the entity and a map from
+/// component names to components.
+pub struct SerializeWorld<'a> {
+    registry: &'a SerdeRegistry,
+    world: &'a World,
+    entities: Vec<Entity>,
+}
+
+impl Serialize for SerializeWorld<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.entities.len()))?;
+        for &entity in &self.entities {
+            if !self.world.contains(entity) {
+                return Err(ser::Error::custom("no such entity"));
+            }
+            seq.serialize_element(&SerializeEntity {
+                registry: self.registry,
+                world: self.world,
+                entity,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+struct SerializeEntity<'a> {
+    registry: &'a SerdeRegistry,
+    world: &'a World,
+    entity: Entity,
+}
+
+impl Serialize for SerializeEntity<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.entity)?;
+        tuple.serialize_element(&SerializeComponents {
+            registry: self.registry,
+            world: self.world,
+            entity: self.entity,
+        })?;
+        tuple.end()
+    }
+}
+
+struct SerializeComponents<'a> {
+    registry: &'a SerdeRegistry,
+    world: &'a World,
+    entity: Entity,
+}
+
+impl Serialize for SerializeComponents<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let registrations = self
+            .registry
+            .registrations
+            .iter()
+            .filter(|registration| (registration.has)(self.world, self.entity))
+            .collect::<Vec<_>>();
+        let mut map = serializer.serialize_map(Some(registrations.len()))?;
+        for registration in registrations {
+            map.serialize_entry(
+                &registration.name,
+                &SerializeComponent {
+                    registration,
+                    world: self.world,
+                    entity: self.entity,
+                },
+            )?;
+        }
+        map.end()
+    }
+}
+
+struct SerializeComponent<'a> {
+    registration: &'a SerdeRegistration,
+    world: &'a World,
+    entity: Entity,
+}
+
+impl Serialize for SerializeComponent<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut serializer = Some(serializer);
+        let mut result = None;
+        (self.registration.serialize)(self.world, self.entity, &mut |component| {
+            result = Some(component.serialize(serializer.take().unwrap()));
+        });
+        // the entity was checked to have the component
+        result.unwrap()
+    }
+}
+
+struct WorldVisitor<'a> {
+    registry: &'a SerdeRegistry,
+    world: &'a mut World,
+    entity_map: &'a mut EntityMap,
+}
+
+impl<'de> Visitor<'de> for WorldVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut builder = EntityBuilder::new();
+        while let Some(saved) = seq.next_element_seed(EntitySeed {
+            registry: self.registry,
+            builder: &mut builder,
+        })? {
+            let entity = self.world.spawn(builder.build());
+            self.entity_map.insert(saved, entity);
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes an entity's components into `builder`, returning the saved entity
+struct EntitySeed<'a> {
+    registry: &'a SerdeRegistry,
+    builder: &'a mut EntityBuilder,
+}
+
+impl<'de> DeserializeSeed<'de> for EntitySeed<'_> {
+    type Value = Entity;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntitySeed<'_> {
+    type Value = Entity;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an entity and its components")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let entity = seq
+            .next_element::<Entity>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        seq.next_element_seed(ComponentsSeed {
+            registry: self.registry,
+            builder: self.builder,
+        })?
+        .ok_or_else(|| de::Error::invalid_length(1, &"an entity and its components"))?;
+        Ok(entity)
+    }
+}
+
+struct ComponentsSeed<'a> {
+    registry: &'a SerdeRegistry,
+    builder: &'a mut EntityBuilder,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentsSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentsSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of component names to components")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while let Some(registration) = map.next_key_seed(RegistrationSeed(self.registry))? {
+            map.next_value_seed(ComponentSeed {
+                registration,
+                builder: self.builder,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks up the registration of a component name
+struct RegistrationSeed<'a>(&'a SerdeRegistry);
+
+impl<'a, 'de> DeserializeSeed<'de> for RegistrationSeed<'a> {
+    type Value = &'a SerdeRegistration;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for RegistrationSeed<'a> {
+    type Value = &'a SerdeRegistration;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a registered component name")
+    }
+
+    fn visit_str<E>(self, name: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match self.0.indices.get(name) {
+            Some(&index) => Ok(&self.0.registrations[index]),
+            None => Err(de::Error::custom(format_args!(
+                "unregistered component {}",
+                name
+            ))),
+        }
+    }
+}
+
+struct ComponentSeed<'a> {
+    registration: &'a SerdeRegistration,
+    builder: &'a mut EntityBuilder,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.registration.deserialize)(&mut deserializer, self.builder).map_err(de::Error::custom)
     }
 }
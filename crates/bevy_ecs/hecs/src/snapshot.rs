@@ -0,0 +1,152 @@
+// modified by Bevy contributors
+
+use crate::{
+    alloc::{boxed::Box, vec::Vec},
+    archetype::{ComponentId, ComponentTicks, TypeInfo},
+    entities::EntitiesSnapshot,
+    Component, Entity,
+};
+use core::{any::Any, fmt, mem, ptr::NonNull, slice};
+use hashbrown::{HashMap, HashSet};
+
+/// The component types that are saved by `World::snapshot`
+///
+/// Components of ignored types are not saved, and are dropped when a snapshot is restored. Taking
+/// a snapshot fails if the world has components of types that are neither registered nor ignored.
+#[derive(Default)]
+pub struct SnapshotRegistry {
+    registrations: HashMap<ComponentId, SnapshotRegistration>,
+    ignored: HashSet<ComponentId>,
+}
+
+impl SnapshotRegistry {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save `T` components in snapshots
+    pub fn register<T: Component + Clone>(&mut self) -> &mut Self {
+        self.registrations.insert(
+            ComponentId::of::<T>(),
+            SnapshotRegistration {
+                info: TypeInfo::of::<T>(),
+                clone_components: clone_components::<T>,
+                clone_component: clone_component::<T>,
+            },
+        );
+        self
+    }
+
+    /// Leave `T` components out of snapshots
+    pub fn ignore<T: Component>(&mut self) -> &mut Self {
+        self.ignore_id(ComponentId::of::<T>())
+    }
+
+    /// Leave components of the type `id` out of snapshots, such as runtime component types
+    pub fn ignore_id(&mut self, id: ComponentId) -> &mut Self {
+        self.ignored.insert(id);
+        self
+    }
+
+    /// The registration of the type `id`, `None` if it is ignored, or an error if it is unknown
+    pub(crate) fn get(
+        &self,
+        id: ComponentId,
+    ) -> Result<Option<SnapshotRegistration>, UnregisteredComponent> {
+        match self.registrations.get(&id) {
+            Some(registration) => Ok(Some(*registration)),
+            None if self.ignored.contains(&id) => Ok(None),
+            None => Err(UnregisteredComponent(id)),
+        }
+    }
+}
+
+/// Error indicating that `World::snapshot` found components of a type that is neither registered
+/// nor ignored in the `SnapshotRegistry`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnregisteredComponent(pub ComponentId);
+
+impl fmt::Display for UnregisteredComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "component {:?} is neither registered nor ignored for snapshots",
+            self.0
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnregisteredComponent {}
+
+#[derive(Copy, Clone)]
+pub(crate) struct SnapshotRegistration {
+    pub info: TypeInfo,
+    clone_components: unsafe fn(NonNull<u8>, usize) -> Box<dyn Any + Send + Sync>,
+    clone_component: fn(&(dyn Any + Send + Sync), usize, &mut dyn FnMut(*mut u8)),
+}
+
+unsafe fn clone_components<T: Component + Clone>(
+    components: NonNull<u8>,
+    len: usize,
+) -> Box<dyn Any + Send + Sync> {
+    Box::new(slice::from_raw_parts(components.cast::<T>().as_ptr(), len).to_vec())
+}
+
+fn clone_component<T: Component + Clone>(
+    components: &(dyn Any + Send + Sync),
+    index: usize,
+    f: &mut dyn FnMut(*mut u8),
+) {
+    let mut component = components.downcast_ref::<Vec<T>>().unwrap()[index].clone();
+    f((&mut component as *mut T).cast::<u8>());
+    mem::forget(component);
+}
+
+/// The saved state of a `World`, see `World::snapshot`
+pub struct WorldSnapshot {
+    pub(crate) entities: EntitiesSnapshot,
+    pub(crate) archetypes: Vec<ArchetypeSnapshot>,
+    pub(crate) sparse_sets: Vec<SparseSetSnapshot>,
+}
+
+/// The entities of an archetype, in order, and their registered components
+pub(crate) struct ArchetypeSnapshot {
+    pub entities: Vec<Entity>,
+    pub columns: Vec<ColumnSnapshot>,
+}
+
+/// The registered components of a sparse set, and the entities they belong to
+pub(crate) struct SparseSetSnapshot {
+    pub entities: Vec<Entity>,
+    pub column: ColumnSnapshot,
+}
+
+/// Clones of consecutive components of one type, and their ticks
+pub(crate) struct ColumnSnapshot {
+    pub registration: SnapshotRegistration,
+    components: Box<dyn Any + Send + Sync>,
+    pub ticks: Vec<ComponentTicks>,
+}
+
+impl ColumnSnapshot {
+    /// # Safety
+    /// `components` must point to `ticks.len()` components of the registered type
+    pub unsafe fn new(
+        registration: SnapshotRegistration,
+        components: NonNull<u8>,
+        ticks: &[ComponentTicks],
+    ) -> Self {
+        Self {
+            registration,
+            components: (registration.clone_components)(components, ticks.len()),
+            ticks: ticks.to_vec(),
+        }
+    }
+
+    /// Passes a clone of the component at `index` to `f`, which must move it out of the pointer
+    pub fn clone_component(&self, index: usize, mut f: impl FnMut(*mut u8)) {
+        (self.registration.clone_component)(&*self.components, index, &mut f)
+    }
+}
@@ -0,0 +1,252 @@
+// modified by Bevy contributors
+
+use crate::{
+    alloc::{
+        alloc::{alloc, dealloc, Layout},
+        vec::Vec,
+    },
+    archetype::{ComponentTicks, TypeInfo},
+    Entity,
+};
+use core::{
+    cell::UnsafeCell,
+    ptr::{self, NonNull},
+};
+
+/// How the components of a type are stored in a `World`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StorageType {
+    /// Components are stored in the tables of archetypes, which is fastest to iterate. Adding or
+    /// removing the component moves the entity to another archetype.
+    Table,
+    /// Components are stored in a sparse set outside of archetypes, so adding or removing them
+    /// does not move the entity. Iteration is slower, as each entity is looked up in the set.
+    SparseSet,
+}
+
+impl Default for StorageType {
+    fn default() -> Self {
+        StorageType::Table
+    }
+}
+
+const EMPTY: u32 = u32::MAX;
+
+/// The components of one type whose storage type is `StorageType::SparseSet`
+///
+/// Components are packed densely in no particular order and looked up through an array indexed by
+/// entity id.
+pub(crate) struct SparseSet {
+    ty: TypeInfo,
+    // Dense index of each entity id's component, or `EMPTY`
+    sparse: Vec<u32>,
+    entities: Vec<Entity>,
+    // UnsafeCell allows queries holding shared references to the set to mutate components and
+    // their ticks
+    ticks: UnsafeCell<Vec<ComponentTicks>>,
+    data: UnsafeCell<NonNull<u8>>,
+    capacity: usize,
+}
+
+impl SparseSet {
+    pub fn new(ty: TypeInfo) -> Self {
+        Self {
+            ty,
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            ticks: UnsafeCell::new(Vec::new()),
+            // dangling, but aligned for zero sized components
+            data: UnsafeCell::new(unsafe {
+                NonNull::new_unchecked(ty.layout().align() as *mut u8)
+            }),
+            capacity: 0,
+        }
+    }
+
+    pub fn type_info(&self) -> TypeInfo {
+        self.ty
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// The entities that have a component in this set
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// The components of `entities`, in the same order
+    pub fn data(&self) -> NonNull<u8> {
+        unsafe { *self.data.get() }
+    }
+
+    /// The ticks of the components of `entities`, in the same order
+    pub fn ticks(&self) -> &[ComponentTicks] {
+        unsafe { &*self.ticks.get() }
+    }
+
+    #[inline]
+    fn dense_index(&self, entity: Entity) -> Option<usize> {
+        match self.sparse.get(entity.id as usize) {
+            Some(&index) if index != EMPTY => Some(index as usize),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.dense_index(entity).is_some()
+    }
+
+    /// The component and ticks of `entity`, if it has a component in this set
+    #[inline]
+    pub fn get(&self, entity: Entity) -> Option<(NonNull<u8>, NonNull<ComponentTicks>)> {
+        let index = self.dense_index(entity)?;
+        unsafe {
+            Some((
+                NonNull::new_unchecked(
+                    (*self.data.get())
+                        .as_ptr()
+                        .add(index * self.ty.layout().size()),
+                ),
+                NonNull::new_unchecked((*self.ticks.get()).as_mut_ptr().add(index)),
+            ))
+        }
+    }
+
+    /// Moves `component` into the set. If `entity` already has a component, it is dropped and
+    /// replaced and its ticks are kept, otherwise the component is tracked as added at
+    /// `change_tick`.
+    pub unsafe fn insert(&mut self, entity: Entity, component: *mut u8, change_tick: u32) {
+        let size = self.ty.layout().size();
+        if let Some((ptr, _)) = self.get(entity) {
+            self.ty.drop(ptr.as_ptr());
+            ptr::copy_nonoverlapping(component, ptr.as_ptr(), size);
+            return;
+        }
+
+        let id = entity.id as usize;
+        if id >= self.sparse.len() {
+            self.sparse.resize(id + 1, EMPTY);
+        }
+        let index = self.entities.len();
+        if index == self.capacity {
+            self.grow();
+        }
+        self.sparse[id] = index as u32;
+        self.entities.push(entity);
+        self.ticks.get_mut().push(ComponentTicks::new(change_tick));
+        ptr::copy_nonoverlapping(
+            component,
+            (*self.data.get()).as_ptr().add(index * size),
+            size,
+        );
+    }
+
+    /// Drops the component of `entity`, returning whether it had one
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        match self.dense_index(entity) {
+            Some(index) => {
+                unsafe {
+                    self.ty.drop(
+                        (*self.data.get())
+                            .as_ptr()
+                            .add(index * self.ty.layout().size()),
+                    );
+                    self.swap_remove(index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the component of `entity` without dropping it
+    ///
+    /// # Safety
+    /// The component must have been moved out of the set already
+    pub unsafe fn forget(&mut self, entity: Entity) {
+        if let Some(index) = self.dense_index(entity) {
+            self.swap_remove(index);
+        }
+    }
+
+    /// Fills the gap at `index`, whose component must already be dropped or moved out, with the
+    /// last component
+    unsafe fn swap_remove(&mut self, index: usize) {
+        let size = self.ty.layout().size();
+        let last = self.entities.len() - 1;
+        let removed = self.entities.swap_remove(index);
+        self.ticks.get_mut().swap_remove(index);
+        self.sparse[removed.id as usize] = EMPTY;
+        if index != last {
+            let data = (*self.data.get()).as_ptr();
+            ptr::copy_nonoverlapping(data.add(last * size), data.add(index * size), size);
+            self.sparse[self.entities[index].id as usize] = index as u32;
+        }
+    }
+
+    /// Drops every component
+    pub fn clear(&mut self) {
+        let size = self.ty.layout().size();
+        for (index, entity) in self.entities.iter().enumerate() {
+            unsafe {
+                self.ty.drop((*self.data.get()).as_ptr().add(index * size));
+            }
+            self.sparse[entity.id as usize] = EMPTY;
+        }
+        self.entities.clear();
+        self.ticks.get_mut().clear();
+    }
+
+    /// Clamps component ticks that are too old to be compared against `change_tick`. See
+    /// `World::check_change_ticks`.
+    pub fn check_change_ticks(&mut self, change_tick: u32) {
+        for ticks in self.ticks.get_mut().iter_mut() {
+            ticks.check_ticks(change_tick);
+        }
+    }
+
+    fn grow(&mut self) {
+        let size = self.ty.layout().size();
+        let new_capacity = (self.capacity * 2).max(4);
+        if size != 0 {
+            unsafe {
+                let new_data = NonNull::new(alloc(
+                    Layout::from_size_align(size * new_capacity, self.ty.layout().align()).unwrap(),
+                ))
+                .unwrap();
+                if self.capacity != 0 {
+                    ptr::copy_nonoverlapping(
+                        (*self.data.get()).as_ptr(),
+                        new_data.as_ptr(),
+                        size * self.entities.len(),
+                    );
+                    self.dealloc();
+                }
+                self.data = UnsafeCell::new(new_data);
+            }
+        }
+        self.capacity = new_capacity;
+    }
+
+    unsafe fn dealloc(&mut self) {
+        let size = self.ty.layout().size();
+        if size != 0 && self.capacity != 0 {
+            dealloc(
+                (*self.data.get()).as_ptr(),
+                Layout::from_size_align_unchecked(size * self.capacity, self.ty.layout().align()),
+            );
+        }
+    }
+}
+
+impl Drop for SparseSet {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe {
+            self.dealloc();
+        }
+    }
+}
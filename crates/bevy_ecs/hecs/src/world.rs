@@ -14,8 +14,15 @@
 
 // modified by Bevy contributors
 
-use crate::alloc::vec::Vec;
-use core::{any::TypeId, convert::TryFrom, fmt, mem, ptr};
+use crate::alloc::{boxed::Box, vec::Vec};
+use core::{
+    any::type_name,
+    convert::TryFrom,
+    fmt,
+    ptr::{self, NonNull},
+    slice,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 #[cfg(feature = "std")]
 use std::error::Error;
@@ -23,8 +30,18 @@ use std::error::Error;
 use hashbrown::{HashMap, HashSet};
 
 use crate::{
-    archetype::Archetype,
-    entities::{Entities, Location},
+    archetype::{Archetype, ComponentId, TypeInfo, CHECK_TICK_THRESHOLD},
+    component_hooks::{ComponentHook, ComponentHooks},
+    dynamic::{ComponentDescriptor, DynamicQuery, DynamicQueryBorrow},
+    entities::{Entities, EntityReserver, Location},
+    entity_builder::EntityBuilder,
+    entity_map::{EntityMap, MapEntities, MapEntitiesError},
+    removed_components::RemovedComponents,
+    snapshot::{
+        ArchetypeSnapshot, ColumnSnapshot, SnapshotRegistry, SparseSetSnapshot,
+        UnregisteredComponent, WorldSnapshot,
+    },
+    sparse_set::{SparseSet, StorageType},
     Bundle, DynamicBundle, Entity, EntityRef, MissingComponent, NoSuchEntity, Query, QueryBorrow,
     QueryOne, Ref, RefMut,
 };
@@ -38,11 +55,21 @@ use crate::{
 /// runs, allowing for extremely fast, cache-friendly iteration.
 pub struct World {
     entities: Entities,
-    index: HashMap<Vec<TypeId>, u32>,
-    removed_components: HashMap<TypeId, Vec<Entity>>,
+    index: HashMap<Vec<ComponentId>, u32>,
+    pub(crate) removed_components: HashMap<ComponentId, RemovedComponents>,
     #[allow(missing_docs)]
     pub archetypes: Vec<Archetype>,
+    // Boxed, as archetypes point to the sparse sets
+    sparse_sets: HashMap<ComponentId, Box<SparseSet>>,
+    // The archetypes of spawned bundles that have sparse set components, which `index` can't find
+    bundle_archetypes: HashMap<Vec<ComponentId>, u32>,
     archetype_generation: u64,
+    change_tick: AtomicU32,
+    last_change_tick: u32,
+    last_check_tick: u32,
+    hooks: HashMap<ComponentId, ComponentHooks>,
+    // Indexed by `ComponentId::ExternalId`
+    component_descriptors: Vec<ComponentDescriptor>,
 }
 
 impl World {
@@ -57,8 +84,15 @@ impl World {
             entities: Entities::default(),
             index,
             archetypes,
+            sparse_sets: HashMap::default(),
+            bundle_archetypes: HashMap::default(),
             archetype_generation: 0,
             removed_components: HashMap::default(),
+            change_tick: AtomicU32::new(1),
+            last_change_tick: 0,
+            last_check_tick: 0,
+            hooks: HashMap::default(),
+            component_descriptors: Vec::new(),
         }
     }
 
@@ -81,8 +115,9 @@ impl World {
     /// let b = world.spawn((456, true));
     /// ```
     pub fn spawn(&mut self, components: impl DynamicBundle) -> Entity {
-        let entity = Entity::new();
-        self.spawn_as_entity(entity, components);
+        self.flush();
+        let entity = self.entities.alloc();
+        self.spawn_inner(entity, components);
         entity
     }
 
@@ -94,31 +129,67 @@ impl World {
     /// `(x,)`.
     ///
     /// Any type that satisfies `Send + Sync + 'static` can be used as a component.
+    ///
+    /// If an entity with the same id already exists, it is despawned first, regardless of its
+    /// generation. This is intended for entities reserved with `reserve_entity` and for recreating
+    /// entities from serialized ids.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// let mut world = World::new();
+    /// let a = world.reserve_entity();
+    /// world.spawn_as_entity(a, (123, "abc"));
+    /// assert_eq!(*world.get::<i32>(a).unwrap(), 123);
+    /// ```
     pub fn spawn_as_entity(&mut self, entity: Entity, components: impl DynamicBundle) {
-        let archetype_id = components.with_ids(|ids| {
-            self.index.get(ids).copied().unwrap_or_else(|| {
-                let x = self.archetypes.len() as u32;
-                self.archetypes.push(Archetype::new(components.type_info()));
-                self.index.insert(ids.to_vec(), x);
-                self.archetype_generation += 1;
-                x
-            })
-        });
+        self.flush();
+        // The replaced entity may be of an older generation, so removals are recorded under
+        // its own handle rather than the new one
+        let existing = self.entities.resolve_id(entity.id);
+        if let Some(existing) = existing {
+            if !self.hooks.is_empty() {
+                self.run_remove_hooks(existing);
+            }
+        }
+        if let Some(location) = self.entities.alloc_at(entity) {
+            self.remove_from_archetype(existing.unwrap(), location);
+        }
+        self.spawn_inner(entity, components);
+    }
+
+    fn spawn_inner(&mut self, entity: Entity, components: impl DynamicBundle) {
+        let hooked_types = if self.hooks.is_empty() {
+            None
+        } else {
+            Some(components.with_ids(|ids| ids.to_vec()))
+        };
+
+        let archetype_id =
+            components.with_ids(|ids| self.bundle_archetype(ids, || components.type_info()));
 
+        let change_tick = self.change_tick();
         let archetype = &mut self.archetypes[archetype_id as usize];
+        let sparse_sets = &mut self.sparse_sets;
         unsafe {
-            let index = archetype.allocate(entity.id());
+            let index = archetype.allocate(entity);
             components.put(|ptr, ty, size| {
-                archetype.put_dynamic(ptr, ty, size, index, true);
+                if let Some(set) = sparse_sets.get_mut(&ty) {
+                    set.insert(entity, ptr, change_tick);
+                } else {
+                    archetype.put_dynamic(ptr, ty, size, index, Some(change_tick));
+                }
                 true
             });
-            self.entities.insert(
-                entity,
-                Location {
-                    archetype: archetype_id,
-                    index,
-                },
-            );
+            self.entities.meta[entity.id as usize].location = Location {
+                archetype: archetype_id,
+                index,
+            };
+        }
+
+        if let Some(types) = hooked_types {
+            self.run_hooks(entity, &types, |hooks| &hooks.on_add);
+            self.run_hooks(entity, &types, |hooks| &hooks.on_insert);
         }
     }
 
@@ -140,35 +211,58 @@ impl World {
         I: IntoIterator,
         I::Item: Bundle,
     {
+        self.flush();
         let iter = iter.into_iter();
         let (lower, upper) = iter.size_hint();
         let archetype_id = self.reserve_inner::<I::Item>(
             u32::try_from(upper.unwrap_or(lower)).expect("iterator too large"),
         );
 
+        let hooked_entities =
+            if I::Item::with_static_ids(|ids| ids.iter().any(|ty| self.hooks.contains_key(ty))) {
+                Some(Vec::new())
+            } else {
+                None
+            };
         SpawnBatchIter {
             inner: iter,
-            entities: &mut self.entities,
+            change_tick: *self.change_tick.get_mut(),
+            world: self,
             archetype_id,
-            archetype: &mut self.archetypes[archetype_id as usize],
+            hooked_entities,
         }
     }
 
     /// Destroy an entity and all its components
     pub fn despawn(&mut self, entity: Entity) -> Result<(), NoSuchEntity> {
+        self.flush();
+        if !self.hooks.is_empty() {
+            self.entities.get(entity)?;
+            self.run_remove_hooks(entity);
+        }
         let loc = self.entities.free(entity)?;
+        self.remove_from_archetype(entity, loc);
+        Ok(())
+    }
+
+    /// Drops the components of `entity` stored at `loc`. The entity's id must already be freed or
+    /// reallocated.
+    fn remove_from_archetype(&mut self, entity: Entity, loc: Location) {
         let archetype = &mut self.archetypes[loc.archetype as usize];
         if let Some(moved) = unsafe { archetype.remove(loc.index) } {
-            self.entities.get_mut(Entity::from_id(moved)).unwrap().index = loc.index;
+            self.entities.get_mut(moved).unwrap().index = loc.index;
         }
         for ty in archetype.types() {
-            let removed_entities = self
-                .removed_components
+            self.removed_components
                 .entry(ty.id())
-                .or_insert_with(|| Vec::new());
-            removed_entities.push(entity);
+                .or_default()
+                .push(entity);
+        }
+        for (&ty, set) in self.sparse_sets.iter_mut() {
+            if set.remove(entity) {
+                self.removed_components.entry(ty).or_default().push(entity);
+            }
         }
-        Ok(())
     }
 
     /// Ensure `additional` entities with exact components `T` can be spawned without reallocating
@@ -179,15 +273,8 @@ impl World {
     fn reserve_inner<T: Bundle>(&mut self, additional: u32) -> u32 {
         self.entities.reserve(additional);
 
-        let archetype_id = T::with_static_ids(|ids| {
-            self.index.get(ids).copied().unwrap_or_else(|| {
-                let x = self.archetypes.len() as u32;
-                self.archetypes.push(Archetype::new(T::static_type_info()));
-                self.index.insert(ids.to_vec(), x);
-                self.archetype_generation += 1;
-                x
-            })
-        });
+        let archetype_id =
+            T::with_static_ids(|ids| self.bundle_archetype(ids, T::static_type_info));
 
         self.archetypes[archetype_id as usize].reserve(additional);
         archetype_id
@@ -197,24 +284,379 @@ impl World {
     ///
     /// Preserves allocated storage for reuse.
     pub fn clear(&mut self) {
+        self.flush();
+        if !self.hooks.is_empty() {
+            let entities = self
+                .archetypes
+                .iter()
+                .flat_map(|archetype| archetype.iter_entities().copied())
+                .collect::<Vec<_>>();
+            for entity in entities {
+                self.run_remove_hooks(entity);
+            }
+        }
         for archetype in &mut self.archetypes {
             for ty in archetype.types() {
-                let removed_entities = self
-                    .removed_components
+                self.removed_components
                     .entry(ty.id())
-                    .or_insert_with(|| Vec::new());
-                removed_entities.extend(archetype.iter_entities().map(|id| Entity::from_id(*id)));
+                    .or_default()
+                    .extend(archetype.iter_entities().copied());
             }
             archetype.clear();
         }
+        for (&ty, set) in self.sparse_sets.iter_mut() {
+            self.removed_components
+                .entry(ty)
+                .or_default()
+                .extend(set.entities().iter().copied());
+            set.clear();
+        }
         self.entities.clear();
     }
 
+    /// Save the entities of the world and their components of the types registered in `registry`,
+    /// which can be restored with `restore`
+    ///
+    /// Fails if the world has components of a type that `registry` neither registers nor ignores.
+    /// Panics if a registered component is borrowed uniquely.
+    pub fn snapshot(
+        &self,
+        registry: &SnapshotRegistry,
+    ) -> Result<WorldSnapshot, UnregisteredComponent> {
+        let mut archetypes = Vec::new();
+        for archetype in self.archetypes.iter().filter(|x| x.len() != 0) {
+            let mut columns = Vec::new();
+            for ty in archetype.types() {
+                if let Some(registration) = registry.get(ty.id())? {
+                    archetype.borrow_dynamic(ty.id());
+                    let (components, ticks) = archetype.column_dynamic(ty.id()).unwrap();
+                    columns.push(unsafe { ColumnSnapshot::new(registration, components, ticks) });
+                    archetype.release_dynamic(ty.id());
+                }
+            }
+            archetypes.push(ArchetypeSnapshot {
+                entities: archetype.iter_entities().copied().collect(),
+                columns,
+            });
+        }
+
+        let mut sparse_sets = Vec::new();
+        for (&ty, set) in self.sparse_sets.iter().filter(|(_, set)| !set.is_empty()) {
+            if let Some(registration) = registry.get(ty)? {
+                for archetype in &self.archetypes {
+                    archetype.borrow_dynamic(ty);
+                }
+                sparse_sets.push(SparseSetSnapshot {
+                    entities: set.entities().to_vec(),
+                    column: unsafe { ColumnSnapshot::new(registration, set.data(), set.ticks()) },
+                });
+                for archetype in &self.archetypes {
+                    archetype.release_dynamic(ty);
+                }
+            }
+        }
+
+        Ok(WorldSnapshot {
+            entities: self.entities.snapshot(),
+            archetypes,
+            sparse_sets,
+        })
+    }
+
+    /// Replace the entities of the world and their components with those saved in `snapshot`
+    ///
+    /// Entities get the same ids and generations they had. Ids of entities spawned after the snapshot
+    /// was taken are reused with a newer generation, and ids reserved since then are never reserved
+    /// again. Restored components keep the ticks they had, so restoring is not detected as a change.
+    /// Component hooks do not run and removals are not tracked.
+    ///
+    /// Panics if components were saved in a sparse set, but their type is no longer stored in one.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// let mut registry = SnapshotRegistry::new();
+    /// registry.register::<i32>();
+    /// let mut world = World::new();
+    /// let a = world.spawn((1,));
+    /// let snapshot = world.snapshot(&registry).unwrap();
+    /// *world.get_mut::<i32>(a).unwrap() = 2;
+    /// let b = world.spawn((3,));
+    /// world.restore(&snapshot);
+    /// assert_eq!(*world.get::<i32>(a).unwrap(), 1);
+    /// assert!(!world.contains(b));
+    /// let c = world.spawn((3,));
+    /// assert_eq!(c.id(), b.id());
+    /// assert_ne!(c, b);
+    /// ```
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+        for sparse_set_snapshot in &snapshot.sparse_sets {
+            let id = sparse_set_snapshot.column.registration.info.id();
+            assert!(
+                self.sparse_sets.contains_key(&id),
+                "cannot restore the components of {:?} saved in a sparse set, since they are no longer stored in one",
+                id
+            );
+        }
+        for archetype in &mut self.archetypes {
+            archetype.clear();
+        }
+        for set in self.sparse_sets.values_mut() {
+            set.clear();
+        }
+        self.entities.restore(&snapshot.entities);
+
+        for archetype_snapshot in &snapshot.archetypes {
+            let archetype_id = self.table_archetype(
+                archetype_snapshot
+                    .columns
+                    .iter()
+                    .map(|column| column.registration.info)
+                    .collect(),
+            );
+            let archetype = &mut self.archetypes[archetype_id as usize];
+            archetype.reserve(archetype_snapshot.entities.len() as u32);
+            for (row, &entity) in archetype_snapshot.entities.iter().enumerate() {
+                let index = unsafe { archetype.allocate(entity) };
+                self.entities.meta[entity.id as usize].location = Location {
+                    archetype: archetype_id,
+                    index,
+                };
+                for column in &archetype_snapshot.columns {
+                    let ty = column.registration.info;
+                    let ticks = column.ticks[row];
+                    match self.sparse_sets.get_mut(&ty.id()) {
+                        Some(set) => column.clone_component(row, |component| unsafe {
+                            set.insert(entity, component, 0);
+                            *set.get(entity).unwrap().1.as_ptr() = ticks;
+                        }),
+                        None => column.clone_component(row, |component| unsafe {
+                            archetype.put_dynamic(
+                                component,
+                                ty.id(),
+                                ty.layout().size(),
+                                index,
+                                None,
+                            );
+                            archetype
+                                .get_type_state_mut(ty.id())
+                                .unwrap()
+                                .component_ticks[index as usize] = ticks;
+                        }),
+                    }
+                }
+            }
+        }
+
+        for sparse_set_snapshot in &snapshot.sparse_sets {
+            let column = &sparse_set_snapshot.column;
+            let set = self
+                .sparse_sets
+                .get_mut(&column.registration.info.id())
+                .unwrap();
+            for (row, &entity) in sparse_set_snapshot.entities.iter().enumerate() {
+                column.clone_component(row, |component| unsafe {
+                    set.insert(entity, component, 0);
+                    *set.get(entity).unwrap().1.as_ptr() = column.ticks[row];
+                });
+            }
+        }
+    }
+
+    /// Move the entities of `other` that `filter` accepts into this world, returning the map from
+    /// the moved entities to the entities they were spawned as
+    ///
+    /// Every component is moved, including components stored in sparse sets and components of
+    /// types registered with `register_component`. Runtime component types are registered in this
+    /// world as they are encountered, unless a type with the same name and layout already is, and
+    /// their components are given the ids of this world. The `on_remove` hooks of `other` and the `on_add`
+    /// and `on_insert` hooks of this world run, and the components are tracked as added. Components
+    /// that refer to the moved entities can be updated with `map_entities`. References between the
+    /// moved entities and the entities left in `other` are not updated, so they refer to entities
+    /// that no longer exist.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// let mut staging = World::new();
+    /// let a = staging.spawn((123, "abc"));
+    /// let b = staging.spawn((456,));
+    /// let mut world = World::new();
+    /// let entity_map = world.move_entities_from(&mut staging, |entity| entity.get::<&str>().is_some());
+    /// assert_eq!(*world.get::<i32>(entity_map.get(a).unwrap()).unwrap(), 123);
+    /// assert!(!staging.contains(a));
+    /// assert!(staging.contains(b));
+    /// ```
+    pub fn move_entities_from(
+        &mut self,
+        other: &mut World,
+        mut filter: impl FnMut(EntityRef<'_>) -> bool,
+    ) -> EntityMap {
+        other.flush();
+        let moved = other
+            .iter()
+            .filter_map(|(entity, entity_ref)| Some(entity).filter(|_| filter(entity_ref)))
+            .collect::<Vec<_>>();
+
+        let mut entity_map = EntityMap::new();
+        let mut builder = EntityBuilder::new();
+        // runtime component types of `other`, by their type info in this world
+        let mut external_types = HashMap::new();
+        for entity in moved {
+            if !other.hooks.is_empty() {
+                other.run_remove_hooks(entity);
+            }
+            // a hook may have despawned the entity
+            let loc = match other.entities.free(entity) {
+                Ok(loc) => loc,
+                Err(NoSuchEntity) => continue,
+            };
+            for id in other.component_ids(entity, loc) {
+                if let ComponentId::ExternalId(index) = id {
+                    if !external_types.contains_key(&id) {
+                        let descriptor = &other.component_descriptors[index as usize];
+                        external_types.insert(id, self.import_component(descriptor));
+                    }
+                }
+            }
+            unsafe {
+                other.take_components(entity, loc, &mut builder, &external_types);
+            }
+            entity_map.insert(entity, self.spawn(builder.build()));
+        }
+        entity_map
+    }
+
+    /// The ids of the components of `entity`, stored at `loc`
+    fn component_ids(&self, entity: Entity, loc: Location) -> Vec<ComponentId> {
+        self.archetypes[loc.archetype as usize]
+            .types()
+            .iter()
+            .map(|ty| ty.id())
+            .chain(
+                self.sparse_sets
+                    .iter()
+                    .filter(|(_, set)| set.contains(entity))
+                    .map(|(&ty, _)| ty),
+            )
+            .collect()
+    }
+
+    /// The type info of the runtime component type described by `descriptor` in this world,
+    /// registering it unless a type with the same name and layout already is
+    fn import_component(&mut self, descriptor: &ComponentDescriptor) -> TypeInfo {
+        let existing = self.component_descriptors.iter().position(|existing| {
+            existing.name() == descriptor.name() && existing.layout() == descriptor.layout()
+        });
+        match existing {
+            Some(index) => self
+                .component_type_info(ComponentId::ExternalId(index as u64))
+                .unwrap(),
+            None => self.register_component(descriptor.clone()),
+        }
+    }
+
+    /// Moves the components of `entity` stored at `loc` into `builder` without dropping them,
+    /// replacing the type info of runtime component types with `external_types`. The entity's id
+    /// must already be freed.
+    unsafe fn take_components(
+        &mut self,
+        entity: Entity,
+        loc: Location,
+        builder: &mut EntityBuilder,
+        external_types: &HashMap<ComponentId, TypeInfo>,
+    ) {
+        let archetype = &mut self.archetypes[loc.archetype as usize];
+        let types = archetype.types().to_vec();
+        let removed_components = &mut self.removed_components;
+        if let Some(moved) = archetype.move_to(loc.index, |component, ty, size, _| {
+            let info = match external_types.get(&ty) {
+                Some(info) => *info,
+                None => *types.iter().find(|info| info.id() == ty).unwrap(),
+            };
+            builder.add_dynamic(info, slice::from_raw_parts(component, size));
+            removed_components.entry(ty).or_default().push(entity);
+        }) {
+            self.entities.get_mut(moved).unwrap().index = loc.index;
+        }
+        for (&ty, set) in self.sparse_sets.iter_mut() {
+            if let Some((component, _)) = set.get(entity) {
+                let info = external_types
+                    .get(&ty)
+                    .copied()
+                    .unwrap_or_else(|| set.type_info());
+                builder.add_dynamic(
+                    info,
+                    slice::from_raw_parts(component.as_ptr(), info.layout().size()),
+                );
+                set.forget(entity);
+                removed_components.entry(ty).or_default().push(entity);
+            }
+        }
+    }
+
+    /// Update the `T` components of the entities mapped to by `entity_map` to refer to the entities
+    /// they are mapped to, such as after `move_entities_from`
+    ///
+    /// Stops at the first entity that refers to an unmapped entity.
+    pub fn map_entities<T: Component + MapEntities>(
+        &mut self,
+        entity_map: &EntityMap,
+    ) -> Result<(), MapEntitiesError> {
+        for entity in entity_map.values() {
+            if let Ok(mut component) = self.get_mut::<T>(entity) {
+                component.map_entities(entity_map)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Whether `entity` still exists
+    ///
+    /// Reserved entities are not considered to exist until the world is flushed.
     pub fn contains(&self, entity: Entity) -> bool {
         self.entities.contains(entity)
     }
 
+    /// Allocate an entity id without borrowing the world mutably
+    ///
+    /// The entity is added to the world without any components the next time the world is flushed.
+    /// Use `spawn_as_entity` or `insert` to give it components.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// let mut world = World::new();
+    /// let a = world.reserve_entity();
+    /// assert!(!world.contains(a));
+    /// world.flush();
+    /// assert!(world.contains(a));
+    /// ```
+    pub fn reserve_entity(&self) -> Entity {
+        self.entities.get_reserver().reserve_entity()
+    }
+
+    /// Returns a handle that can reserve entities for this world from any thread
+    ///
+    /// See `reserve_entity`.
+    pub fn get_entity_reserver(&self) -> EntityReserver {
+        self.entities.get_reserver()
+    }
+
+    /// Convert all reserved entities into empty entities that can be iterated and accessed
+    ///
+    /// Invoked implicitly by `spawn`, `despawn`, `insert`, and `remove`.
+    pub fn flush(&mut self) {
+        if !self.entities.needs_flush() {
+            return;
+        }
+        let archetype = &mut self.archetypes[0];
+        self.entities.flush(|entity, location| {
+            location.archetype = 0;
+            location.index = unsafe { archetype.allocate(entity) };
+        });
+    }
+
     /// Efficiently iterate over all entities that have certain components
     ///
     /// Calling `iter` on the returned value yields `(Entity, Q)` tuples, where `Q` is some query
@@ -255,7 +697,80 @@ impl World {
     /// assert!(entities.contains(&(b, 456, false)));
     /// ```
     pub fn query<Q: Query>(&self) -> QueryBorrow<'_, Q> {
-        QueryBorrow::new(&self.archetypes)
+        self.query_with_ticks(self.last_change_tick, self.change_tick())
+    }
+
+    /// Like `query`, but detects changes made after `last_change_tick` instead of since the last
+    /// call to `clear_trackers`, and records mutations at `change_tick`
+    ///
+    /// Useful for systems that track the tick they last ran at. See `increment_change_tick`.
+    pub fn query_with_ticks<Q: Query>(
+        &self,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> QueryBorrow<'_, Q> {
+        QueryBorrow::new(&self.archetypes, last_change_tick, change_tick)
+    }
+
+    /// Efficiently iterate over the components identified by `query`, which may be defined at
+    /// runtime
+    ///
+    /// Panics if a component is already borrowed incompatibly, like `query`.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// # use std::alloc::Layout;
+    /// let mut world = World::new();
+    /// let health = world.register_component(ComponentDescriptor::new("Health", Layout::new::<u32>()));
+    /// let mut builder = EntityBuilder::new();
+    /// unsafe { builder.add_dynamic(health, &10u32.to_ne_bytes()) };
+    /// let a = world.spawn(builder.build());
+    ///
+    /// let query = DynamicQuery::new().write(health.id());
+    /// for mut item in world.query_dynamic(query).iter() {
+    ///     item.write[0].copy_from_slice(&20u32.to_ne_bytes());
+    /// }
+    /// let query = DynamicQuery::new().read(health.id());
+    /// let mut borrow = world.query_dynamic(query);
+    /// let item = borrow.iter().next().unwrap();
+    /// assert_eq!(item.entity, a);
+    /// assert_eq!(item.read[0], &20u32.to_ne_bytes());
+    /// ```
+    pub fn query_dynamic(&self, query: DynamicQuery) -> DynamicQueryBorrow<'_> {
+        DynamicQueryBorrow::new(&self.archetypes, query, self.change_tick())
+    }
+
+    /// Register a component type defined at runtime, returning the `TypeInfo` used to add it to
+    /// entities. Each call registers a new type, even if an identical descriptor was registered
+    /// before.
+    pub fn register_component(&mut self, descriptor: ComponentDescriptor) -> TypeInfo {
+        let id = ComponentId::ExternalId(self.component_descriptors.len() as u64);
+        self.component_descriptors.push(descriptor);
+        self.component_type_info(id).unwrap()
+    }
+
+    /// The descriptor of a component type registered with `register_component`
+    pub fn component_descriptor(&self, id: ComponentId) -> Option<&ComponentDescriptor> {
+        match id {
+            ComponentId::ExternalId(id) => self.component_descriptors.get(id as usize),
+            ComponentId::RustTypeId(_) => None,
+        }
+    }
+
+    /// The `TypeInfo` of a component type registered with `register_component`
+    pub fn component_type_info(&self, id: ComponentId) -> Option<TypeInfo> {
+        match id {
+            ComponentId::ExternalId(external_id) => {
+                let descriptor = self.component_descriptor(id)?;
+                Some(TypeInfo::external(
+                    external_id,
+                    descriptor.layout(),
+                    descriptor.drop_fn(),
+                ))
+            }
+            ComponentId::RustTypeId(_) => None,
+        }
     }
 
     /// Prepare a query against a single entity
@@ -278,8 +793,25 @@ impl World {
     /// assert_eq!(*number, 246);
     /// ```
     pub fn query_one<Q: Query>(&self, entity: Entity) -> Result<QueryOne<'_, Q>, NoSuchEntity> {
+        self.query_one_with_ticks(entity, self.last_change_tick, self.change_tick())
+    }
+
+    /// Like `query_one`, but with explicit change ticks. See `query_with_ticks`.
+    pub fn query_one_with_ticks<Q: Query>(
+        &self,
+        entity: Entity,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Result<QueryOne<'_, Q>, NoSuchEntity> {
         let loc = self.entities.get(entity)?;
-        Ok(unsafe { QueryOne::new(&self.archetypes[loc.archetype as usize], loc.index) })
+        Ok(unsafe {
+            QueryOne::new(
+                &self.archetypes[loc.archetype as usize],
+                loc.index,
+                last_change_tick,
+                change_tick,
+            )
+        })
     }
 
     /// Borrow the `T` component of `entity`
@@ -288,9 +820,6 @@ impl World {
     /// components.
     pub fn get<T: Component>(&self, entity: Entity) -> Result<Ref<'_, T>, ComponentError> {
         let loc = self.entities.get(entity)?;
-        if loc.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(unsafe { Ref::new(&self.archetypes[loc.archetype as usize], loc.index)? })
     }
 
@@ -298,20 +827,36 @@ impl World {
     ///
     /// Panics if the component is already borrowed from another entity with the same components.
     pub fn get_mut<T: Component>(&self, entity: Entity) -> Result<RefMut<'_, T>, ComponentError> {
+        self.get_mut_with_tick(entity, self.change_tick())
+    }
+
+    /// Like `get_mut`, but records mutations at `change_tick`. See `query_with_ticks`.
+    pub fn get_mut_with_tick<T: Component>(
+        &self,
+        entity: Entity,
+        change_tick: u32,
+    ) -> Result<RefMut<'_, T>, ComponentError> {
         let loc = self.entities.get(entity)?;
-        if loc.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
-        Ok(unsafe { RefMut::new(&self.archetypes[loc.archetype as usize], loc.index)? })
+        Ok(unsafe {
+            RefMut::new(
+                &self.archetypes[loc.archetype as usize],
+                loc.index,
+                change_tick,
+            )?
+        })
     }
 
     /// Access an entity regardless of its component types
     ///
     /// Does not immediately borrow any component.
     pub fn entity(&self, entity: Entity) -> Result<EntityRef<'_>, NoSuchEntity> {
-        Ok(match self.entities.get(entity)? {
-            Location { archetype: 0, .. } => EntityRef::empty(),
-            loc => unsafe { EntityRef::new(&self.archetypes[loc.archetype as usize], loc.index) },
+        let loc = self.entities.get(entity)?;
+        Ok(unsafe {
+            EntityRef::new(
+                &self.archetypes[loc.archetype as usize],
+                loc.index,
+                self.change_tick(),
+            )
         })
     }
 
@@ -332,14 +877,46 @@ impl World {
     /// assert!(ids.contains(&b));
     /// ```
     pub fn iter(&self) -> Iter<'_> {
-        Iter::new(&self.archetypes, &self.entities)
+        Iter::new(&self.archetypes, &self.entities, self.change_tick())
     }
 
-    #[allow(missing_docs)]
+    /// The hooks that run when `T` components are added to, inserted on, or removed from entities
+    pub fn component_hooks<T: Component>(&mut self) -> &mut ComponentHooks {
+        self.hooks.entry(ComponentId::of::<T>()).or_default()
+    }
+
+    /// Runs the hooks selected by `select` for each of `types` on `entity`
+    fn run_hooks(
+        &mut self,
+        entity: Entity,
+        types: &[ComponentId],
+        select: fn(&ComponentHooks) -> &Vec<ComponentHook>,
+    ) {
+        let hooks = types
+            .iter()
+            .filter_map(|ty| self.hooks.get(ty))
+            .flat_map(|hooks| select(hooks).iter().cloned())
+            .collect::<Vec<_>>();
+        for hook in hooks {
+            hook(self, entity);
+        }
+    }
+
+    /// Runs the `on_remove` hooks of every component of `entity`
+    fn run_remove_hooks(&mut self, entity: Entity) {
+        if let Ok(loc) = self.entities.get(entity) {
+            let types = self.component_ids(entity, loc);
+            self.run_hooks(entity, &types, |hooks| &hooks.on_remove);
+        }
+    }
+
+    /// The entities that had a `C` component removed since the last call to `clear_trackers`
+    ///
+    /// To read removals across calls to `clear_trackers`, use a `RemovedComponentsReader`.
     pub fn removed<C: Component>(&self) -> &[Entity] {
         self.removed_components
-            .get(&TypeId::of::<C>())
-            .map_or(&[], |entities| entities.as_slice())
+            .get(&ComponentId::of::<C>())
+            .map_or(&[], |removed| removed.current())
     }
 
     /// Add `components` to `entity`
@@ -363,14 +940,45 @@ impl World {
         entity: Entity,
         components: impl DynamicBundle,
     ) -> Result<(), NoSuchEntity> {
-        use hashbrown::hash_map::Entry;
+        self.flush();
+        if self.hooks.is_empty() {
+            return self.insert_inner(entity, components);
+        }
+
+        let loc = self.entities.get(entity)?;
+        let (added, inserted): (Vec<_>, Vec<_>) = components
+            .type_info()
+            .iter()
+            .map(|ty| (ty.id(), !self.has_component(entity, loc, ty.id())))
+            .partition(|(_, added)| *added);
+        let added = added.into_iter().map(|(ty, _)| ty).collect::<Vec<_>>();
+        let inserted = added
+            .iter()
+            .copied()
+            .chain(inserted.into_iter().map(|(ty, _)| ty))
+            .collect::<Vec<_>>();
+        self.insert_inner(entity, components)?;
+        self.run_hooks(entity, &added, |hooks| &hooks.on_add);
+        self.run_hooks(entity, &inserted, |hooks| &hooks.on_insert);
+        Ok(())
+    }
 
-        let loc = self.entities.get_mut(entity)?;
+    fn insert_inner(
+        &mut self,
+        entity: Entity,
+        components: impl DynamicBundle,
+    ) -> Result<(), NoSuchEntity> {
+        let change_tick = *self.change_tick.get_mut();
+        let loc = self.entities.get(entity)?;
         unsafe {
             // Assemble Vec<TypeInfo> for the final entity
             let arch = &mut self.archetypes[loc.archetype as usize];
             let mut info = arch.types().to_vec();
             for ty in components.type_info() {
+                if self.sparse_sets.contains_key(&ty.id()) {
+                    // Sparse set components don't change the archetype, and are replaced when put
+                    continue;
+                }
                 if let Some(ptr) = arch.get_dynamic(ty.id(), ty.layout().size(), loc.index) {
                     ty.drop(ptr.as_ptr());
                 } else {
@@ -380,23 +988,18 @@ impl World {
             info.sort();
 
             // Find the archetype it'll live in
-            let elements = info.iter().map(|x| x.id()).collect::<Vec<_>>();
-            let target = match self.index.entry(elements) {
-                Entry::Occupied(x) => *x.get(),
-                Entry::Vacant(x) => {
-                    let index = self.archetypes.len() as u32;
-                    self.archetypes.push(Archetype::new(info));
-                    x.insert(index);
-                    self.archetype_generation += 1;
-                    index
-                }
-            };
+            let target = self.table_archetype(info);
 
+            let sparse_sets = &mut self.sparse_sets;
             if target == loc.archetype {
                 // Update components in the current archetype
                 let arch = &mut self.archetypes[loc.archetype as usize];
                 components.put(|ptr, ty, size| {
-                    arch.put_dynamic(ptr, ty, size, loc.index, false);
+                    if let Some(set) = sparse_sets.get_mut(&ty) {
+                        set.insert(entity, ptr, change_tick);
+                    } else {
+                        arch.put_dynamic(ptr, ty, size, loc.index, None);
+                    }
                     true
                 });
                 return Ok(());
@@ -408,22 +1011,32 @@ impl World {
                 loc.archetype as usize,
                 target as usize,
             );
-            let target_index = target_arch.allocate(entity.id());
-            loc.archetype = target;
-            let old_index = mem::replace(&mut loc.index, target_index);
-            if let Some(moved) =
-                source_arch.move_to(old_index, |ptr, ty, size, is_added, is_mutated| {
-                    target_arch.put_dynamic(ptr, ty, size, target_index, false);
-                    let type_state = target_arch.get_type_state_mut(ty).unwrap();
-                    type_state.added_entities[target_index as usize] = is_added;
-                    type_state.mutated_entities[target_index as usize] = is_mutated;
-                })
-            {
-                self.entities.get_mut(Entity::from_id(moved)).unwrap().index = old_index;
+            let target_index = target_arch.allocate(entity);
+            let old_index = loc.index;
+            *self.entities.get_mut(entity).unwrap() = Location {
+                archetype: target,
+                index: target_index,
+            };
+            if let Some(moved) = source_arch.move_to(old_index, |ptr, ty, size, ticks| {
+                target_arch.put_dynamic(ptr, ty, size, target_index, None);
+                let type_state = target_arch.get_type_state_mut(ty).unwrap();
+                type_state.component_ticks[target_index as usize] = ticks;
+            }) {
+                self.entities.get_mut(moved).unwrap().index = old_index;
             }
 
             components.put(|ptr, ty, size| {
-                target_arch.put_dynamic(ptr, ty, size, target_index, true);
+                if let Some(set) = sparse_sets.get_mut(&ty) {
+                    set.insert(entity, ptr, change_tick);
+                    return true;
+                }
+                // Overwritten components keep the ticks that were moved over with them
+                let added_tick = if source_arch.has_dynamic(ty) {
+                    None
+                } else {
+                    Some(change_tick)
+                };
+                target_arch.put_dynamic(ptr, ty, size, target_index, added_tick);
                 true
             });
         }
@@ -461,56 +1074,66 @@ impl World {
     /// assert_eq!(*world.get::<bool>(e).unwrap(), true);
     /// ```
     pub fn remove<T: Bundle>(&mut self, entity: Entity) -> Result<T, ComponentError> {
-        use hashbrown::hash_map::Entry;
-
-        let loc = self.entities.get_mut(entity)?;
+        self.flush();
+        let loc = self.entities.get(entity)?;
+        if !self.hooks.is_empty() {
+            let types = T::with_static_ids(|ids| ids.to_vec());
+            // hooks only run if the removal can succeed
+            if types.iter().all(|ty| self.has_component(entity, loc, *ty)) {
+                self.run_hooks(entity, &types, |hooks| &hooks.on_remove);
+            }
+        }
+        let loc = self.entities.get(entity)?;
         unsafe {
+            let old_index = loc.index;
+            let source_arch = &self.archetypes[loc.archetype as usize];
+            let sparse_sets = &self.sparse_sets;
+            let bundle = T::get(|ty, size| match sparse_sets.get(&ty) {
+                Some(set) => set.get(entity).map(|(component, _)| component),
+                None => source_arch.get_dynamic(ty, size, old_index),
+            })?;
+
             let removed = T::with_static_ids(|ids| ids.iter().copied().collect::<HashSet<_>>());
+            for ty in removed.iter() {
+                if let Some(set) = self.sparse_sets.get_mut(ty) {
+                    set.forget(entity);
+                    self.removed_components.entry(*ty).or_default().push(entity);
+                }
+            }
+
             let info = self.archetypes[loc.archetype as usize]
                 .types()
                 .iter()
                 .cloned()
                 .filter(|x| !removed.contains(&x.id()))
                 .collect::<Vec<_>>();
-            let elements = info.iter().map(|x| x.id()).collect::<Vec<_>>();
-            let target = match self.index.entry(elements) {
-                Entry::Occupied(x) => *x.get(),
-                Entry::Vacant(x) => {
-                    self.archetypes.push(Archetype::new(info));
-                    let index = (self.archetypes.len() - 1) as u32;
-                    x.insert(index);
-                    self.archetype_generation += 1;
-                    index
-                }
-            };
-            let old_index = loc.index;
-            let source_arch = &self.archetypes[loc.archetype as usize];
-            let bundle = T::get(|ty, size| source_arch.get_dynamic(ty, size, old_index))?;
+            let target = self.table_archetype(info);
+            if target == loc.archetype {
+                // Only sparse set components were removed
+                return Ok(bundle);
+            }
             let (source_arch, target_arch) = index2(
                 &mut self.archetypes,
                 loc.archetype as usize,
                 target as usize,
             );
-            let target_index = target_arch.allocate(entity.id());
-            loc.archetype = target;
-            loc.index = target_index;
+            let target_index = target_arch.allocate(entity);
+            *self.entities.get_mut(entity).unwrap() = Location {
+                archetype: target,
+                index: target_index,
+            };
             let removed_components = &mut self.removed_components;
-            if let Some(moved) =
-                source_arch.move_to(old_index, |src, ty, size, is_added, is_mutated| {
-                    // Only move the components present in the target archetype, i.e. the non-removed ones.
-                    if let Some(dst) = target_arch.get_dynamic(ty, size, target_index) {
-                        ptr::copy_nonoverlapping(src, dst.as_ptr(), size);
-                        let state = target_arch.get_type_state_mut(ty).unwrap();
-                        state.added_entities[target_index as usize] = is_added;
-                        state.mutated_entities[target_index as usize] = is_mutated;
-                    } else {
-                        let removed_entities =
-                            removed_components.entry(ty).or_insert_with(|| Vec::new());
-                        removed_entities.push(entity);
-                    }
-                })
-            {
-                self.entities.get_mut(Entity::from_id(moved)).unwrap().index = old_index;
+            if let Some(moved) = source_arch.move_to(old_index, |src, ty, size, ticks| {
+                // Only move the components present in the target archetype, i.e. the non-removed ones.
+                if let Some(dst) = target_arch.get_dynamic(ty, size, target_index) {
+                    ptr::copy_nonoverlapping(src, dst.as_ptr(), size);
+                    let state = target_arch.get_type_state_mut(ty).unwrap();
+                    state.component_ticks[target_index as usize] = ticks;
+                } else {
+                    removed_components.entry(ty).or_default().push(entity);
+                }
+            }) {
+                self.entities.get_mut(moved).unwrap().index = old_index;
             }
             Ok(bundle)
         }
@@ -533,14 +1156,11 @@ impl World {
     /// same component of `entity` may be live simultaneous to the returned reference.
     pub unsafe fn get_unchecked<T: Component>(&self, entity: Entity) -> Result<&T, ComponentError> {
         let loc = self.entities.get(entity)?;
-        if loc.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
-        Ok(&*self.archetypes[loc.archetype as usize]
-            .get::<T>()
-            .ok_or_else(MissingComponent::new::<T>)?
-            .as_ptr()
-            .add(loc.index as usize))
+        let (component, _) = self.archetypes[loc.archetype as usize]
+            .column::<T>(loc.index as usize)
+            .and_then(|column| column.get(0))
+            .ok_or_else(MissingComponent::new::<T>)?;
+        Ok(&*component.as_ptr())
     }
 
     /// Uniquely borrow the `T` component of `entity` without safety checks
@@ -556,14 +1176,133 @@ impl World {
         entity: Entity,
     ) -> Result<&mut T, ComponentError> {
         let loc = self.entities.get(entity)?;
-        if loc.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
+        let (component, _) = self.archetypes[loc.archetype as usize]
+            .column::<T>(loc.index as usize)
+            .and_then(|column| column.get(0))
+            .ok_or_else(MissingComponent::new::<T>)?;
+        Ok(&mut *component.as_ptr())
+    }
+
+    /// Set how `T` components are stored
+    ///
+    /// Components stored in sparse sets can be added and removed without moving the entity to
+    /// another archetype, at the cost of slower iteration. This suits components that are added
+    /// and removed often, like markers.
+    ///
+    /// Panics if any entity has a `T` component.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// struct Selected;
+    ///
+    /// let mut world = World::new();
+    /// world.set_storage_type::<Selected>(StorageType::SparseSet);
+    /// let a = world.spawn((123, "abc"));
+    /// let archetype = world.get_entity_location(a).unwrap().archetype;
+    /// world.insert_one(a, Selected).unwrap();
+    /// assert_eq!(world.get_entity_location(a).unwrap().archetype, archetype);
+    /// assert_eq!(world.query::<(&i32, &Selected)>().iter().count(), 1);
+    /// ```
+    pub fn set_storage_type<T: Component>(&mut self, storage_type: StorageType) {
+        let id = ComponentId::of::<T>();
+        assert!(
+            self.archetypes
+                .iter()
+                .all(|archetype| archetype.len() == 0 || !archetype.has_dynamic(id))
+                && self.sparse_sets.get(&id).map_or(true, |set| set.is_empty()),
+            "the storage type of {} can only be set while no entity has one",
+            type_name::<T>()
+        );
+        match storage_type {
+            StorageType::Table => {
+                if self.sparse_sets.remove(&id).is_some() {
+                    for archetype in self.archetypes.iter_mut() {
+                        archetype.remove_sparse_set(id);
+                    }
+                    self.bundle_archetypes.clear();
+                    self.archetype_generation += 1;
+                }
+            }
+            StorageType::SparseSet => {
+                if !self.sparse_sets.contains_key(&id) {
+                    let mut set = Box::new(SparseSet::new(TypeInfo::of::<T>()));
+                    for archetype in self.archetypes.iter_mut() {
+                        archetype.add_sparse_set(id, NonNull::from(&mut *set));
+                    }
+                    self.sparse_sets.insert(id, set);
+                    self.bundle_archetypes.clear();
+                    self.archetype_generation += 1;
+                }
+            }
+        }
+    }
+
+    /// How `T` components are stored. See `set_storage_type`.
+    pub fn storage_type<T: Component>(&self) -> StorageType {
+        if self.sparse_sets.contains_key(&ComponentId::of::<T>()) {
+            StorageType::SparseSet
+        } else {
+            StorageType::Table
         }
-        Ok(&mut *self.archetypes[loc.archetype as usize]
-            .get::<T>()
-            .ok_or_else(MissingComponent::new::<T>)?
-            .as_ptr()
-            .add(loc.index as usize))
+    }
+
+    /// Whether `entity`, located at `loc`, has a component of type `ty`
+    fn has_component(&self, entity: Entity, loc: Location, ty: ComponentId) -> bool {
+        match self.sparse_sets.get(&ty) {
+            Some(set) => set.contains(entity),
+            None => self.archetypes[loc.archetype as usize].has_dynamic(ty),
+        }
+    }
+
+    /// Find or create the archetype of entities spawned with a bundle of the components `ids`,
+    /// whose type info is produced by `info` only if the archetype isn't known yet
+    fn bundle_archetype(
+        &mut self,
+        ids: &[ComponentId],
+        info: impl FnOnce() -> Vec<TypeInfo>,
+    ) -> u32 {
+        if self.sparse_sets.is_empty() {
+            if let Some(&index) = self.index.get(ids) {
+                return index;
+            }
+            let index = self.push_archetype(info());
+            self.index.insert(ids.to_vec(), index);
+            index
+        } else {
+            if let Some(&index) = self.bundle_archetypes.get(ids) {
+                return index;
+            }
+            let index = self.table_archetype(info());
+            self.bundle_archetypes.insert(ids.to_vec(), index);
+            index
+        }
+    }
+
+    /// Find or create the archetype of entities with the components `info`, leaving out the
+    /// components stored in sparse sets
+    fn table_archetype(&mut self, mut info: Vec<TypeInfo>) -> u32 {
+        info.retain(|ty| !self.sparse_sets.contains_key(&ty.id()));
+        info.sort();
+        let elements = info.iter().map(|x| x.id()).collect::<Vec<_>>();
+        if let Some(&index) = self.index.get(&elements) {
+            return index;
+        }
+        let index = self.push_archetype(info);
+        self.index.insert(elements, index);
+        index
+    }
+
+    /// Add an archetype of entities with the components `types`, which any entity may have
+    /// sparse set components in addition to
+    fn push_archetype(&mut self, types: Vec<TypeInfo>) -> u32 {
+        let mut archetype = Archetype::new(types);
+        for (&ty, set) in self.sparse_sets.iter_mut() {
+            archetype.add_sparse_set(ty, NonNull::from(&mut **set));
+        }
+        self.archetypes.push(archetype);
+        self.archetype_generation += 1;
+        self.archetypes.len() as u32 - 1
     }
 
     /// Inspect the archetypes that entities are organized into
@@ -601,13 +1340,50 @@ impl World {
         self.entities.get(entity).ok()
     }
 
-    /// Clears each entity's tracker state. For example, each entity's component "mutated" state will be reset to `false`.
+    /// The tick that changes made directly through the world are recorded at
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Acquire)
+    }
+
+    /// Advances the change tick, returning the tick before it was advanced
+    ///
+    /// Systems call this once per run, then query the world with the returned tick and the tick
+    /// returned on their previous run. See `query_with_ticks`.
+    pub fn increment_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::AcqRel)
+    }
+
+    /// The change tick at the last call to `clear_trackers`. `query` detects changes made after it.
+    pub fn last_change_tick(&self) -> u32 {
+        self.last_change_tick
+    }
+
+    /// Clears each entity's tracker state, so that changes made so far are no longer visible to
+    /// `query`. Queries using their own ticks through `query_with_ticks` are unaffected.
     pub fn clear_trackers(&mut self) {
+        self.last_change_tick = self.increment_change_tick();
+        self.check_change_ticks();
+        for removed in self.removed_components.values_mut() {
+            removed.update();
+        }
+    }
+
+    /// Clamps the ticks of all components so they stay comparable as the change tick wraps around.
+    /// This only does work once every `CHECK_TICK_THRESHOLD` ticks.
+    fn check_change_ticks(&mut self) {
+        let change_tick = self.change_tick();
+        if change_tick.wrapping_sub(self.last_check_tick) < CHECK_TICK_THRESHOLD {
+            return;
+        }
+
         for archetype in self.archetypes.iter_mut() {
-            archetype.clear_trackers();
+            archetype.check_change_ticks(change_tick);
+        }
+        for set in self.sparse_sets.values_mut() {
+            set.check_change_ticks(change_tick);
         }
 
-        self.removed_components.clear();
+        self.last_check_tick = change_tick;
     }
 }
 
@@ -684,15 +1460,17 @@ pub struct Iter<'a> {
     entities: &'a Entities,
     current: Option<&'a Archetype>,
     index: u32,
+    change_tick: u32,
 }
 
 impl<'a> Iter<'a> {
-    fn new(archetypes: &'a [Archetype], entities: &'a Entities) -> Self {
+    fn new(archetypes: &'a [Archetype], entities: &'a Entities, change_tick: u32) -> Self {
         Self {
             archetypes: archetypes.iter(),
             entities,
             current: None,
             index: 0,
+            change_tick,
         }
     }
 }
@@ -717,9 +1495,8 @@ impl<'a> Iterator for Iter<'a> {
                     }
                     let index = self.index;
                     self.index += 1;
-                    let id = current.entity_id(index);
-                    return Some((Entity::from_id(id), unsafe {
-                        EntityRef::new(current, index)
+                    return Some((current.get_entity(index), unsafe {
+                        EntityRef::new(current, index, self.change_tick)
                     }));
                 }
             }
@@ -727,7 +1504,7 @@ impl<'a> Iterator for Iter<'a> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, Some(self.entities.entity_locations.len()))
+        (0, Some(self.entities.len() as usize))
     }
 }
 
@@ -761,9 +1538,11 @@ where
     I::Item: Bundle,
 {
     inner: I,
-    entities: &'a mut Entities,
+    world: &'a mut World,
     archetype_id: u32,
-    archetype: &'a mut Archetype,
+    change_tick: u32,
+    // Spawned entities whose hooks run once the batch is complete, if the bundle has any hooks
+    hooked_entities: Option<Vec<Entity>>,
 }
 
 impl<I> Drop for SpawnBatchIter<'_, I>
@@ -772,7 +1551,15 @@ where
     I::Item: Bundle,
 {
     fn drop(&mut self) {
-        for _ in self {}
+        for _ in &mut *self {}
+        if let Some(entities) = self.hooked_entities.take() {
+            let types = I::Item::with_static_ids(|ids| ids.to_vec());
+            for entity in entities {
+                self.world.run_hooks(entity, &types, |hooks| &hooks.on_add);
+                self.world
+                    .run_hooks(entity, &types, |hooks| &hooks.on_insert);
+            }
+        }
     }
 }
 
@@ -785,20 +1572,27 @@ where
 
     fn next(&mut self) -> Option<Entity> {
         let components = self.inner.next()?;
-        let entity = Entity::new();
+        let entity = self.world.entities.alloc();
+        let change_tick = self.change_tick;
+        let archetype = &mut self.world.archetypes[self.archetype_id as usize];
         unsafe {
-            let index = self.archetype.allocate(entity.id());
+            let index = archetype.allocate(entity);
+            let sparse_sets = &mut self.world.sparse_sets;
             components.put(|ptr, ty, size| {
-                self.archetype.put_dynamic(ptr, ty, size, index, true);
+                if let Some(set) = sparse_sets.get_mut(&ty) {
+                    set.insert(entity, ptr, change_tick);
+                } else {
+                    archetype.put_dynamic(ptr, ty, size, index, Some(change_tick));
+                }
                 true
             });
-            self.entities.insert(
-                entity,
-                Location {
-                    archetype: self.archetype_id,
-                    index,
-                },
-            );
+            self.world.entities.meta[entity.id as usize].location = Location {
+                archetype: self.archetype_id,
+                index,
+            };
+        }
+        if let Some(hooked_entities) = &mut self.hooked_entities {
+            hooked_entities.push(entity);
         }
         Some(entity)
     }
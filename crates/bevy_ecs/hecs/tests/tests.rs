@@ -404,3 +404,787 @@ fn remove_tracking() {
         "world clears result in 'removed component' states"
     );
 }
+
+#[test]
+fn removed_components_reader() {
+    let mut world = World::new();
+    let mut early_reader = RemovedComponentsReader::<i32>::default();
+    let mut late_reader = RemovedComponentsReader::<i32>::default();
+    let a = world.spawn(("abc", 123));
+    let b = world.spawn(("abc", 123));
+    let c = world.spawn(("abc", 123));
+
+    world.remove_one::<i32>(a).unwrap();
+    assert_eq!(early_reader.iter(&world).collect::<Vec<_>>(), &[a]);
+
+    world.despawn(b).unwrap();
+    world.clear_trackers();
+    assert_eq!(
+        early_reader.iter(&world).collect::<Vec<_>>(),
+        &[b],
+        "removals are visible after clearing trackers once"
+    );
+    assert_eq!(
+        late_reader.iter(&world).collect::<Vec<_>>(),
+        &[a, b],
+        "readers track removals independently"
+    );
+
+    world.despawn(c).unwrap();
+    world.clear_trackers();
+    world.clear_trackers();
+    assert_eq!(
+        early_reader.iter(&world).count(),
+        0,
+        "removals are dropped after clearing trackers twice"
+    );
+}
+
+#[test]
+fn despawned_entity_is_not_reused() {
+    let mut world = World::new();
+    let a = world.spawn(("abc", 123));
+    world.despawn(a).unwrap();
+    let b = world.spawn(("def", 456));
+    assert_eq!(a.id(), b.id(), "the freed id is reused");
+    assert_ne!(a, b, "but with a new generation");
+    assert!(!world.contains(a));
+    assert!(world.get::<i32>(a).is_err());
+    assert_eq!(world.despawn(a), Err(NoSuchEntity));
+    assert!(world.insert_one(a, true).is_err());
+    assert_eq!(*world.get::<i32>(b).unwrap(), 456);
+
+    world.clear();
+    let c = world.spawn(("ghi", 789));
+    assert!(
+        !world.contains(b),
+        "clearing the world invalidates entities"
+    );
+    assert!(world.contains(c));
+}
+
+#[test]
+fn reserve_entity() {
+    let mut world = World::new();
+    let a = world.spawn((123,));
+    let b = world.reserve_entity();
+    let c = world.get_entity_reserver().reserve_entity();
+    assert_ne!(b, a);
+    assert_ne!(b, c);
+    assert!(
+        !world.contains(b),
+        "reserved entities don't exist until flushed"
+    );
+
+    world.spawn_as_entity(b, (456,));
+    assert_eq!(*world.get::<i32>(b).unwrap(), 456);
+    assert!(world.contains(c), "spawning flushes reserved entities");
+    assert!(world.get::<i32>(c).is_err());
+    assert_eq!(world.iter().count(), 3);
+
+    world.insert_one(c, 789).unwrap();
+    let mut values = world.query::<&i32>().iter().copied().collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(values, &[123, 456, 789]);
+}
+
+#[test]
+fn spawn_as_entity_replaces_existing() {
+    let mut world = World::new();
+    let a = world.spawn(("abc", 123));
+    world.spawn_as_entity(Entity::from_id(a.id()), (456,));
+    assert_eq!(world.iter().count(), 1);
+    assert!(world.get::<&str>(Entity::from_id(a.id())).is_err());
+    assert_eq!(world.removed::<&'static str>(), &[Entity::from_id(a.id())]);
+
+    let far = Entity::from_id(100);
+    world.spawn_as_entity(far, (789,));
+    assert_eq!(*world.get::<i32>(far).unwrap(), 789);
+    let spawned = world.spawn((0,));
+    assert!(
+        spawned.id() < 100,
+        "ids skipped by spawn_as_entity are reused"
+    );
+}
+
+#[test]
+fn spawn_as_entity_reuses_free_ids() {
+    let mut world = World::new();
+    let entities = (0..8).map(|i| world.spawn((i,))).collect::<Vec<_>>();
+    for &entity in &entities {
+        world.despawn(entity).unwrap();
+    }
+
+    let claimed = [entities[5], entities[0], entities[7]];
+    for (i, &entity) in claimed.iter().enumerate() {
+        world.spawn_as_entity(entity, (100 + i as i32,));
+    }
+    let mut ids = claimed.iter().map(|e| e.id()).collect::<Vec<_>>();
+    for i in 0..6 {
+        ids.push(world.spawn((i,)).id());
+    }
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 9, "free ids are handed out once");
+    assert_eq!(world.iter().count(), 9);
+    assert_eq!(*world.get::<i32>(entities[0]).unwrap(), 101);
+}
+
+#[test]
+fn spawn_as_entity_replaces_older_generation() {
+    #[derive(Debug, Eq, PartialEq)]
+    struct Stunned(u32);
+
+    let mut world = World::new();
+    world.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let a = world.spawn((123, Stunned(1)));
+    world.despawn(a).unwrap();
+    let b = world.spawn(("abc", Stunned(2)));
+    assert_eq!(b.id(), a.id());
+    assert_ne!(b.generation(), a.generation());
+    world.clear_trackers();
+
+    let c = Entity::from_bits(u64::from(b.generation() + 1) << 32 | u64::from(b.id()));
+    world.spawn_as_entity(c, (456,));
+    assert!(!world.contains(b));
+    assert_eq!(*world.get::<i32>(c).unwrap(), 456);
+    assert_eq!(world.removed::<&'static str>(), &[b]);
+    assert_eq!(world.removed::<Stunned>(), &[b]);
+    assert!(world.get::<Stunned>(c).is_err());
+}
+
+#[test]
+fn component_hooks() {
+    use std::sync::{Arc, Mutex};
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut world = World::new();
+    let (add_log, insert_log, remove_log) = (log.clone(), log.clone(), log.clone());
+    world
+        .component_hooks::<i32>()
+        .on_add(move |world, entity| {
+            add_log
+                .lock()
+                .unwrap()
+                .push(("add", *world.get::<i32>(entity).unwrap()))
+        })
+        .on_insert(move |world, entity| {
+            insert_log
+                .lock()
+                .unwrap()
+                .push(("insert", *world.get::<i32>(entity).unwrap()))
+        })
+        .on_remove(move |world, entity| {
+            remove_log
+                .lock()
+                .unwrap()
+                .push(("remove", *world.get::<i32>(entity).unwrap()))
+        });
+    let take_log = || std::mem::take(&mut *log.lock().unwrap());
+
+    let a = world.spawn((1, "abc"));
+    assert_eq!(take_log(), &[("add", 1), ("insert", 1)]);
+    world.insert_one(a, 2).unwrap();
+    assert_eq!(take_log(), &[("insert", 2)]);
+    world.insert_one(a, true).unwrap();
+    assert_eq!(take_log(), &[]);
+    world.remove_one::<i32>(a).unwrap();
+    assert_eq!(take_log(), &[("remove", 2)]);
+    assert!(world.remove_one::<i32>(a).is_err());
+    assert_eq!(take_log(), &[]);
+    world.insert(a, (3,)).unwrap();
+    assert_eq!(take_log(), &[("add", 3), ("insert", 3)]);
+    world.despawn(a).unwrap();
+    assert_eq!(take_log(), &[("remove", 3)]);
+
+    world.spawn_batch((4..6).map(|x| (x,))).for_each(drop);
+    assert_eq!(
+        take_log(),
+        &[("add", 4), ("insert", 4), ("add", 5), ("insert", 5)]
+    );
+    world.clear();
+    let mut removed = take_log();
+    removed.sort();
+    assert_eq!(removed, &[("remove", 4), ("remove", 5)]);
+}
+
+#[test]
+fn spawn_after_storage_type_change() {
+    struct Stunned;
+
+    let mut world = World::new();
+    world.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let a = world.spawn((1, Stunned));
+    let b = world.spawn((2, Stunned));
+    let sparse_archetype = world.get_entity_location(a).unwrap().archetype;
+    assert_eq!(
+        world.get_entity_location(b).unwrap().archetype,
+        sparse_archetype
+    );
+    assert_eq!(
+        world
+            .archetypes()
+            .nth(sparse_archetype as usize)
+            .unwrap()
+            .types()
+            .len(),
+        1
+    );
+
+    // the archetype found for the bundle while Stunned was sparse must not be reused
+    world.clear();
+    world.set_storage_type::<Stunned>(StorageType::Table);
+    let a = world.spawn((1, Stunned));
+    let table_archetype = world.get_entity_location(a).unwrap().archetype;
+    assert_ne!(table_archetype, sparse_archetype);
+    assert_eq!(
+        world
+            .archetypes()
+            .nth(table_archetype as usize)
+            .unwrap()
+            .types()
+            .len(),
+        2
+    );
+    assert!(world.get::<Stunned>(a).is_ok());
+
+    world.clear();
+    world.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let entities = world.spawn_batch(vec![(1, Stunned)]).collect::<Vec<_>>();
+    assert_eq!(
+        world.get_entity_location(entities[0]).unwrap().archetype,
+        sparse_archetype
+    );
+    assert!(world.get::<Stunned>(entities[0]).is_ok());
+}
+
+#[test]
+fn sparse_set_storage() {
+    #[derive(Debug, Eq, PartialEq)]
+    struct Stunned(u32);
+
+    let mut world = World::new();
+    world.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let a = world.spawn((1, "a"));
+    let b = world.spawn((2, "b", Stunned(20)));
+    let c = world.spawn((Stunned(30),));
+    let archetype = world.get_entity_location(a).unwrap().archetype;
+    assert_eq!(world.get_entity_location(b).unwrap().archetype, archetype);
+
+    world.clear_trackers();
+    world.insert_one(a, Stunned(10)).unwrap();
+    assert_eq!(world.get_entity_location(a).unwrap().archetype, archetype);
+    assert_eq!(*world.get::<Stunned>(a).unwrap(), Stunned(10));
+    assert_eq!(*world.get::<Stunned>(c).unwrap(), Stunned(30));
+
+    let mut stunned = world
+        .query::<(Entity, &Stunned)>()
+        .iter()
+        .map(|(e, s)| (e, s.0))
+        .collect::<Vec<_>>();
+    stunned.sort();
+    assert_eq!(stunned, &[(a, 10), (b, 20), (c, 30)]);
+    let added = world
+        .query::<(Entity, Added<Stunned>)>()
+        .iter()
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(added, &[a]);
+
+    world.remove_one::<Stunned>(b).unwrap();
+    assert!(world.get::<Stunned>(b).is_err());
+    assert_eq!(world.removed::<Stunned>(), &[b]);
+    let mut optional = world
+        .query::<(&i32, Option<&Stunned>)>()
+        .iter()
+        .map(|(&i, s)| (i, s.map(|s| s.0)))
+        .collect::<Vec<_>>();
+    optional.sort();
+    assert_eq!(optional, &[(1, Some(10)), (2, None)]);
+    let without = world
+        .query::<Without<Stunned, &i32>>()
+        .iter()
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(without, &[2]);
+    let with = world
+        .query::<With<Stunned, &i32>>()
+        .iter()
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(with, &[1]);
+    assert!(world.query_one::<&Stunned>(b).unwrap().get().is_none());
+
+    for mut stunned in world.query::<Mut<Stunned>>().iter() {
+        if stunned.0 == 30 {
+            stunned.0 += 1;
+        }
+    }
+    let mutated = world
+        .query::<(Entity, Mutated<Stunned>)>()
+        .iter()
+        .map(|(e, s)| (e, s.0))
+        .collect::<Vec<_>>();
+    assert_eq!(mutated, &[(c, 31)]);
+
+    world.despawn(a).unwrap();
+    assert_eq!(world.removed::<Stunned>(), &[b, a]);
+    let d = world.spawn((4, "d"));
+    assert!(
+        world.get::<Stunned>(d).is_err(),
+        "despawned components are dropped"
+    );
+    world.clear();
+    assert_eq!(world.removed::<Stunned>(), &[b, a, c]);
+    assert_eq!(world.query::<&Stunned>().iter().count(), 0);
+}
+
+#[test]
+fn runtime_defined_components() {
+    use std::{
+        alloc::Layout,
+        convert::TryInto,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    unsafe fn count_drop(_: *mut u8) {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let mut world = World::new();
+    let health = world.register_component(unsafe {
+        ComponentDescriptor::new("Health", Layout::new::<u32>()).with_drop(count_drop)
+    });
+    let tag = world.register_component(ComponentDescriptor::new("Tag", Layout::new::<()>()));
+    assert_ne!(health.id(), tag.id());
+    assert_eq!(
+        world.component_descriptor(health.id()).unwrap().name(),
+        "Health"
+    );
+    assert!(world
+        .component_descriptor(ComponentId::of::<i32>())
+        .is_none());
+
+    let mut builder = EntityBuilder::new();
+    unsafe { builder.add_dynamic(health, &10u32.to_ne_bytes()) }.add(1i32);
+    let a = world.spawn(builder.build());
+    let b = world.spawn((2i32,));
+    unsafe {
+        builder
+            .add_dynamic(health, &20u32.to_ne_bytes())
+            .add_dynamic(tag, &[]);
+    }
+    world.insert(b, builder.build()).unwrap();
+
+    let query = DynamicQuery::new()
+        .read(ComponentId::of::<i32>())
+        .write(health.id());
+    for mut item in world.query_dynamic(query).iter() {
+        let value = u32::from_ne_bytes(item.read[0].try_into().unwrap()) as i32;
+        item.write[0].copy_from_slice(&(value as u32 * 100).to_ne_bytes());
+    }
+    let mut healths = world
+        .query_dynamic(DynamicQuery::new().read(health.id()))
+        .iter()
+        .map(|item| {
+            (
+                item.entity,
+                u32::from_ne_bytes(item.read[0].try_into().unwrap()),
+            )
+        })
+        .collect::<Vec<_>>();
+    healths.sort();
+    assert_eq!(healths, &[(a, 100), (b, 200)]);
+    let tagged = world
+        .query_dynamic(DynamicQuery::new().read(tag.id()))
+        .iter()
+        .map(|item| item.entity)
+        .collect::<Vec<_>>();
+    assert_eq!(tagged, &[b]);
+    assert_eq!(*world.get::<i32>(b).unwrap(), 2);
+
+    world.despawn(a).unwrap();
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+    drop(world);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+#[should_panic(expected = "already borrowed")]
+fn dynamic_query_borrow_conflict() {
+    let mut world = World::new();
+    world.spawn((1i32,));
+    let query = DynamicQuery::new()
+        .read(ComponentId::of::<i32>())
+        .write(ComponentId::of::<i32>());
+    world.query_dynamic(query);
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn serialize_world() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Name(String);
+    #[derive(Serialize, Deserialize)]
+    struct Target(Entity);
+    impl MapEntities for Target {
+        fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+            self.0 = entity_map.get(self.0)?;
+            Ok(())
+        }
+    }
+
+    let mut registry = SerdeRegistry::new();
+    registry
+        .register::<Name>("Name")
+        .register_mapped::<Target>("Target");
+
+    let mut world = World::new();
+    let unsaved = world.spawn((Name("unsaved".to_string()),));
+    let a = world.spawn((Name("a".to_string()), true));
+    let b = world.spawn((Name("b".to_string()), Target(a)));
+    let saved = serde_json::to_string(&registry.serialize_world(&world)).unwrap();
+    let subset = serde_json::to_string(&registry.serialize_entities(&world, vec![a, b])).unwrap();
+
+    let mut loaded = World::new();
+    loaded.spawn((Name("existing".to_string()),));
+    let entity_map = registry
+        .deserialize_world(&mut loaded, &mut serde_json::Deserializer::from_str(&saved))
+        .unwrap();
+    assert_eq!(entity_map.len(), 3);
+    let loaded_a = entity_map.get(a).unwrap();
+    let loaded_b = entity_map.get(b).unwrap();
+    assert_eq!(
+        *loaded.get::<Name>(loaded_a).unwrap(),
+        Name("a".to_string())
+    );
+    assert!(
+        loaded.get::<bool>(loaded_a).is_err(),
+        "unregistered components are not saved"
+    );
+    assert_eq!(loaded.get::<Target>(loaded_b).unwrap().0, loaded_a);
+    assert_eq!(loaded.query::<&Name>().iter().count(), 4);
+
+    let mut loaded = World::new();
+    let entity_map = registry
+        .deserialize_world(
+            &mut loaded,
+            &mut serde_json::Deserializer::from_str(&subset),
+        )
+        .unwrap();
+    assert!(entity_map.get(unsaved).is_err());
+    assert_eq!(loaded.query::<&Name>().iter().count(), 2);
+
+    // references to entities that were not saved cannot be mapped
+    let subset = serde_json::to_string(&registry.serialize_entities(&world, vec![b])).unwrap();
+    assert!(registry
+        .deserialize_world(
+            &mut World::new(),
+            &mut serde_json::Deserializer::from_str(&subset)
+        )
+        .is_err());
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn serialize_reused_entity() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Target(Entity);
+    impl MapEntities for Target {
+        fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+            self.0 = entity_map.get(self.0)?;
+            Ok(())
+        }
+    }
+
+    let mut registry = SerdeRegistry::new();
+    registry
+        .register::<i32>("i32")
+        .register_mapped::<Target>("Target");
+
+    let mut world = World::new();
+    let despawned = world.spawn((1,));
+    world.despawn(despawned).unwrap();
+    let a = world.spawn((2,));
+    assert_eq!(a.id(), despawned.id());
+    assert!(a.generation() > 0);
+    let b = world.spawn((Target(a),));
+    let saved = serde_json::to_string(&registry.serialize_world(&world)).unwrap();
+
+    let mut loaded = World::new();
+    let entity_map = registry
+        .deserialize_world(&mut loaded, &mut serde_json::Deserializer::from_str(&saved))
+        .unwrap();
+    let loaded_a = entity_map.get(a).unwrap();
+    assert_eq!(*loaded.get::<i32>(loaded_a).unwrap(), 2);
+    assert_eq!(
+        loaded.get::<Target>(entity_map.get(b).unwrap()).unwrap().0,
+        loaded_a
+    );
+    assert!(entity_map.get(despawned).is_err());
+}
+
+#[test]
+fn snapshot_and_restore() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Name(String);
+    #[derive(Clone, Debug, PartialEq)]
+    struct Stunned(u32);
+
+    // every entity, with all of its components and whether they changed since the trackers were
+    // last cleared
+    type State = Vec<(
+        Entity,
+        Option<(i32, bool)>,
+        Option<Name>,
+        Option<Stunned>,
+        bool,
+    )>;
+    fn state(world: &World) -> State {
+        let mut entities = world.iter().map(|(e, _)| e).collect::<Vec<_>>();
+        entities.sort_by_key(|e| e.id());
+        let mutated = world
+            .query::<(Entity, Mutated<i32>)>()
+            .iter()
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        entities
+            .into_iter()
+            .map(|e| {
+                (
+                    e,
+                    world.get::<i32>(e).ok().map(|i| (*i, mutated.contains(&e))),
+                    world.get::<Name>(e).ok().map(|name| (*name).clone()),
+                    world
+                        .get::<Stunned>(e)
+                        .ok()
+                        .map(|stunned| (*stunned).clone()),
+                    world.get::<bool>(e).is_ok(),
+                )
+            })
+            .collect()
+    }
+
+    let mut registry = SnapshotRegistry::new();
+    registry
+        .register::<i32>()
+        .register::<Name>()
+        .register::<Stunned>();
+    let mut world = World::new();
+    world.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let a = world.spawn((1, Name("a".to_string())));
+    let b = world.spawn((2, Stunned(10), true));
+    let c = world.spawn((3,));
+    world.despawn(c).unwrap();
+    let d = world.spawn((4, Name("d".to_string())));
+    let e = world.spawn((Stunned(30),));
+    world.clear_trackers();
+    *world.get_mut::<i32>(d).unwrap() = 5;
+
+    assert_eq!(
+        world.snapshot(&registry).err(),
+        Some(UnregisteredComponent(ComponentId::of::<bool>())),
+        "components must be registered or ignored"
+    );
+    registry.ignore::<bool>();
+
+    let mut before = state(&world);
+    let snapshot = world.snapshot(&registry).unwrap();
+    let spawned_after_snapshot = world.spawn((6,));
+
+    *world.get_mut::<i32>(a).unwrap() = 100;
+    world.insert_one(a, Stunned(20)).unwrap();
+    world.remove_one::<Stunned>(b).unwrap();
+    world.despawn(d).unwrap();
+    world.despawn(e).unwrap();
+    world.spawn((7, Name("e".to_string())));
+
+    world.restore(&snapshot);
+    // ignored components are not restored
+    before[1].4 = false;
+    assert_eq!(state(&world), before);
+    assert!(!world.contains(c) && !world.contains(spawned_after_snapshot));
+
+    // snapshotting the restored world saves the same state
+    let restored_snapshot = world.snapshot(&registry).unwrap();
+    world.spawn((8,));
+    world.despawn(a).unwrap();
+    world.restore(&restored_snapshot);
+    assert_eq!(state(&world), before);
+    // ids handed out after the snapshot are reused with a newer generation
+    let respawned = world.spawn((6,));
+    assert_eq!(respawned.id(), spawned_after_snapshot.id());
+    assert!(respawned.generation() > spawned_after_snapshot.generation());
+
+    // a snapshot can be restored any number of times
+    world.restore(&snapshot);
+    assert_eq!(state(&world), before);
+}
+
+#[test]
+#[should_panic(expected = "no longer stored in one")]
+fn restore_sparse_set_into_table() {
+    #[derive(Clone)]
+    struct Stunned;
+
+    let mut registry = SnapshotRegistry::new();
+    registry.register::<Stunned>();
+    let mut world = World::new();
+    world.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let a = world.spawn((Stunned,));
+    let snapshot = world.snapshot(&registry).unwrap();
+    world.despawn(a).unwrap();
+    world.set_storage_type::<Stunned>(StorageType::Table);
+    world.restore(&snapshot);
+}
+
+#[test]
+fn restore_keeps_reserved_ids() {
+    let registry = SnapshotRegistry::new();
+    let mut world = World::new();
+    let reserver = world.get_entity_reserver();
+    let a = world.spawn(());
+    let snapshot = world.snapshot(&registry).unwrap();
+
+    let reserved = reserver.reserve_entity();
+    world.restore(&snapshot);
+    let reserved_again = reserver.reserve_entity();
+    assert_ne!(reserved_again.id(), reserved.id());
+
+    // the reservations are flushed, and the world doesn't hand out their ids
+    let spawned = world.spawn((1,));
+    assert!(world.contains(a) && world.contains(reserved) && world.contains(reserved_again));
+    assert!(spawned != reserved && spawned != reserved_again);
+    world.spawn_as_entity(reserved, (2,));
+    world.spawn_as_entity(reserved_again, (3,));
+    let mut values = world.query::<&i32>().iter().copied().collect::<Vec<_>>();
+    values.sort();
+    assert_eq!(values, vec![1, 2, 3]);
+}
+
+#[test]
+fn move_entities_between_worlds() {
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    struct Stunned(u32);
+    struct Target(Entity);
+    impl MapEntities for Target {
+        fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+            self.0 = entity_map.get(self.0)?;
+            Ok(())
+        }
+    }
+
+    let shared = Arc::new(());
+    let mut staging = World::new();
+    staging.set_storage_type::<Stunned>(StorageType::SparseSet);
+    let a = staging.spawn(("a", shared.clone()));
+    let b = staging.spawn(("b", Target(a), Stunned(3)));
+    let kept = staging.spawn((shared.clone(),));
+
+    let mut world = World::new();
+    world.spawn((true,));
+    let mut entity_map =
+        world.move_entities_from(&mut staging, |entity| entity.get::<&str>().is_some());
+    world.map_entities::<Target>(&entity_map).unwrap();
+    assert_eq!(entity_map.len(), 2);
+    let moved_a = entity_map.get(a).unwrap();
+    let moved_b = entity_map.get(b).unwrap();
+    assert_eq!(*world.get::<&str>(moved_a).unwrap(), "a");
+    assert_eq!(world.get::<Target>(moved_b).unwrap().0, moved_a);
+    assert_eq!(*world.get::<Stunned>(moved_b).unwrap(), Stunned(3));
+    assert_eq!(
+        world.query::<Added<&str>>().iter().count(),
+        2,
+        "moved components are added to the new world"
+    );
+
+    assert!(!staging.contains(a) && !staging.contains(b));
+    assert!(staging.get::<Arc<()>>(kept).is_ok());
+    assert_eq!(staging.removed::<Stunned>(), &[b]);
+    assert_eq!(
+        Arc::strong_count(&shared),
+        3,
+        "moved components are not dropped"
+    );
+    drop(world);
+    assert_eq!(Arc::strong_count(&shared), 2);
+
+    // references to entities that were not moved cannot be mapped
+    let mut world = World::new();
+    let c = staging.spawn((Target(kept),));
+    entity_map = world.move_entities_from(&mut staging, |entity| entity.get::<Target>().is_some());
+    assert!(entity_map.get(c).is_ok());
+    assert!(world.map_entities::<Target>(&entity_map).is_err());
+}
+
+#[test]
+fn move_runtime_defined_components() {
+    use std::{alloc::Layout, convert::TryInto};
+
+    let mut staging = World::new();
+    let health =
+        staging.register_component(ComponentDescriptor::new("Health", Layout::new::<u32>()));
+    let mut builder = EntityBuilder::new();
+    unsafe { builder.add_dynamic(health, &10u32.to_ne_bytes()) }.add(1i32);
+    let a = staging.spawn(builder.build());
+
+    // the destination already uses the first runtime id for another type
+    let mut world = World::new();
+    let tag = world.register_component(ComponentDescriptor::new("Tag", Layout::new::<()>()));
+    assert_eq!(tag.id(), health.id());
+    let entity_map = world.move_entities_from(&mut staging, |_| true);
+    let moved_a = entity_map.get(a).unwrap();
+
+    let moved_health = world
+        .component_type_info(ComponentId::ExternalId(1))
+        .unwrap();
+    assert_eq!(
+        world
+            .component_descriptor(moved_health.id())
+            .unwrap()
+            .name(),
+        "Health"
+    );
+    let healths = world
+        .query_dynamic(DynamicQuery::new().read(moved_health.id()))
+        .iter()
+        .map(|item| {
+            (
+                item.entity,
+                u32::from_ne_bytes(item.read[0].try_into().unwrap()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(healths, &[(moved_a, 10)]);
+    assert_eq!(
+        world
+            .query_dynamic(DynamicQuery::new().read(tag.id()))
+            .iter()
+            .count(),
+        0
+    );
+
+    // types that are already registered are reused
+    let b = staging.spawn(builder.build());
+    unsafe { builder.add_dynamic(health, &20u32.to_ne_bytes()) };
+    staging.insert(b, builder.build()).unwrap();
+    let entity_map = world.move_entities_from(&mut staging, |_| true);
+    assert!(world
+        .component_type_info(ComponentId::ExternalId(2))
+        .is_none());
+    assert_eq!(
+        world
+            .query_dynamic(DynamicQuery::new().read(moved_health.id()))
+            .iter()
+            .count(),
+        2
+    );
+    assert!(entity_map.get(b).is_ok());
+}
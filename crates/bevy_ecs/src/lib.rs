@@ -2,20 +2,25 @@ pub use bevy_hecs::{Query as HecsQuery, *};
 mod resource;
 mod schedule;
 mod system;
+mod tasks;
 mod world;
 
 pub use resource::*;
 pub use schedule::*;
 pub use system::{Query, *};
+pub use tasks::*;
 pub use world::*;
 
 pub mod prelude {
     pub use crate::{
         resource::{FromResources, Local, Res, ResMut, Resource, Resources},
         system::{
-            Commands, IntoForEachSystem, IntoQuerySystem, IntoThreadLocalSystem, Query, System,
+            Commands, In, IntoChainSystem, IntoForEachSystem, IntoOrderedSystem, IntoQuerySystem,
+            IntoThreadLocalSystem, Query, System,
         },
+        tasks::{AsyncComputeTaskPool, ComputeTaskPool, IoTaskPool, Task},
         world::WorldBuilderSource,
-        Added, Bundle, Changed, Component, Entity, Mut, Mutated, Ref, RefMut, With, Without, World,
+        Added, Bundle, Changed, Component, Entity, Mut, Mutated, Or, Ref, RefMut,
+        RemovedComponentsReader, With, Without, World,
     };
 }
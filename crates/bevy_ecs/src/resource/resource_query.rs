@@ -3,9 +3,8 @@ use crate::{
     system::{SystemId, TypeAccess},
     Resource, ResourceIndex,
 };
-use bevy_hecs::smaller_tuples_too;
+use bevy_hecs::{smaller_tuples_too, ComponentTicks};
 use core::{
-    any::TypeId,
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
@@ -14,14 +13,42 @@ use std::marker::PhantomData;
 /// Shared borrow of a Resource
 pub struct Res<'a, T: Resource> {
     value: &'a T,
+    ticks: &'a ComponentTicks,
+    last_change_tick: u32,
+    change_tick: u32,
 }
 
 impl<'a, T: Resource> Res<'a, T> {
-    pub unsafe fn new(value: NonNull<T>) -> Self {
+    /// Changes made after `last_change_tick` are detected
+    pub unsafe fn new(
+        value: NonNull<T>,
+        ticks: NonNull<ComponentTicks>,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self {
         Self {
             value: &*value.as_ptr(),
+            ticks: &*ticks.as_ptr(),
+            last_change_tick,
+            change_tick,
         }
     }
+
+    /// Whether the resource was added since the system last ran
+    pub fn added(&self) -> bool {
+        self.ticks.is_added(self.last_change_tick, self.change_tick)
+    }
+
+    /// Whether the resource was mutated since the system last ran
+    pub fn mutated(&self) -> bool {
+        self.ticks
+            .is_mutated(self.last_change_tick, self.change_tick)
+    }
+
+    /// Whether the resource was added or mutated since the system last ran
+    pub fn changed(&self) -> bool {
+        self.added() || self.mutated()
+    }
 }
 
 /// A clone that is unsafe to perform. You probably shouldn't use this.
@@ -31,7 +58,12 @@ pub trait UnsafeClone {
 
 impl<'a, T: Resource> UnsafeClone for Res<'a, T> {
     unsafe fn unsafe_clone(&self) -> Self {
-        Self { value: self.value }
+        Self {
+            value: self.value,
+            ticks: self.ticks,
+            last_change_tick: self.last_change_tick,
+            change_tick: self.change_tick,
+        }
     }
 }
 
@@ -47,18 +79,48 @@ impl<'a, T: Resource> Deref for Res<'a, T> {
 }
 
 /// Unique borrow of a Resource
+///
+/// Mutable access marks the resource as mutated.
 pub struct ResMut<'a, T: Resource> {
     _marker: PhantomData<&'a T>,
     value: *mut T,
+    ticks: *mut ComponentTicks,
+    last_change_tick: u32,
+    change_tick: u32,
 }
 
 impl<'a, T: Resource> ResMut<'a, T> {
-    pub unsafe fn new(value: NonNull<T>) -> Self {
+    /// Changes made after `last_change_tick` are detected, and mutations are recorded at
+    /// `change_tick`
+    pub unsafe fn new(
+        value: NonNull<T>,
+        ticks: NonNull<ComponentTicks>,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self {
         Self {
             value: value.as_ptr(),
+            ticks: ticks.as_ptr(),
+            last_change_tick,
+            change_tick,
             _marker: Default::default(),
         }
     }
+
+    /// Whether the resource was added since the system last ran
+    pub fn added(&self) -> bool {
+        unsafe { &*self.ticks }.is_added(self.last_change_tick, self.change_tick)
+    }
+
+    /// Whether the resource was mutated since the system last ran, including by this system
+    pub fn mutated(&self) -> bool {
+        unsafe { &*self.ticks }.is_mutated(self.last_change_tick, self.change_tick)
+    }
+
+    /// Whether the resource was added or mutated since the system last ran
+    pub fn changed(&self) -> bool {
+        self.added() || self.mutated()
+    }
 }
 
 unsafe impl<T: Resource> Send for ResMut<'_, T> {}
@@ -74,7 +136,10 @@ impl<'a, T: Resource> Deref for ResMut<'a, T> {
 
 impl<'a, T: Resource> DerefMut for ResMut<'a, T> {
     fn deref_mut(&mut self) -> &mut T {
-        unsafe { &mut *self.value }
+        unsafe {
+            (*self.ticks).mutated = self.change_tick;
+            &mut *self.value
+        }
     }
 }
 
@@ -82,6 +147,9 @@ impl<'a, T: Resource> UnsafeClone for ResMut<'a, T> {
     unsafe fn unsafe_clone(&self) -> Self {
         Self {
             value: self.value,
+            ticks: self.ticks,
+            last_change_tick: self.last_change_tick,
+            change_tick: self.change_tick,
             _marker: Default::default(),
         }
     }
@@ -133,7 +201,14 @@ pub trait FetchResource<'a>: Sized {
     fn borrow(resources: &Resources);
     fn release(resources: &Resources);
 
-    unsafe fn get(resources: &'a Resources, system_id: Option<SystemId>) -> Self::Item;
+    /// Changes made after `last_change_tick` are detected, and mutations are recorded at
+    /// `change_tick`
+    unsafe fn get(
+        resources: &'a Resources,
+        system_id: Option<SystemId>,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self::Item;
 }
 
 impl<'a, T: Resource> ResourceQuery for Res<'a, T> {
@@ -146,8 +221,14 @@ pub struct FetchResourceRead<T>(NonNull<T>);
 impl<'a, T: Resource> FetchResource<'a> for FetchResourceRead<T> {
     type Item = Res<'a, T>;
 
-    unsafe fn get(resources: &'a Resources, _system_id: Option<SystemId>) -> Self::Item {
-        Res::new(resources.get_unsafe_ref::<T>(ResourceIndex::Global))
+    unsafe fn get(
+        resources: &'a Resources,
+        _system_id: Option<SystemId>,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self::Item {
+        let (value, ticks) = resources.get_unsafe_ref_with_ticks::<T>(ResourceIndex::Global);
+        Res::new(value, ticks, last_change_tick, change_tick)
     }
 
     fn borrow(resources: &Resources) {
@@ -160,7 +241,7 @@ impl<'a, T: Resource> FetchResource<'a> for FetchResourceRead<T> {
 
     fn access() -> TypeAccess {
         let mut access = TypeAccess::default();
-        access.immutable.insert(TypeId::of::<T>());
+        access.add_read::<T>();
         access
     }
 }
@@ -175,8 +256,14 @@ pub struct FetchResourceWrite<T>(NonNull<T>);
 impl<'a, T: Resource> FetchResource<'a> for FetchResourceWrite<T> {
     type Item = ResMut<'a, T>;
 
-    unsafe fn get(resources: &'a Resources, _system_id: Option<SystemId>) -> Self::Item {
-        ResMut::new(resources.get_unsafe_ref::<T>(ResourceIndex::Global))
+    unsafe fn get(
+        resources: &'a Resources,
+        _system_id: Option<SystemId>,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self::Item {
+        let (value, ticks) = resources.get_unsafe_ref_with_ticks::<T>(ResourceIndex::Global);
+        ResMut::new(value, ticks, last_change_tick, change_tick)
     }
 
     fn borrow(resources: &Resources) {
@@ -189,7 +276,7 @@ impl<'a, T: Resource> FetchResource<'a> for FetchResourceWrite<T> {
 
     fn access() -> TypeAccess {
         let mut access = TypeAccess::default();
-        access.mutable.insert(TypeId::of::<T>());
+        access.add_write::<T>();
         access
     }
 }
@@ -210,7 +297,12 @@ pub struct FetchResourceLocalMut<T>(NonNull<T>);
 impl<'a, T: Resource + FromResources> FetchResource<'a> for FetchResourceLocalMut<T> {
     type Item = Local<'a, T>;
 
-    unsafe fn get(resources: &'a Resources, system_id: Option<SystemId>) -> Self::Item {
+    unsafe fn get(
+        resources: &'a Resources,
+        system_id: Option<SystemId>,
+        _last_change_tick: u32,
+        _change_tick: u32,
+    ) -> Self::Item {
         let id = system_id.expect("Local<T> resources can only be used by systems");
         Local {
             value: resources
@@ -230,7 +322,7 @@ impl<'a, T: Resource + FromResources> FetchResource<'a> for FetchResourceLocalMu
 
     fn access() -> TypeAccess {
         let mut access = TypeAccess::default();
-        access.mutable.insert(TypeId::of::<T>());
+        access.add_write::<T>();
         access
     }
 }
@@ -251,8 +343,13 @@ macro_rules! tuple_impl {
             }
 
             #[allow(unused_variables)]
-            unsafe fn get(resources: &'a Resources, system_id: Option<SystemId>) -> Self::Item {
-                ($($name::get(resources, system_id),)*)
+            unsafe fn get(
+                resources: &'a Resources,
+                system_id: Option<SystemId>,
+                last_change_tick: u32,
+                change_tick: u32,
+            ) -> Self::Item {
+                ($($name::get(resources, system_id, last_change_tick, change_tick),)*)
             }
 
             #[allow(unused_mut)]
@@ -1,8 +1,14 @@
 use super::{FetchResource, ResourceQuery};
 use crate::system::SystemId;
-use bevy_hecs::{Archetype, Ref, RefMut, TypeInfo};
+use bevy_hecs::{
+    Archetype, ComponentTicks, Entity, Ref, RefMut, TypeInfo, CHECK_TICK_THRESHOLD, MAX_CHANGE_AGE,
+};
 use core::any::TypeId;
-use std::{collections::HashMap, ptr::NonNull};
+use std::{
+    collections::HashMap,
+    ptr::NonNull,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 /// A Resource type
 pub trait Resource: Send + Sync + 'static {}
@@ -20,9 +26,24 @@ pub enum ResourceIndex {
 }
 
 /// A collection of resource instances identified by their type.
-#[derive(Default)]
+///
+/// Resources track when they were added and mutated with their own change tick, the same way
+/// components do in a [World](bevy_hecs::World). Systems detect changes made since they last ran
+/// with [Res::changed](crate::Res::changed) and friends.
 pub struct Resources {
     pub(crate) resource_data: HashMap<TypeId, ResourceData>,
+    change_tick: AtomicU32,
+    last_check_tick: u32,
+}
+
+impl Default for Resources {
+    fn default() -> Self {
+        Self {
+            resource_data: Default::default(),
+            change_tick: AtomicU32::new(1),
+            last_check_tick: 0,
+        }
+    }
 }
 
 impl Resources {
@@ -56,6 +77,7 @@ impl Resources {
 
     fn insert_resource<T: Resource>(&mut self, mut resource: T, resource_index: ResourceIndex) {
         let type_id = TypeId::of::<T>();
+        let change_tick = self.change_tick();
         let data = self.resource_data.entry(type_id).or_insert_with(|| {
             let mut types = Vec::new();
             types.push(TypeInfo::of::<T>());
@@ -83,7 +105,7 @@ impl Resources {
         };
 
         if index == archetype.len() {
-            unsafe { archetype.allocate(index) };
+            unsafe { archetype.allocate(Entity::from_id(index)) };
         } else if index > archetype.len() {
             panic!("attempted to access index beyond 'current_capacity + 1'")
         }
@@ -92,11 +114,15 @@ impl Resources {
             let resource_ptr = (&mut resource as *mut T).cast::<u8>();
             archetype.put_dynamic(
                 resource_ptr,
-                type_id,
+                type_id.into(),
                 core::mem::size_of::<T>(),
                 index,
-                added,
+                if added { Some(change_tick) } else { None },
             );
+            if !added {
+                let ticks = archetype.get_ticks::<T>().unwrap().as_ptr();
+                (*ticks.add(index as usize)).mutated = change_tick;
+            }
             std::mem::forget(resource);
         }
     }
@@ -124,19 +150,60 @@ impl Resources {
                     ResourceIndex::Global => data.default_index?,
                     ResourceIndex::System(id) => *data.system_id_to_archetype_index.get(&id.0)?,
                 };
-                RefMut::new(&data.archetype, index).ok()
+                RefMut::new(&data.archetype, index, self.change_tick()).ok()
             })
     }
 
+    /// Fetches the resources in `Q`. They report every change that is still tracked as changed.
     pub fn query<Q: ResourceQuery>(&self) -> <Q::Fetch as FetchResource>::Item {
-        unsafe { Q::Fetch::get(&self, None) }
+        let change_tick = self.change_tick();
+        unsafe {
+            Q::Fetch::get(
+                &self,
+                None,
+                change_tick.wrapping_sub(MAX_CHANGE_AGE),
+                change_tick,
+            )
+        }
     }
 
+    /// Fetches the resources in `Q` for the system `id`, detecting changes made after
+    /// `last_change_tick`. Mutations are recorded at `change_tick`.
     pub fn query_system<Q: ResourceQuery>(
         &self,
         id: SystemId,
+        last_change_tick: u32,
+        change_tick: u32,
     ) -> <Q::Fetch as FetchResource>::Item {
-        unsafe { Q::Fetch::get(&self, Some(id)) }
+        unsafe { Q::Fetch::get(&self, Some(id), last_change_tick, change_tick) }
+    }
+
+    /// The current change tick. Resources added or mutated outside of systems are recorded at it.
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Acquire)
+    }
+
+    /// Advances the change tick, returning the tick before it was advanced
+    ///
+    /// Systems call this once per run, then fetch resources with the returned tick and the tick
+    /// returned on their previous run. See `query_system`.
+    pub fn increment_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::AcqRel)
+    }
+
+    /// Clamps the ticks of all resources so they stay comparable as the change tick wraps around.
+    /// This only does work once every `CHECK_TICK_THRESHOLD` ticks.
+    pub fn check_change_ticks(&mut self) {
+        let change_tick = self.change_tick();
+        if change_tick.wrapping_sub(self.last_check_tick) < CHECK_TICK_THRESHOLD {
+            return;
+        }
+
+        for data in self.resource_data.values_mut() {
+            data.archetype.check_change_ticks(change_tick);
+        }
+
+        self.last_check_tick = change_tick;
     }
 
     #[inline]
@@ -157,6 +224,30 @@ impl Resources {
             .unwrap_or_else(|| panic!("Resource does not exist {}", std::any::type_name::<T>()))
     }
 
+    /// Like `get_unsafe_ref`, but also returns the ticks of the resource
+    #[inline]
+    pub unsafe fn get_unsafe_ref_with_ticks<T: Resource>(
+        &self,
+        resource_index: ResourceIndex,
+    ) -> (NonNull<T>, NonNull<ComponentTicks>) {
+        self.resource_data
+            .get(&TypeId::of::<T>())
+            .and_then(|data| {
+                let index = match resource_index {
+                    ResourceIndex::Global => data.default_index?,
+                    ResourceIndex::System(id) => {
+                        data.system_id_to_archetype_index.get(&id.0).cloned()?
+                    }
+                };
+                let (resource, ticks) = data.archetype.get_with_ticks::<T>()?;
+                Some((
+                    NonNull::new_unchecked(resource.as_ptr().add(index as usize)),
+                    NonNull::new_unchecked(ticks.as_ptr().add(index as usize)),
+                ))
+            })
+            .unwrap_or_else(|| panic!("Resource does not exist {}", std::any::type_name::<T>()))
+    }
+
     pub fn borrow<T: Resource>(&self) {
         if let Some(data) = self.resource_data.get(&TypeId::of::<T>()) {
             data.archetype.borrow::<T>();
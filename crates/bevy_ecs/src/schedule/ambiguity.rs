@@ -0,0 +1,210 @@
+use super::{ordering_dependencies, Schedule};
+use crate::system::{System, ThreadLocalExecution, TypeAccess};
+use bevy_hecs::World;
+use fixedbitset::FixedBitSet;
+use std::{
+    any::TypeId,
+    borrow::Cow,
+    sync::{Arc, Mutex},
+};
+
+/// A pair of systems in the same stage that conflict on a component or resource, but have no
+/// ordering constraint between them
+///
+/// The order these systems run in is decided by the order they were added to the stage, which can
+/// change when unrelated systems or plugins are added. Use `before` / `after` constraints to pick
+/// an order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SystemAmbiguity {
+    pub stage: Cow<'static, str>,
+    pub systems: [Cow<'static, str>; 2],
+    /// Indices of the world archetypes that one system writes and the other reads or writes
+    pub archetypes: Vec<usize>,
+    /// The ids and names of the component types that one system writes and the other reads or
+    /// writes
+    pub components: Vec<(TypeId, &'static str)>,
+    /// The ids and names of the resources that one system writes and the other reads or writes
+    pub resources: Vec<(TypeId, &'static str)>,
+}
+
+impl Schedule {
+    /// Returns the pairs of systems in each stage whose execution order is ambiguous
+    ///
+    /// Component conflicts are found using the archetypes currently in `world`, so this is best
+    /// called once the world is populated. Thread local systems run exclusively and are not
+    /// reported.
+    pub fn ambiguities(&self, world: &World) -> Vec<SystemAmbiguity> {
+        let mut ambiguities = Vec::new();
+        for stage_name in self.stage_order.iter() {
+            if let Some(systems) = self.sorted_stage_systems(stage_name) {
+                stage_ambiguities(stage_name, &systems, world, &mut ambiguities);
+            }
+        }
+        ambiguities
+    }
+}
+
+fn stage_ambiguities(
+    stage_name: &Cow<'static, str>,
+    systems: &[Arc<Mutex<Box<dyn System>>>],
+    world: &World,
+    ambiguities: &mut Vec<SystemAmbiguity>,
+) {
+    // systems are sorted by their ordering constraints, so the systems a system must run after
+    // always come earlier in the stage
+    let dependencies = ordering_dependencies(systems);
+    let mut runs_after = vec![FixedBitSet::with_capacity(systems.len()); systems.len()];
+    for system_index in 0..systems.len() {
+        for &dependency in dependencies[system_index].iter() {
+            let transitive = runs_after[dependency].clone();
+            runs_after[system_index].union_with(&transitive);
+            runs_after[system_index].insert(dependency);
+        }
+    }
+
+    let mut systems = systems
+        .iter()
+        .map(|system| system.lock().unwrap())
+        .collect::<Vec<_>>();
+    for system in systems.iter_mut() {
+        system.update_archetype_access(world);
+    }
+
+    for (index, system) in systems.iter().enumerate() {
+        if system.thread_local_execution() == ThreadLocalExecution::Immediate {
+            continue;
+        }
+        for (earlier_index, earlier_system) in systems[..index].iter().enumerate() {
+            if earlier_system.thread_local_execution() == ThreadLocalExecution::Immediate
+                || runs_after[index].contains(earlier_index)
+            {
+                continue;
+            }
+
+            let access = system.archetype_access();
+            let earlier_access = earlier_system.archetype_access();
+            let archetypes = access
+                .mutable
+                .intersection(&earlier_access.mutable)
+                .chain(access.mutable.intersection(&earlier_access.immutable))
+                .chain(access.immutable.intersection(&earlier_access.mutable))
+                .collect::<FixedBitSet>();
+            let components = if archetypes.count_ones(..) > 0 {
+                conflicting_types(&access.components, &earlier_access.components)
+            } else {
+                Vec::new()
+            };
+
+            let resources =
+                conflicting_types(system.resource_access(), earlier_system.resource_access());
+
+            if archetypes.count_ones(..) > 0 || !resources.is_empty() {
+                ambiguities.push(SystemAmbiguity {
+                    stage: stage_name.clone(),
+                    systems: [earlier_system.name(), system.name()],
+                    archetypes: archetypes.ones().collect(),
+                    components,
+                    resources,
+                });
+            }
+        }
+    }
+}
+
+/// The types that one of the accesses writes and the other reads or writes, sorted by name
+fn conflicting_types(access: &TypeAccess, other: &TypeAccess) -> Vec<(TypeId, &'static str)> {
+    let mut types = access
+        .mutable
+        .intersection(&other.mutable)
+        .chain(access.mutable.intersection(&other.immutable))
+        .chain(access.immutable.intersection(&other.mutable))
+        .map(|&id| {
+            let name = access
+                .type_name(id)
+                .or_else(|| other.type_name(id))
+                .unwrap_or("<unnamed>");
+            (id, name)
+        })
+        .collect::<Vec<_>>();
+    types.sort_by_key(|&(id, name)| (name, id));
+    types.dedup();
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        resource::{Res, ResMut},
+        schedule::Schedule,
+        system::{IntoOrderedSystem, IntoQuerySystem, Query},
+    };
+    use bevy_hecs::{Mut, World};
+    use std::any::TypeId;
+
+    #[test]
+    fn ambiguities() {
+        fn read_u32(_query: Query<&u32>) {}
+        fn write_u32(_query: Query<Mut<u32>>) {}
+        fn write_u32_after(_query: Query<Mut<u32>>) {}
+        fn read_u64(_res: Res<u64>) {}
+        fn write_u64(_res: ResMut<u64>) {}
+        fn write_u64_again(_res: ResMut<u64>) {}
+
+        let mut world = World::new();
+        world.spawn((0u32,));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", read_u32.system().label("read"));
+        schedule.add_system_to_stage("update", write_u32.system().label("write"));
+        schedule.add_system_to_stage("update", write_u32_after.system().after("write"));
+        schedule.add_system_to_stage("update", read_u64.system().before("read"));
+        schedule.add_system_to_stage("update", write_u64.system().label("write_u64"));
+        schedule.add_system_to_stage("update", write_u64_again.system().after("write_u64"));
+
+        let short_name = |name: &str| name.rsplit("::").next().unwrap().to_string();
+        let mut ambiguities = schedule
+            .ambiguities(&world)
+            .into_iter()
+            .map(|ambiguity| {
+                let mut systems = [
+                    short_name(&ambiguity.systems[0]),
+                    short_name(&ambiguity.systems[1]),
+                ];
+                systems.sort();
+                (
+                    systems,
+                    ambiguity.archetypes.len(),
+                    ambiguity.components,
+                    ambiguity.resources,
+                )
+            })
+            .collect::<Vec<_>>();
+        ambiguities.sort();
+
+        let pair = |a: &str, b: &str| [a.to_string(), b.to_string()];
+        let u32_type = (TypeId::of::<u32>(), std::any::type_name::<u32>());
+        let u64_type = (TypeId::of::<u64>(), std::any::type_name::<u64>());
+        // write_u32_after is ordered after write_u32, but not relative to read_u32. read_u64 runs
+        // before read_u32, which doesn't order it relative to the systems writing u64
+        assert_eq!(
+            ambiguities,
+            vec![
+                (pair("read_u32", "write_u32"), 1, vec![u32_type], vec![]),
+                (
+                    pair("read_u32", "write_u32_after"),
+                    1,
+                    vec![u32_type],
+                    vec![]
+                ),
+                (pair("read_u64", "write_u64"), 0, vec![], vec![u64_type]),
+                (
+                    pair("read_u64", "write_u64_again"),
+                    0,
+                    vec![],
+                    vec![u64_type]
+                ),
+            ]
+        );
+    }
+}
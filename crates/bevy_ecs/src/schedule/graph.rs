@@ -0,0 +1,233 @@
+use super::{ordering_dependencies, ParallelExecutor, Schedule};
+use crate::system::{System, ThreadLocalExecution};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+
+impl Schedule {
+    /// Renders the schedule as a [Graphviz](https://graphviz.org) DOT graph
+    ///
+    /// Each stage is drawn as a cluster containing its systems in execution order. Thread local
+    /// systems are drawn as boxes. Edges connect systems to the systems they are ordered after, and
+    /// connect systems to the resources they read and write.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph schedule {\n");
+        let mut resources = BTreeSet::new();
+        for (stage_index, stage_name, systems) in self.stages_in_order() {
+            write_stage(&mut dot, stage_index, stage_name, &systems);
+            for (system_index, dependencies) in ordering_dependencies(&systems).iter().enumerate() {
+                for &dependency in dependencies.iter() {
+                    writeln!(
+                        dot,
+                        "    \"{}\" -> \"{}\";",
+                        node_id(stage_index, dependency),
+                        node_id(stage_index, system_index)
+                    )
+                    .unwrap();
+                }
+            }
+
+            for (system_index, system) in systems.iter().enumerate() {
+                let system = system.lock().unwrap();
+                let access = system.resource_access();
+                let node = node_id(stage_index, system_index);
+                let mut edges = access
+                    .immutable
+                    .iter()
+                    .map(|&id| (access.type_name(id), false))
+                    .chain(
+                        access
+                            .mutable
+                            .iter()
+                            .map(|&id| (access.type_name(id), true)),
+                    )
+                    .map(|(name, write)| (name.unwrap_or("<unknown>"), write))
+                    .collect::<Vec<_>>();
+                edges.sort();
+                for (name, write) in edges {
+                    resources.insert(name);
+                    if write {
+                        writeln!(
+                            dot,
+                            "    \"{}\" -> \"resource {}\" [label=\"write\"];",
+                            node,
+                            escape(name)
+                        )
+                        .unwrap();
+                    } else {
+                        writeln!(
+                            dot,
+                            "    \"resource {}\" -> \"{}\" [label=\"read\", style=dashed];",
+                            escape(name),
+                            node
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+
+        for name in resources {
+            writeln!(
+                dot,
+                "    \"resource {}\" [label=\"{}\", shape=note];",
+                escape(name),
+                escape(name)
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn stages_in_order(
+        &self,
+    ) -> impl Iterator<Item = (usize, &str, Cow<'_, [Arc<Mutex<Box<dyn System>>>]>)> {
+        self.stage_order
+            .iter()
+            .enumerate()
+            .filter_map(move |(stage_index, stage_name)| {
+                let systems = self.sorted_stage_systems(stage_name)?;
+                Some((stage_index, stage_name.as_ref(), systems))
+            })
+    }
+}
+
+impl ParallelExecutor {
+    /// Renders the dependencies computed between the systems of `schedule` the last time it was run
+    /// by this executor as a [Graphviz](https://graphviz.org) DOT graph
+    ///
+    /// An edge from one system to another means the second system waits for the first to finish.
+    /// Stages that have not run yet have no edges.
+    pub fn dependency_graph_dot(&self, schedule: &Schedule) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        for (stage_index, stage_name, systems) in schedule.stages_in_order() {
+            write_stage(&mut dot, stage_index, stage_name, &systems);
+            let executor_stage = match self.stages.get(stage_index) {
+                Some(executor_stage) => executor_stage,
+                None => continue,
+            };
+            for (system_index, dependencies) in
+                executor_stage.system_dependencies.iter().enumerate()
+            {
+                for dependency in dependencies.ones() {
+                    writeln!(
+                        dot,
+                        "    \"{}\" -> \"{}\";",
+                        node_id(stage_index, dependency),
+                        node_id(stage_index, system_index)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn write_stage(
+    dot: &mut String,
+    stage_index: usize,
+    stage_name: &str,
+    systems: &[Arc<Mutex<Box<dyn System>>>],
+) {
+    writeln!(dot, "    subgraph \"cluster_{}\" {{", stage_index).unwrap();
+    writeln!(dot, "        label=\"{}\";", escape(stage_name)).unwrap();
+    for (system_index, system) in systems.iter().enumerate() {
+        let system = system.lock().unwrap();
+        let shape = match system.thread_local_execution() {
+            ThreadLocalExecution::NextFlush => "ellipse",
+            ThreadLocalExecution::Immediate => "box",
+        };
+        writeln!(
+            dot,
+            "        \"{}\" [label=\"{}\", shape={}];",
+            node_id(stage_index, system_index),
+            escape(&system.name()),
+            shape
+        )
+        .unwrap();
+    }
+    dot.push_str("    }\n");
+}
+
+fn node_id(stage_index: usize, system_index: usize) -> String {
+    format!("system {} {}", stage_index, system_index)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        resource::{Res, ResMut, Resources},
+        schedule::{ParallelExecutor, Schedule},
+        system::{IntoOrderedSystem, IntoQuerySystem, IntoThreadLocalSystem},
+    };
+    use bevy_hecs::World;
+
+    fn produce(_value: ResMut<u32>) {}
+    fn consume(_value: Res<u32>) {}
+    fn exclusive(_world: &mut World, _resources: &mut Resources) {}
+
+    fn schedule() -> Schedule {
+        let mut schedule = Schedule::default();
+        schedule.add_stage("first");
+        schedule.add_stage("second");
+        schedule.add_system_to_stage("first", consume.system().after("produce"));
+        schedule.add_system_to_stage("first", produce.system().label("produce"));
+        schedule.add_system_to_stage("second", exclusive.thread_local_system());
+        schedule
+    }
+
+    #[test]
+    fn schedule_to_dot() {
+        let dot = schedule().to_dot();
+        let name = |name: &str| format!("{}::{}", module_path!(), name);
+        assert_eq!(
+            dot,
+            format!(
+                r#"digraph schedule {{
+    subgraph "cluster_0" {{
+        label="first";
+        "system 0 0" [label="{produce}", shape=ellipse];
+        "system 0 1" [label="{consume}", shape=ellipse];
+    }}
+    "system 0 0" -> "system 0 1";
+    "system 0 0" -> "resource u32" [label="write"];
+    "resource u32" -> "system 0 1" [label="read", style=dashed];
+    subgraph "cluster_1" {{
+        label="second";
+        "system 1 0" [label="{exclusive}", shape=box];
+    }}
+    "resource u32" [label="u32", shape=note];
+}}
+"#,
+                produce = name("produce"),
+                consume = name("consume"),
+                exclusive = name("exclusive"),
+            )
+        );
+    }
+
+    #[test]
+    fn executor_dependency_graph_dot() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(0u32);
+        let mut schedule = schedule();
+        let mut executor = ParallelExecutor::default();
+        schedule.initialize(&mut resources);
+        executor.run(&mut schedule, &mut world, &mut resources);
+
+        let dot = executor.dependency_graph_dot(&schedule);
+        assert!(dot.contains("    \"system 0 0\" -> \"system 0 1\";\n"));
+        assert_eq!(dot.matches("->").count(), 1);
+    }
+}
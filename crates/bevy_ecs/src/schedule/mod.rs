@@ -1,5 +1,10 @@
+mod ambiguity;
+mod graph;
 mod parallel_executor;
+mod run_criteria;
 mod schedule;
 
+pub use ambiguity::*;
 pub use parallel_executor::*;
+pub use run_criteria::*;
 pub use schedule::*;
@@ -1,4 +1,4 @@
-use super::Schedule;
+use super::{ordering_dependencies, Schedule, ShouldRun};
 use crate::{
     resource::Resources,
     system::{ArchetypeAccess, System, ThreadLocalExecution, TypeAccess},
@@ -19,12 +19,20 @@ use std::{
 /// * in a given stage, systems the read archetype X cannot run before systems registered before them that write archetype X
 /// * in a given stage, systems that mutate resource Y cannot run before systems registered before them that read/write resource Y
 /// * in a given stage, systems the read resource Y cannot run before systems registered before them that write resource Y
-
+/// * in a given stage, systems cannot run before the systems they are ordered after using `before` / `after` constraints
+///
+/// In deterministic mode (see [ParallelExecutor::set_deterministic] and [ParallelExecutorOptions::with_deterministic])
+/// the systems of each stage instead run one at a
+/// time on the calling thread, in the order of the stage: systems come after the systems they are ordered after, and
+/// otherwise keep the order they were added in. The order does not depend on the number of threads. Thread local
+/// systems run right after their system function, and the thread local functions of all other systems run in stage
+/// order at the end of the stage.
 #[derive(Debug)]
 pub struct ParallelExecutor {
-    stages: Vec<ExecutorStage>,
+    pub(crate) stages: Vec<ExecutorStage>,
     last_schedule_generation: usize,
     clear_trackers: bool,
+    deterministic: bool,
 }
 
 impl Default for ParallelExecutor {
@@ -33,6 +41,7 @@ impl Default for ParallelExecutor {
             stages: Default::default(),
             last_schedule_generation: usize::MAX, // MAX forces prepare to run the first time
             clear_trackers: true,
+            deterministic: false,
         }
     }
 }
@@ -45,7 +54,21 @@ impl ParallelExecutor {
         }
     }
 
+    /// Runs the systems of each stage one at a time in a stable order, instead of in parallel
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     pub fn run(&mut self, schedule: &mut Schedule, world: &mut World, resources: &mut Resources) {
+        schedule.sort_systems();
+        let deterministic = self.deterministic
+            || resources
+                .get::<ParallelExecutorOptions>()
+                .map_or(false, |options| options.is_deterministic());
         let schedule_generation = schedule.generation();
         let schedule_changed = schedule.generation() != self.last_schedule_generation;
         if schedule_changed {
@@ -56,13 +79,33 @@ impl ParallelExecutor {
         for (stage_name, executor_stage) in schedule.stage_order.iter().zip(self.stages.iter_mut())
         {
             if let Some(stage_systems) = schedule.stages.get_mut(stage_name) {
-                executor_stage.run(world, resources, stage_systems, schedule_changed);
+                let mut run_criteria = schedule.stage_run_criteria.get_mut(stage_name);
+                loop {
+                    let should_run = match run_criteria.as_mut() {
+                        Some(run_criteria) => run_criteria.should_run(world, resources),
+                        None => ShouldRun::Yes,
+                    };
+                    if should_run == ShouldRun::No {
+                        break;
+                    }
+
+                    if deterministic {
+                        Schedule::run_stage(stage_systems, world, resources);
+                    } else {
+                        executor_stage.run(world, resources, stage_systems, schedule_changed);
+                    }
+
+                    if should_run == ShouldRun::Yes {
+                        break;
+                    }
+                }
             }
         }
 
         if self.clear_trackers {
             world.clear_trackers();
         }
+        resources.check_change_ticks();
 
         self.last_schedule_generation = schedule_generation;
     }
@@ -76,6 +119,8 @@ pub struct ParallelExecutorOptions {
     num_threads: Option<usize>,
     /// If some value, we'll set up the thread pool's' workers to the given stack size. See `rayon::ThreadPoolBuilder::stack_size`.
     stack_size: Option<usize>,
+    /// If true, every [ParallelExecutor] run with these options as a resource runs deterministically.
+    deterministic: bool,
     // TODO: Do we also need/want to expose other features (*_handler, etc.)
 }
 
@@ -98,6 +143,16 @@ impl ParallelExecutorOptions {
         self
     }
 
+    /// Sets the deterministic option, using the builder pattern. See [ParallelExecutor::set_deterministic].
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     /// Creates a new ThreadPoolBuilder based on the current options.
     pub(crate) fn create_builder(&self) -> rayon::ThreadPoolBuilder {
         let mut builder = rayon::ThreadPoolBuilder::new();
@@ -117,9 +172,11 @@ impl ParallelExecutorOptions {
 #[derive(Debug, Clone)]
 pub struct ExecutorStage {
     /// each system's set of dependencies
-    system_dependencies: Vec<FixedBitSet>,
+    pub(crate) system_dependencies: Vec<FixedBitSet>,
     /// each system's dependents (the systems that can't run until this system has run)
     system_dependents: Vec<Vec<usize>>,
+    /// the systems each system must run after, as required by its ordering constraints
+    ordering_dependencies: Vec<Vec<usize>>,
     /// stores the indices of thread local systems in this stage, which are used during stage.prepare()
     thread_local_system_indices: Vec<usize>,
     next_thread_local_index: usize,
@@ -130,6 +187,9 @@ pub struct ExecutorStage {
     sender: Sender<usize>,
     receiver: Receiver<usize>,
     last_archetypes_generation: ArchetypesGeneration,
+    /// whether the stage has been set up for the current schedule. stages can be skipped by run criteria,
+    /// so this may still be false after the schedule changes
+    prepared: bool,
 }
 
 impl Default for ExecutorStage {
@@ -138,6 +198,7 @@ impl Default for ExecutorStage {
         Self {
             system_dependents: Default::default(),
             system_dependencies: Default::default(),
+            ordering_dependencies: Default::default(),
             thread_local_system_indices: Default::default(),
             next_thread_local_index: 0,
             finished_systems: Default::default(),
@@ -145,6 +206,7 @@ impl Default for ExecutorStage {
             sender,
             receiver,
             last_archetypes_generation: ArchetypesGeneration(u64::MAX), // MAX forces prepare to run the first time
+            prepared: false,
         }
     }
 }
@@ -239,6 +301,19 @@ impl ExecutorStage {
                             }
                         }
 
+                        // systems are sorted by their ordering constraints, so these always come earlier. ordering
+                        // dependencies from before the last thread local system are already satisfied by it
+                        for &earlier_system_index in self.ordering_dependencies[system_index].iter()
+                        {
+                            if earlier_system_index >= prepare_system_index_range.start
+                                && !self.system_dependencies[system_index]
+                                    .contains(earlier_system_index)
+                            {
+                                self.system_dependents[earlier_system_index].push(system_index);
+                                self.system_dependencies[system_index].insert(earlier_system_index);
+                            }
+                        }
+
                         current_archetype_access.union(archetype_access);
                         current_resource_access.union(resource_access);
 
@@ -313,7 +388,7 @@ impl ExecutorStage {
                 self.running_systems.insert(system_index);
                 scope.spawn_fifo(move |_| {
                     let mut system = system.lock().unwrap();
-                    system.run(world, resources);
+                    system.run((), world, resources);
                     sender.send(system_index).unwrap();
                 });
 
@@ -331,6 +406,9 @@ impl ExecutorStage {
         systems: &[Arc<Mutex<Box<dyn System>>>],
         schedule_changed: bool,
     ) {
+        let schedule_changed = schedule_changed || !self.prepared;
+        self.prepared = true;
+
         // if the schedule has changed, clear executor state / fill it with new defaults
         if schedule_changed {
             self.system_dependencies.clear();
@@ -341,6 +419,8 @@ impl ExecutorStage {
             self.system_dependents.clear();
             self.system_dependents.resize(systems.len(), Vec::new());
 
+            self.ordering_dependencies = ordering_dependencies(systems);
+
             self.finished_systems.grow(systems.len());
             self.running_systems.grow(systems.len());
 
@@ -386,7 +466,7 @@ impl ExecutorStage {
                 // if a thread local system is ready to run, run it exclusively on the main thread
                 let mut system = systems[thread_local_index].lock().unwrap();
                 self.running_systems.insert(thread_local_index);
-                system.run(world, resources);
+                system.run((), world, resources);
                 system.run_thread_local(world, resources);
                 self.finished_systems.insert(thread_local_index);
                 self.sender.send(thread_local_index).unwrap();
@@ -437,14 +517,16 @@ impl ExecutorStage {
 
 #[cfg(test)]
 mod tests {
-    use super::ParallelExecutor;
+    use super::{ParallelExecutor, ParallelExecutorOptions};
     use crate::{
         resource::{Res, ResMut, Resources},
-        schedule::Schedule,
-        system::{IntoQuerySystem, IntoThreadLocalSystem, Query},
+        schedule::{Schedule, ShouldRun},
+        system::{
+            IntoForEachSystem, IntoOrderedSystem, IntoQuerySystem, IntoThreadLocalSystem, Query,
+        },
         Commands,
     };
-    use bevy_hecs::{Entity, World};
+    use bevy_hecs::{Entity, Mut, Mutated, World};
     use fixedbitset::FixedBitSet;
     use std::sync::{Arc, Mutex};
 
@@ -482,6 +564,43 @@ mod tests {
         executor.run(&mut schedule, &mut world, &mut resources);
     }
 
+    #[test]
+    fn query_par_iter_on_thread_pool() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Mutex::new(Vec::<Option<usize>>::new()));
+        world.spawn_batch((0..100).map(|_| (0u32,)));
+        world.spawn_batch((0..100).map(|_| (0u32, 0u64)));
+
+        fn increment(threads: Res<Mutex<Vec<Option<usize>>>>, mut query: Query<Mut<u32>>) {
+            query.par_iter(16).for_each(|mut value| {
+                *value += 1;
+                threads.lock().unwrap().push(rayon::current_thread_index());
+            });
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", increment.system());
+
+        let pool = ParallelExecutorOptions::new()
+            .with_num_threads(Some(4))
+            .create_builder()
+            .build()
+            .unwrap();
+        let mut executor = ParallelExecutor::default();
+        pool.install(|| executor.run(&mut schedule, &mut world, &mut resources));
+
+        let values = world.query::<&u32>().iter().copied().collect::<Vec<_>>();
+        assert_eq!(values, vec![1; 200]);
+        let threads = resources.get::<Mutex<Vec<Option<usize>>>>().unwrap();
+        let threads = threads.lock().unwrap();
+        assert_eq!(threads.len(), 200);
+        assert!(threads
+            .iter()
+            .all(|index| index.map_or(false, |index| index < 4)));
+    }
+
     #[test]
     fn intra_stage_archetype_change_prepare() {
         let mut world = World::new();
@@ -510,6 +629,182 @@ mod tests {
         executor.run(&mut schedule, &mut world, &mut resources);
     }
 
+    #[test]
+    fn ordering_constraints() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Counter::default());
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+
+        fn third(counter: Res<Counter>) {
+            let mut count = counter.count.lock().unwrap();
+            assert_eq!(*count, 2, "should always be the 3rd system to run");
+            *count += 1;
+        }
+
+        fn second(counter: Res<Counter>) {
+            let mut count = counter.count.lock().unwrap();
+            assert_eq!(*count, 1, "should always be the 2nd system to run");
+            *count += 1;
+        }
+
+        fn first(counter: Res<Counter>) {
+            let mut count = counter.count.lock().unwrap();
+            assert_eq!(*count, 0, "should always be the 1st system to run");
+            *count += 1;
+        }
+
+        // these systems only read resources, so without constraints they could run in any order
+        schedule.add_system_to_stage("update", third.system().after("second"));
+        schedule.add_system_to_stage("update", second.system().label("second"));
+        schedule.add_system_to_stage("update", first.system().before("second"));
+
+        let mut executor = ParallelExecutor::default();
+        executor.run(&mut schedule, &mut world, &mut resources);
+
+        assert_eq!(
+            executor.stages[0].system_dependents,
+            vec![vec![1], vec![2], vec![]]
+        );
+        assert_eq!(
+            *resources.get::<Counter>().unwrap().count.lock().unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn deterministic_order() {
+        #[derive(Default)]
+        struct Log(Mutex<Vec<&'static str>>);
+
+        fn a(log: Res<Log>) {
+            log.0.lock().unwrap().push("a");
+        }
+
+        fn b(log: Res<Log>) {
+            log.0.lock().unwrap().push("b");
+        }
+
+        fn c(log: Res<Log>) {
+            log.0.lock().unwrap().push("c");
+        }
+
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Log::default());
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", a.system().after("c"));
+        schedule.add_system_to_stage("update", b.system());
+        schedule.add_system_to_stage("update", c.system().label("c"));
+
+        let mut executor = ParallelExecutor::default();
+        executor.set_deterministic(true);
+        for _ in 0..3 {
+            executor.run(&mut schedule, &mut world, &mut resources);
+        }
+
+        assert_eq!(
+            *resources.get::<Log>().unwrap().0.lock().unwrap(),
+            vec!["b", "c", "a", "b", "c", "a", "b", "c", "a"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cyclic ordering constraints")]
+    fn ordering_cycle() {
+        fn a() {}
+        fn b() {}
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", a.system().label("a").after("b"));
+        schedule.add_system_to_stage("update", b.system().label("b").after("a"));
+
+        // systems are sorted once, before the schedule first runs
+        let mut executor = ParallelExecutor::default();
+        executor.run(&mut schedule, &mut World::new(), &mut Resources::default());
+    }
+
+    #[test]
+    fn run_criteria() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Counter::default());
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("skipped");
+        schedule.add_stage("looped");
+
+        fn increment(counter: Res<Counter>) {
+            *counter.count.lock().unwrap() += 1;
+        }
+
+        schedule.add_system_to_stage("skipped", increment.system());
+        schedule.add_system_to_stage("looped", increment.system());
+        schedule
+            .set_stage_run_criteria("skipped", |_: &mut World, _: &mut Resources| ShouldRun::No);
+        let mut remaining_runs = 3;
+        schedule.set_stage_run_criteria("looped", move |_: &mut World, _: &mut Resources| {
+            remaining_runs -= 1;
+            match remaining_runs {
+                0 => ShouldRun::Yes,
+                _ => ShouldRun::YesAndLoop,
+            }
+        });
+
+        let mut executor = ParallelExecutor::default();
+        executor.run(&mut schedule, &mut world, &mut resources);
+        assert_eq!(
+            *resources.get::<Counter>().unwrap().count.lock().unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn change_detection_across_skipped_runs() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<usize>::new());
+        world.spawn((0u32,));
+        world.spawn((0u32,));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("write");
+        schedule.add_stage("read");
+
+        fn write(mut value: Mut<u32>) {
+            *value += 1;
+        }
+
+        fn read(mut mutated: ResMut<Vec<usize>>, mut query: Query<Mutated<u32>>) {
+            mutated.push(query.iter().iter().count());
+        }
+
+        // the stages alternate, so the read stage never runs in the same schedule run as the write stage
+        schedule.add_system_to_stage("write", write.system());
+        schedule.add_system_to_stage("read", read.system());
+        let mut write_runs = 0;
+        schedule.set_stage_run_criteria("write", move |_: &mut World, _: &mut Resources| {
+            write_runs += 1;
+            (write_runs % 2 == 1).into()
+        });
+        let mut read_runs = 0;
+        schedule.set_stage_run_criteria("read", move |_: &mut World, _: &mut Resources| {
+            read_runs += 1;
+            (read_runs % 2 == 0).into()
+        });
+
+        let mut executor = ParallelExecutor::default();
+        for _ in 0..4 {
+            executor.run(&mut schedule, &mut world, &mut resources);
+        }
+        assert_eq!(*resources.get::<Vec<usize>>().unwrap(), vec![2, 2]);
+    }
+
     #[test]
     fn schedule() {
         let mut world = World::new();
@@ -0,0 +1,37 @@
+use crate::resource::Resources;
+use bevy_hecs::World;
+
+/// Determines whether a stage should run, and whether it should be checked again afterwards
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShouldRun {
+    /// Skip the stage for this schedule run
+    No,
+    /// Run the stage once
+    Yes,
+    /// Run the stage, then check the run criteria again
+    YesAndLoop,
+}
+
+impl From<bool> for ShouldRun {
+    fn from(should_run: bool) -> Self {
+        if should_run {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        }
+    }
+}
+
+/// Decides when a stage of a [Schedule](crate::Schedule) runs. It is checked right before the stage would run.
+pub trait RunCriteria: Send + Sync + 'static {
+    fn should_run(&mut self, world: &mut World, resources: &mut Resources) -> ShouldRun;
+}
+
+impl<F> RunCriteria for F
+where
+    F: FnMut(&mut World, &mut Resources) -> ShouldRun + Send + Sync + 'static,
+{
+    fn should_run(&mut self, world: &mut World, resources: &mut Resources) -> ShouldRun {
+        self(world, resources)
+    }
+}
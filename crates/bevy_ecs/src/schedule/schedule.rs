@@ -1,6 +1,6 @@
 use crate::{
     resource::Resources,
-    schedule::ParallelExecutorOptions,
+    schedule::{ParallelExecutorOptions, RunCriteria, ShouldRun},
     system::{System, SystemId, ThreadLocalExecution},
 };
 use bevy_hecs::World;
@@ -17,7 +17,10 @@ use std::{
 pub struct Schedule {
     pub(crate) stages: HashMap<Cow<'static, str>, Vec<Arc<Mutex<Box<dyn System>>>>>,
     pub(crate) stage_order: Vec<Cow<'static, str>>,
+    pub(crate) stage_run_criteria: HashMap<Cow<'static, str>, Box<dyn RunCriteria>>,
     pub(crate) system_ids: HashSet<SystemId>,
+    // Stages with systems added since they were last sorted by their ordering constraints
+    unsorted_stages: HashSet<Cow<'static, str>>,
     generation: usize,
     last_initialize_generation: usize,
 }
@@ -79,6 +82,22 @@ impl Schedule {
         self.stage_order.insert(target_index, stage);
     }
 
+    /// Makes the given stage run only when `run_criteria` allows it. This replaces any run criteria
+    /// previously set for the stage.
+    pub fn set_stage_run_criteria(
+        &mut self,
+        stage_name: impl Into<Cow<'static, str>>,
+        run_criteria: impl RunCriteria,
+    ) -> &mut Self {
+        let stage_name = stage_name.into();
+        if !self.stages.contains_key(&stage_name) {
+            panic!("Stage does not exist: {}", stage_name);
+        }
+        self.stage_run_criteria
+            .insert(stage_name, Box::new(run_criteria));
+        self
+    }
+
     pub fn add_system_to_stage(
         &mut self,
         stage_name: impl Into<Cow<'static, str>>,
@@ -98,6 +117,7 @@ impl Schedule {
         }
         self.system_ids.insert(system.id());
         systems.push(Arc::new(Mutex::new(system)));
+        self.unsorted_stages.insert(stage_name);
 
         self.generation += 1;
         self
@@ -122,46 +142,70 @@ impl Schedule {
         }
         self.system_ids.insert(system.id());
         systems.insert(0, Arc::new(Mutex::new(system)));
+        self.unsorted_stages.insert(stage_name);
 
         self.generation += 1;
         self
     }
 
     pub fn run(&mut self, world: &mut World, resources: &mut Resources) {
+        self.sort_systems();
         for stage_name in self.stage_order.iter() {
             if let Some(stage_systems) = self.stages.get_mut(stage_name) {
-                for system in stage_systems.iter_mut() {
-                    let mut system = system.lock().unwrap();
-                    #[cfg(feature = "profiler")]
-                    crate::profiler_start(resources, system.name().clone());
-                    system.update_archetype_access(world);
-                    match system.thread_local_execution() {
-                        ThreadLocalExecution::NextFlush => system.run(world, resources),
-                        ThreadLocalExecution::Immediate => {
-                            system.run(world, resources);
-                            // NOTE: when this is made parallel a full sync is required here
-                            system.run_thread_local(world, resources);
-                        }
+                let mut run_criteria = self.stage_run_criteria.get_mut(stage_name);
+                loop {
+                    let should_run = match run_criteria.as_mut() {
+                        Some(run_criteria) => run_criteria.should_run(world, resources),
+                        None => ShouldRun::Yes,
+                    };
+                    if should_run == ShouldRun::No {
+                        break;
                     }
-                    #[cfg(feature = "profiler")]
-                    crate::profiler_stop(resources, system.name().clone());
-                }
 
-                // "flush"
-                // NOTE: when this is made parallel a full sync is required here
-                for system in stage_systems.iter_mut() {
-                    let mut system = system.lock().unwrap();
-                    match system.thread_local_execution() {
-                        ThreadLocalExecution::NextFlush => {
-                            system.run_thread_local(world, resources)
-                        }
-                        ThreadLocalExecution::Immediate => { /* already ran immediate */ }
+                    Self::run_stage(stage_systems, world, resources);
+
+                    if should_run == ShouldRun::Yes {
+                        break;
                     }
                 }
             }
         }
 
         world.clear_trackers();
+        resources.check_change_ticks();
+    }
+
+    pub(crate) fn run_stage(
+        stage_systems: &mut [Arc<Mutex<Box<dyn System>>>],
+        world: &mut World,
+        resources: &mut Resources,
+    ) {
+        for system in stage_systems.iter_mut() {
+            let mut system = system.lock().unwrap();
+            #[cfg(feature = "profiler")]
+            crate::profiler_start(resources, system.name().clone());
+            system.update_archetype_access(world);
+            match system.thread_local_execution() {
+                ThreadLocalExecution::NextFlush => system.run((), world, resources),
+                ThreadLocalExecution::Immediate => {
+                    system.run((), world, resources);
+                    // NOTE: when this is made parallel a full sync is required here
+                    system.run_thread_local(world, resources);
+                }
+            }
+            #[cfg(feature = "profiler")]
+            crate::profiler_stop(resources, system.name().clone());
+        }
+
+        // "flush"
+        // NOTE: when this is made parallel a full sync is required here
+        for system in stage_systems.iter_mut() {
+            let mut system = system.lock().unwrap();
+            match system.thread_local_execution() {
+                ThreadLocalExecution::NextFlush => system.run_thread_local(world, resources),
+                ThreadLocalExecution::Immediate => { /* already ran immediate */ }
+            }
+        }
     }
 
     // TODO: move this code to ParallelExecutor
@@ -170,6 +214,8 @@ impl Schedule {
             return;
         }
 
+        self.sort_systems();
+
         let thread_pool_builder = resources
             .get::<ParallelExecutorOptions>()
             .map(|options| (*options).clone())
@@ -192,4 +238,127 @@ impl Schedule {
     pub fn generation(&self) -> usize {
         self.generation
     }
+
+    /// Sorts the systems of every stage that changed since it was last sorted. Panics if the
+    /// systems of a stage have cyclic ordering constraints.
+    pub(crate) fn sort_systems(&mut self) {
+        for stage_name in self.unsorted_stages.drain() {
+            if let Some(systems) = self.stages.get_mut(&stage_name) {
+                sort_systems(&stage_name, systems);
+            }
+        }
+    }
+
+    /// The systems of a stage in the order they run in, sorting a copy if the stage has not been
+    /// sorted yet
+    pub(crate) fn sorted_stage_systems(
+        &self,
+        stage_name: &Cow<'static, str>,
+    ) -> Option<Cow<'_, [Arc<Mutex<Box<dyn System>>>]>> {
+        let systems = self.stages.get(stage_name)?;
+        if self.unsorted_stages.contains(stage_name) {
+            let mut systems = systems.clone();
+            sort_systems(stage_name, &mut systems);
+            Some(Cow::Owned(systems))
+        } else {
+            Some(Cow::Borrowed(systems))
+        }
+    }
+}
+
+/// Returns the indices of the systems that each system must run after, according to the
+/// [SystemOrdering](crate::SystemOrdering) of every system in the stage
+pub(crate) fn ordering_dependencies(systems: &[Arc<Mutex<Box<dyn System>>>]) -> Vec<Vec<usize>> {
+    let orderings = systems
+        .iter()
+        .map(|system| system.lock().unwrap().ordering().clone())
+        .collect::<Vec<_>>();
+    let mut labeled_systems = HashMap::<&str, Vec<usize>>::new();
+    for (system_index, ordering) in orderings.iter().enumerate() {
+        if let Some(label) = &ordering.label {
+            labeled_systems
+                .entry(label.as_ref())
+                .or_default()
+                .push(system_index);
+        }
+    }
+
+    let mut dependencies = vec![Vec::new(); systems.len()];
+    for (system_index, ordering) in orderings.iter().enumerate() {
+        for label in ordering.after.iter() {
+            for &other in labeled_systems.get(label.as_ref()).into_iter().flatten() {
+                if other != system_index && !dependencies[system_index].contains(&other) {
+                    dependencies[system_index].push(other);
+                }
+            }
+        }
+        for label in ordering.before.iter() {
+            for &other in labeled_systems.get(label.as_ref()).into_iter().flatten() {
+                if other != system_index && !dependencies[other].contains(&system_index) {
+                    dependencies[other].push(system_index);
+                }
+            }
+        }
+    }
+
+    dependencies
+}
+
+/// Reorders the systems in a stage so that every system comes after the systems it must run after.
+/// Systems without ordering constraints between them keep their insertion order.
+fn sort_systems(stage_name: &str, systems: &mut Vec<Arc<Mutex<Box<dyn System>>>>) {
+    let dependencies = ordering_dependencies(systems);
+    let mut sorted = Vec::with_capacity(systems.len());
+    let mut placed = vec![false; systems.len()];
+    while sorted.len() < systems.len() {
+        let next = (0..systems.len()).find(|&system_index| {
+            !placed[system_index]
+                && dependencies[system_index]
+                    .iter()
+                    .all(|&other| placed[other])
+        });
+        match next {
+            Some(system_index) => {
+                placed[system_index] = true;
+                sorted.push(system_index);
+            }
+            None => {
+                // every remaining system waits on another remaining system, so following those
+                // dependencies must eventually revisit a system
+                let mut path = vec![placed.iter().position(|placed| !placed).unwrap()];
+                let cycle_start = loop {
+                    let current = *path.last().unwrap();
+                    let next = *dependencies[current]
+                        .iter()
+                        .find(|&&other| !placed[other])
+                        .unwrap();
+                    if let Some(position) = path.iter().position(|&system| system == next) {
+                        break position;
+                    }
+                    path.push(next);
+                };
+                let cycle = path[cycle_start..]
+                    .iter()
+                    .rev()
+                    .chain(path.last())
+                    .map(|&system_index| systems[system_index].lock().unwrap().name())
+                    .collect::<Vec<_>>();
+                panic!(
+                    "Systems in stage {} have cyclic ordering constraints: {}",
+                    stage_name,
+                    cycle.join(" -> ")
+                );
+            }
+        }
+    }
+
+    let mut unsorted = std::mem::take(systems)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    systems.extend(
+        sorted
+            .into_iter()
+            .map(|system_index| unsorted[system_index].take().unwrap()),
+    );
 }
@@ -1,8 +1,9 @@
 use super::SystemId;
 use crate::resource::{Resource, Resources};
-use bevy_hecs::{Bundle, Component, DynamicBundle, Entity, World};
+use bevy_hecs::{Bundle, Component, DynamicBundle, Entity, EntityReserver, World};
 use std::{
     marker::PhantomData,
+    ops::{Deref, DerefMut},
     sync::{Arc, Mutex},
 };
 
@@ -160,11 +161,17 @@ impl<T: Resource> ResourcesWriter for InsertLocalResource<T> {
 pub struct CommandsInternal {
     pub commands: Vec<Command>,
     pub current_entity: Option<Entity>,
+    pub entity_reserver: Option<EntityReserver>,
 }
 
 impl CommandsInternal {
     pub fn spawn(&mut self, components: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
-        self.spawn_as_entity(Entity::new(), components)
+        let entity = self
+            .entity_reserver
+            .as_ref()
+            .expect("Cannot spawn an entity because the entity reserver is not set. Call Commands::set_entity_reserver first.")
+            .reserve_entity();
+        self.spawn_as_entity(entity, components)
     }
 
     pub fn spawn_as_entity(
@@ -225,8 +232,26 @@ pub struct Commands {
 }
 
 impl Commands {
-    pub fn spawn(&mut self, components: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
-        self.spawn_as_entity(Entity::new(), components)
+    /// Queues the spawning of an entity with the given components. The entity's id is reserved right away, so it can
+    /// be used in other commands before the entity exists.
+    ///
+    /// Panics if no [EntityReserver] is set. Commands passed to systems have the reserver of their [World], other
+    /// commands need [Commands::set_entity_reserver].
+    ///
+    /// The returned [SpawnedEntity] dereferences to these commands, so further commands can be chained.
+    pub fn spawn(
+        &mut self,
+        components: impl DynamicBundle + Send + Sync + 'static,
+    ) -> SpawnedEntity<'_> {
+        let entity = {
+            let mut commands = self.commands.lock().unwrap();
+            commands.spawn(components);
+            commands.current_entity.unwrap()
+        };
+        SpawnedEntity {
+            entity,
+            commands: self,
+        }
     }
 
     pub fn spawn_as_entity(
@@ -353,19 +378,53 @@ impl Commands {
             phantom: PhantomData,
         })
     }
+
+    /// Sets the [EntityReserver] used to allocate ids for entities spawned by these commands.
+    /// This must be the reserver of the [World] the commands will be applied to.
+    pub fn set_entity_reserver(&self, entity_reserver: EntityReserver) {
+        self.commands.lock().unwrap().entity_reserver = Some(entity_reserver);
+    }
+}
+
+/// An entity spawned by [Commands::spawn]. Dereferences to the [Commands] it was spawned with.
+pub struct SpawnedEntity<'a> {
+    entity: Entity,
+    commands: &'a mut Commands,
+}
+
+impl<'a> SpawnedEntity<'a> {
+    /// The reserved id of the spawned entity
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+}
+
+impl<'a> Deref for SpawnedEntity<'a> {
+    type Target = Commands;
+
+    fn deref(&self) -> &Commands {
+        self.commands
+    }
+}
+
+impl<'a> DerefMut for SpawnedEntity<'a> {
+    fn deref_mut(&mut self) -> &mut Commands {
+        self.commands
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Commands;
     use crate::resource::Resources;
-    use bevy_hecs::World;
+    use bevy_hecs::{Entity, World};
 
     #[test]
     fn command_buffer() {
         let mut world = World::default();
         let mut resources = Resources::default();
         let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
         command_buffer.spawn((1u32, 2u64));
         command_buffer.insert_resource(3.14f32);
         command_buffer.apply(&mut world, &mut resources);
@@ -377,4 +436,65 @@ mod tests {
         assert_eq!(results, vec![(1u32, 2u64)]);
         assert_eq!(*resources.get::<f32>().unwrap(), 3.14f32);
     }
+
+    #[test]
+    fn spawn_returns_reserved_entity() {
+        struct Parent(Entity);
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+        let parent = command_buffer.spawn((1u32,)).id();
+        let child = command_buffer.spawn((2u32,)).id();
+        command_buffer.insert_one(child, Parent(parent));
+        assert!(
+            !world.contains(parent),
+            "entities are spawned when commands are applied"
+        );
+
+        command_buffer.apply(&mut world, &mut resources);
+        assert_eq!(*world.get::<u32>(parent).unwrap(), 1);
+        assert_eq!(world.get::<Parent>(child).unwrap().0, parent);
+    }
+
+    #[test]
+    #[should_panic(expected = "the entity reserver is not set")]
+    fn spawn_requires_entity_reserver() {
+        let mut command_buffer = Commands::default();
+        command_buffer.spawn((1u32,));
+    }
+
+    #[test]
+    fn commands_run_component_hooks() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let added = Arc::new(AtomicUsize::new(0));
+        let removed = Arc::new(AtomicUsize::new(0));
+        let (on_add, on_remove) = (added.clone(), removed.clone());
+        world
+            .component_hooks::<u32>()
+            .on_add(move |_, _| {
+                on_add.fetch_add(1, Ordering::Relaxed);
+            })
+            .on_remove(move |_, _| {
+                on_remove.fetch_add(1, Ordering::Relaxed);
+            });
+
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+        let a = command_buffer.spawn((1u32,)).id();
+        let b = command_buffer.spawn((2u64,)).id();
+        command_buffer.insert_one(b, 3u32);
+        command_buffer.remove_one::<u32>(a);
+        command_buffer.despawn(b);
+        command_buffer.apply(&mut world, &mut resources);
+        assert_eq!(added.load(Ordering::Relaxed), 2);
+        assert_eq!(removed.load(Ordering::Relaxed), 2);
+    }
 }
@@ -2,14 +2,26 @@ pub use super::Query;
 use super::TypeAccess;
 use crate::{
     resource::{FetchResource, ResourceQuery, Resources, UnsafeClone},
-    system::{ArchetypeAccess, Commands, System, SystemId, ThreadLocalExecution},
+    system::{ArchetypeAccess, Commands, System, SystemId, SystemOrdering, ThreadLocalExecution},
 };
-use bevy_hecs::{Fetch, Query as HecsQuery, World};
-use std::borrow::Cow;
+use bevy_hecs::{check_tick, Fetch, Query as HecsQuery, QueryFilter, World};
+use std::{borrow::Cow, marker::PhantomData};
+
+/// The ticks a system runs with. Changes to components and resources made after the `last_` ticks
+/// are detected.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct SystemTicks {
+    pub last_change_tick: u32,
+    pub change_tick: u32,
+    pub last_resource_change_tick: u32,
+    pub resource_change_tick: u32,
+}
 
-pub(crate) struct SystemFn<State, F, ThreadLocalF, Init, SetArchetypeAccess>
+pub(crate) struct SystemFn<State, F, ThreadLocalF, Init, SetArchetypeAccess, In = (), Out = ()>
 where
-    F: FnMut(&World, &Resources, &ArchetypeAccess, &mut State) + Send + Sync,
+    F: FnMut(In, &World, &Resources, &ArchetypeAccess, SystemTicks, &mut State) -> Out
+        + Send
+        + Sync,
     ThreadLocalF: FnMut(&mut World, &mut Resources, &mut State) + Send + Sync,
     Init: FnMut(&mut Resources) + Send + Sync,
     SetArchetypeAccess: FnMut(&World, &mut ArchetypeAccess, &mut State) + Send + Sync,
@@ -25,12 +37,18 @@ where
     pub id: SystemId,
     pub archetype_access: ArchetypeAccess,
     pub set_archetype_access: SetArchetypeAccess,
+    pub ordering: SystemOrdering,
+    pub last_change_tick: u32,
+    pub last_resource_change_tick: u32,
+    pub marker: PhantomData<fn(In) -> Out>,
 }
 
-impl<State, F, ThreadLocalF, Init, SetArchetypeAccess> System
-    for SystemFn<State, F, ThreadLocalF, Init, SetArchetypeAccess>
+impl<State, F, ThreadLocalF, Init, SetArchetypeAccess, In, Out> System<In, Out>
+    for SystemFn<State, F, ThreadLocalF, Init, SetArchetypeAccess, In, Out>
 where
-    F: FnMut(&World, &Resources, &ArchetypeAccess, &mut State) + Send + Sync,
+    F: FnMut(In, &World, &Resources, &ArchetypeAccess, SystemTicks, &mut State) -> Out
+        + Send
+        + Sync,
     ThreadLocalF: FnMut(&mut World, &mut Resources, &mut State) + Send + Sync,
     Init: FnMut(&mut Resources) + Send + Sync,
     SetArchetypeAccess: FnMut(&World, &mut ArchetypeAccess, &mut State) + Send + Sync,
@@ -57,8 +75,29 @@ where
     }
 
     #[inline]
-    fn run(&mut self, world: &World, resources: &Resources) {
-        (self.func)(world, resources, &self.archetype_access, &mut self.state);
+    fn run(&mut self, input: In, world: &World, resources: &Resources) -> Out {
+        let change_tick = world.increment_change_tick();
+        let resource_change_tick = resources.increment_change_tick();
+        // keep ticks of systems that have not run in a long time comparable
+        check_tick(&mut self.last_change_tick, change_tick);
+        check_tick(&mut self.last_resource_change_tick, resource_change_tick);
+        let ticks = SystemTicks {
+            last_change_tick: self.last_change_tick,
+            change_tick,
+            last_resource_change_tick: self.last_resource_change_tick,
+            resource_change_tick,
+        };
+        let output = (self.func)(
+            input,
+            world,
+            resources,
+            &self.archetype_access,
+            ticks,
+            &mut self.state,
+        );
+        self.last_change_tick = change_tick;
+        self.last_resource_change_tick = resource_change_tick;
+        output
     }
 
     fn run_thread_local(&mut self, world: &mut World, resources: &mut Resources) {
@@ -72,6 +111,14 @@ where
     fn id(&self) -> SystemId {
         self.id
     }
+
+    fn ordering(&self) -> &SystemOrdering {
+        &self.ordering
+    }
+
+    fn ordering_mut(&mut self) -> &mut SystemOrdering {
+        &mut self.ordering
+    }
 }
 
 /// Converts `Self` into a For-Each system
@@ -103,12 +150,13 @@ macro_rules! impl_into_foreach_system {
                     thread_local_execution: ThreadLocalExecution::NextFlush,
                     name: core::any::type_name::<Self>().into(),
                     id,
-                    func: move |world, resources, _archetype_access, state| {
+                    func: move |_input, world, resources, _archetype_access, ticks, state| {
+                        state.set_entity_reserver(world.get_entity_reserver());
                         <<($($resource,)*) as ResourceQuery>::Fetch as FetchResource>::borrow(&resources);
                         {
-                            let ($($resource,)*) = resources.query_system::<($($resource,)*)>(id);
-                            for ($($component,)*) in world.query::<($($component,)*)>().iter() {
-                                fn_call!(self, ($($commands, state)*), ($($resource),*), ($($component),*))
+                            let ($($resource,)*) = resources.query_system::<($($resource,)*)>(id, ticks.last_resource_change_tick, ticks.resource_change_tick);
+                            for ($($component,)*) in world.query_with_ticks::<($($component,)*)>(ticks.last_change_tick, ticks.change_tick).iter() {
+                                fn_call!(self, (), ($($commands, state)*), ($($resource),*), ($($component),*))
                             }
                         }
                         <<($($resource,)*) as ResourceQuery>::Fetch as FetchResource>::release(&resources);
@@ -123,8 +171,12 @@ macro_rules! impl_into_foreach_system {
                     archetype_access: ArchetypeAccess::default(),
                     set_archetype_access: |world, archetype_access, _state| {
                         archetype_access.clear();
-                        archetype_access.set_access_for_query::<($($component,)*)>(world);
+                        archetype_access.set_access_for_query::<($($component,)*), ()>(world);
                     },
+                    ordering: SystemOrdering::default(),
+                    last_change_tick: 0,
+                    last_resource_change_tick: 0,
+                    marker: PhantomData,
                 })
             }
         }
@@ -136,30 +188,48 @@ struct QuerySystemState {
     commands: Commands,
 }
 
+/// The input of a system, passed to it by the system it is chained to with
+/// [IntoChainSystem::chain]
+///
+/// Systems that take an input must take it as their first parameter.
+pub struct In<T>(pub T);
+
 /// Converts `Self` into a Query System
-pub trait IntoQuerySystem<Commands, R, Q> {
-    fn system(self) -> Box<dyn System>;
+///
+/// Functions may return a value other than `()`, and take an [In] as their first parameter. Such
+/// systems must be chained to other systems with [IntoChainSystem::chain] before being added to a
+/// schedule.
+pub trait IntoQuerySystem<Commands, R, Q, Input = (), Out = ()> {
+    type In;
+    fn system(self) -> Box<dyn System<Self::In, Out>>;
 }
 
 macro_rules! impl_into_query_system {
-    (($($commands: ident)*), ($($resource: ident),*), ($($query: ident),*)) => {
-        impl<Func, $($resource,)* $($query,)*> IntoQuerySystem<($($commands,)*), ($($resource,)*), ($($query,)*)> for Func where
+    (($($input: ident)?), ($($commands: ident)*), ($($resource: ident),*), ($($query: ident : $filter: ident),*)) => {
+        #[allow(unused_parens)]
+        impl<Func, Out, $($input,)? $($resource,)* $($query, $filter,)*> IntoQuerySystem<($($commands,)*), ($($resource,)*), ($(($query, $filter),)*), ($(In<$input>)?), Out> for Func where
             Func:
-                FnMut($($commands,)* $($resource,)* $(Query<$query>,)*) +
+                FnMut($(In<$input>,)? $($commands,)* $($resource,)* $(Query<$query, $filter>,)*) -> Out +
                 FnMut(
+                    $(In<$input>,)?
                     $($commands,)*
                     $(<<$resource as ResourceQuery>::Fetch as FetchResource>::Item,)*
-                    $(Query<$query>,)*) +
+                    $(Query<$query, $filter>,)*) -> Out +
                 Send + Sync +'static,
+            Out: 'static,
+            $($input: 'static,)?
             $($query: HecsQuery,)*
+            $($filter: QueryFilter,)*
             $($resource: ResourceQuery,)*
         {
+            type In = ($($input)?);
+
             #[allow(non_snake_case)]
             #[allow(unused_variables)]
             #[allow(unused_unsafe)]
             #[allow(unused_assignments)]
             #[allow(unused_mut)]
-            fn system(mut self) -> Box<dyn System> {
+            fn system(mut self) -> Box<dyn System<Self::In, Out>> {
                 let id = SystemId::new();
                 $(let $query = ArchetypeAccess::default();)*
                 Box::new(SystemFn {
@@ -172,20 +242,22 @@ macro_rules! impl_into_query_system {
                     thread_local_execution: ThreadLocalExecution::NextFlush,
                     id,
                     name: core::any::type_name::<Self>().into(),
-                    func: move |world, resources, archetype_access, state| {
+                    func: move |input, world, resources, archetype_access, ticks, state| {
+                        state.commands.set_entity_reserver(world.get_entity_reserver());
                         <<($($resource,)*) as ResourceQuery>::Fetch as FetchResource>::borrow(&resources);
-                        {
-                            let ($($resource,)*) = resources.query_system::<($($resource,)*)>(id);
+                        let output = {
+                            let ($($resource,)*) = resources.query_system::<($($resource,)*)>(id, ticks.last_resource_change_tick, ticks.resource_change_tick);
                             let mut i = 0;
                             $(
-                                let $query = Query::<$query>::new(world, &state.archetype_accesses[i]);
+                                let $query = Query::<$query, $filter>::new(world, &state.archetype_accesses[i], ticks.last_change_tick, ticks.change_tick);
                                 i += 1;
                             )*
 
                             let commands = &state.commands;
-                            fn_call!(self, ($($commands, commands)*), ($($resource),*), ($($query),*))
-                        }
+                            fn_call!(self, ($(In::<$input>(input))?), ($($commands, commands)*), ($($resource),*), ($($query),*))
+                        };
                         <<($($resource,)*) as ResourceQuery>::Fetch as FetchResource>::release(&resources);
+                        output
                     },
                     thread_local_func: move |world, resources, state| {
                         state.commands.apply(world, resources);
@@ -202,11 +274,15 @@ macro_rules! impl_into_query_system {
                         $(
                             access = &mut state.archetype_accesses[i];
                             access.clear();
-                            access.set_access_for_query::<$query>(world);
+                            access.set_access_for_query::<$query, $filter>(world);
                             archetype_access.union(access);
                             i += 1;
                          )*
                     },
+                    ordering: SystemOrdering::default(),
+                    last_change_tick: 0,
+                    last_resource_change_tick: 0,
+                    marker: PhantomData,
                 })
             }
         }
@@ -214,20 +290,21 @@ macro_rules! impl_into_query_system {
 }
 
 macro_rules! fn_call {
-    ($self:ident, ($($commands: ident, $commands_var: ident)*), ($($resource: ident),*), ($($a: ident),*)) => {
-        unsafe { $self($($commands_var.clone(),)* $($resource.unsafe_clone(),)* $($a,)*) }
-    };
-    ($self:ident, (), ($($resource: ident),*), ($($a: ident),*)) => {
-        unsafe { $self($($resource.unsafe_clone(),)* $($a,)*) }
+    ($self:ident, ($($input: expr)?), ($($commands: ident, $commands_var: ident)*), ($($resource: ident),*), ($($a: ident),*)) => {
+        unsafe { $self($($input,)? $($commands_var.clone(),)* $($resource.unsafe_clone(),)* $($a,)*) }
     };
 }
 
 macro_rules! impl_into_query_systems {
-    (($($resource: ident,)*), ($($query: ident),*)) => {
+    (($($resource: ident,)*), ($($query: ident : $filter: ident),*)) => {
+        #[rustfmt::skip]
+        impl_into_query_system!((), (), ($($resource),*), ($($query : $filter),*));
         #[rustfmt::skip]
-        impl_into_query_system!((), ($($resource),*), ($($query),*));
+        impl_into_query_system!((), (Commands), ($($resource),*), ($($query : $filter),*));
         #[rustfmt::skip]
-        impl_into_query_system!((Commands), ($($resource),*), ($($query),*));
+        impl_into_query_system!((Input), (), ($($resource),*), ($($query : $filter),*));
+        #[rustfmt::skip]
+        impl_into_query_system!((Input), (Commands), ($($resource),*), ($($query : $filter),*));
     }
 }
 
@@ -262,17 +339,17 @@ macro_rules! impl_into_systems {
         #[rustfmt::skip]
         impl_into_query_systems!(($($resource,)*), ());
         #[rustfmt::skip]
-        impl_into_query_systems!(($($resource,)*), (A));
+        impl_into_query_systems!(($($resource,)*), (A:FA));
         #[rustfmt::skip]
-        impl_into_query_systems!(($($resource,)*), (A,B));
+        impl_into_query_systems!(($($resource,)*), (A:FA,B:FB));
         #[rustfmt::skip]
-        impl_into_query_systems!(($($resource,)*), (A,B,C));
+        impl_into_query_systems!(($($resource,)*), (A:FA,B:FB,C:FC));
         #[rustfmt::skip]
-        impl_into_query_systems!(($($resource,)*), (A,B,C,D));
+        impl_into_query_systems!(($($resource,)*), (A:FA,B:FB,C:FC,D:FD));
         #[rustfmt::skip]
-        impl_into_query_systems!(($($resource,)*), (A,B,C,D,E));
+        impl_into_query_systems!(($($resource,)*), (A:FA,B:FB,C:FC,D:FD,E:FE));
         #[rustfmt::skip]
-        impl_into_query_systems!(($($resource,)*), (A,B,C,D,E,F));
+        impl_into_query_systems!(($($resource,)*), (A:FA,B:FB,C:FC,D:FD,E:FE,F:FF));
     };
 }
 
@@ -314,7 +391,7 @@ where
             thread_local_func: move |world, resources, _| {
                 self.run(world, resources);
             },
-            func: |_, _, _, _| {},
+            func: |_, _, _, _, _, _| {},
             init_func: |_| {},
             set_archetype_access: |_, _, _| {},
             thread_local_execution: ThreadLocalExecution::Immediate,
@@ -322,6 +399,10 @@ where
             id: SystemId::new(),
             resource_access: TypeAccess::default(),
             archetype_access: ArchetypeAccess::default(),
+            ordering: SystemOrdering::default(),
+            last_change_tick: 0,
+            last_resource_change_tick: 0,
+            marker: PhantomData,
         })
     }
 }
@@ -342,12 +423,16 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{IntoQuerySystem, Query};
+    use super::{In, IntoQuerySystem, Query};
     use crate::{
-        resource::{ResMut, Resources},
+        resource::{Local, Res, ResMut, Resources},
         schedule::Schedule,
+        system::{Commands, IntoChainSystem},
+    };
+    use bevy_hecs::{
+        Added, Changed, Entity, Mut, Mutated, Or, RemovedComponentsReader, StorageType, With,
+        Without, World,
     };
-    use bevy_hecs::{Entity, With, World};
 
     struct A;
     struct B;
@@ -415,4 +500,245 @@ mod tests {
 
         assert!(*resources.get::<bool>().unwrap(), "system ran");
     }
+
+    #[test]
+    fn query_system_reads_removed() {
+        fn read_removed(
+            mut removed: ResMut<Vec<Entity>>,
+            mut reader: Local<RemovedComponentsReader<A>>,
+            query: Query<&A>,
+        ) {
+            removed.extend(query.read_removed(&mut reader));
+        }
+
+        fn despawn(mut commands: Commands, mut query: Query<(Entity, &A)>) {
+            for (entity, _a) in &mut query.iter() {
+                commands.despawn(entity);
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<Entity>::new());
+        let entity = world.spawn((A,));
+
+        // removals happen after the reading system has run, so they are only seen on the next update
+        let mut schedule = Schedule::default();
+        schedule.add_stage("read");
+        schedule.add_stage("despawn");
+        schedule.add_system_to_stage("read", read_removed.system());
+        schedule.add_system_to_stage("despawn", despawn.system());
+
+        schedule.initialize(&mut resources);
+        schedule.run(&mut world, &mut resources);
+        assert!(resources.get::<Vec<Entity>>().unwrap().is_empty());
+
+        schedule.run(&mut world, &mut resources);
+        schedule.run(&mut world, &mut resources);
+        assert_eq!(*resources.get::<Vec<Entity>>().unwrap(), vec![entity]);
+    }
+
+    #[test]
+    fn query_system_filters() {
+        fn mutate(mut query: Query<Mut<B>>) {
+            for mut b in &mut query.iter() {
+                *b = B;
+            }
+        }
+
+        fn filtered(
+            mut entities: ResMut<Vec<Entity>>,
+            mut query: Query<Entity, (With<A>, Or<(Mutated<B>, Without<C>)>)>,
+        ) {
+            entities.extend(&mut query.iter());
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<Entity>::new());
+        let a = world.spawn((A,));
+        world.spawn((A, C));
+        let a_b_c = world.spawn((A, B, C));
+        world.spawn((B,));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", mutate.system());
+        schedule.add_system_to_stage("update", filtered.system());
+        schedule.run(&mut world, &mut resources);
+
+        assert_eq!(*resources.get::<Vec<Entity>>().unwrap(), vec![a, a_b_c]);
+    }
+
+    #[test]
+    fn query_system_changed_size_hint() {
+        fn changed(
+            mut counts: ResMut<Vec<(usize, Option<usize>, usize)>>,
+            mut query: Query<&u32, Changed<u32>>,
+        ) {
+            let mut borrow = query.iter();
+            let iter = borrow.iter();
+            let (lower, upper) = iter.size_hint();
+            counts.push((lower, upper, iter.count()));
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<(usize, Option<usize>, usize)>::new());
+        let a = world.spawn((0u32,));
+        world.spawn((1u32,));
+        world.spawn((2u32,));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", changed.system());
+        schedule.run(&mut world, &mut resources);
+        *world.get_mut::<u32>(a).unwrap() = 3;
+        schedule.run(&mut world, &mut resources);
+        schedule.run(&mut world, &mut resources);
+
+        assert_eq!(
+            *resources
+                .get::<Vec<(usize, Option<usize>, usize)>>()
+                .unwrap(),
+            vec![(0, Some(3), 3), (0, Some(3), 1), (0, Some(3), 0)]
+        );
+    }
+
+    #[test]
+    fn query_system_sparse_set_components() {
+        struct Stunned;
+
+        fn stunned(
+            mut entities: ResMut<Vec<Entity>>,
+            mut query: Query<Entity, (With<u32>, Added<Stunned>)>,
+        ) {
+            entities.extend(&mut query.iter());
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<Entity>::new());
+        world.set_storage_type::<Stunned>(StorageType::SparseSet);
+        world.spawn((0u32,));
+        let b = world.spawn((1u32,));
+        world.spawn((Stunned,));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", stunned.system());
+        schedule.run(&mut world, &mut resources);
+        assert!(resources.get::<Vec<Entity>>().unwrap().is_empty());
+
+        world.insert_one(b, Stunned).unwrap();
+        schedule.run(&mut world, &mut resources);
+        schedule.run(&mut world, &mut resources);
+        assert_eq!(*resources.get::<Vec<Entity>>().unwrap(), vec![b]);
+    }
+
+    #[test]
+    fn query_system_par_iter() {
+        fn increment(mut query: Query<Mut<u32>>) {
+            query.par_iter(7).for_each(|mut value| *value += 1);
+        }
+
+        fn sum(mut total: ResMut<u32>, mut query: Query<&u32>) {
+            *total = query.iter().iter().sum();
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(0u32);
+        world.spawn_batch((0..50).map(|_| (0u32,)));
+        world.spawn_batch((0..50).map(|_| (0u32, A)));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", increment.system());
+        schedule.add_system_to_stage("update", sum.system());
+        schedule.run(&mut world, &mut resources);
+
+        assert_eq!(*resources.get::<u32>().unwrap(), 100);
+    }
+
+    #[test]
+    fn query_system_chain() {
+        fn validate(limit: Res<u32>, mut query: Query<(Entity, &u32)>) -> Result<(), String> {
+            for (entity, value) in &mut query.iter() {
+                if *value > *limit {
+                    return Err(format!("{} is over the limit for {:?}", value, entity));
+                }
+            }
+            Ok(())
+        }
+
+        fn handle_error(In(result): In<Result<(), String>>, mut errors: ResMut<Vec<String>>) {
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(10u32);
+        resources.insert(Vec::<String>::new());
+        let entity = world.spawn((5u32,));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", validate.system().chain(handle_error.system()));
+
+        schedule.run(&mut world, &mut resources);
+        assert!(resources.get::<Vec<String>>().unwrap().is_empty());
+
+        *world.get_mut::<u32>(entity).unwrap() = 20;
+        schedule.run(&mut world, &mut resources);
+        assert_eq!(
+            *resources.get::<Vec<String>>().unwrap(),
+            vec![format!("20 is over the limit for {:?}", entity)]
+        );
+    }
+
+    #[test]
+    fn query_system_resource_changes() {
+        struct Settings(u32);
+
+        fn read_only(settings: ResMut<Settings>) {
+            // reading through ResMut doesn't mark the resource mutated
+            let _value = settings.0;
+        }
+
+        fn detect(settings: Res<Settings>, mut log: ResMut<Vec<(bool, bool)>>) {
+            log.push((settings.added(), settings.changed()));
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Settings(0));
+        resources.insert(Vec::<(bool, bool)>::new());
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", read_only.system());
+        schedule.add_system_to_stage("update", detect.system());
+
+        schedule.run(&mut world, &mut resources);
+        schedule.run(&mut world, &mut resources);
+        resources.get_mut::<Settings>().unwrap().0 = 1;
+        schedule.run(&mut world, &mut resources);
+        schedule.run(&mut world, &mut resources);
+        resources.insert(Settings(2));
+        schedule.run(&mut world, &mut resources);
+
+        assert_eq!(
+            *resources.get::<Vec<(bool, bool)>>().unwrap(),
+            vec![
+                (true, true),
+                (false, false),
+                (false, true),
+                (false, false),
+                (false, true)
+            ]
+        );
+    }
 }
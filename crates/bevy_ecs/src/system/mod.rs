@@ -4,6 +4,7 @@ mod into_system;
 mod profiler;
 mod query;
 mod system;
+mod system_chaining;
 
 pub use commands::*;
 pub use into_system::*;
@@ -11,3 +12,4 @@ pub use into_system::*;
 pub use profiler::*;
 pub use query::*;
 pub use system::*;
+pub use system_chaining::*;
@@ -1,15 +1,21 @@
 use crate::ArchetypeAccess;
 use bevy_hecs::{
-    Archetype, Component, ComponentError, Entity, Fetch, Query as HecsQuery, QueryOne, Ref, RefMut,
-    World,
+    Archetype, Component, ComponentError, Entity, EntityFilter, Fetch, Query as HecsQuery,
+    QueryFilter, QueryOne, Ref, RefMut, RemovedComponentsReader, World,
 };
 use std::marker::PhantomData;
 
 /// Provides scoped access to a World according to a given [HecsQuery]
-pub struct Query<'a, Q: HecsQuery> {
+///
+/// Iteration only visits entities that match the [QueryFilter] `F`, such as `With<T>`, `Without<T>`, `Added<T>`,
+/// `Mutated<T>`, `Changed<T>`, tuples of filters and [Or](bevy_hecs::Or). Filters do not fetch the components they
+/// check, and archetypes rejected by the filter are not part of the system's [ArchetypeAccess].
+pub struct Query<'a, Q: HecsQuery, F: QueryFilter = ()> {
     pub(crate) world: &'a World,
     pub(crate) archetype_access: &'a ArchetypeAccess,
-    _marker: PhantomData<Q>,
+    last_change_tick: u32,
+    change_tick: u32,
+    _marker: PhantomData<(Q, F)>,
 }
 
 /// An error that occurs when using a [Query]
@@ -21,19 +27,40 @@ pub enum QueryError {
     NoSuchEntity,
 }
 
-impl<'a, Q: HecsQuery> Query<'a, Q> {
+impl<'a, Q: HecsQuery, F: QueryFilter> Query<'a, Q, F> {
+    /// Changes made after `last_change_tick` are visible to change detection queries like
+    /// [Mutated](bevy_hecs::Mutated). Mutations made through this query are recorded at `change_tick`.
     #[inline]
-    pub fn new(world: &'a World, archetype_access: &'a ArchetypeAccess) -> Self {
+    pub fn new(
+        world: &'a World,
+        archetype_access: &'a ArchetypeAccess,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self {
         Self {
             world,
             archetype_access,
+            last_change_tick,
+            change_tick,
             _marker: PhantomData::default(),
         }
     }
 
     #[inline]
-    pub fn iter(&mut self) -> QueryBorrow<'_, Q> {
-        QueryBorrow::new(&self.world.archetypes, self.archetype_access)
+    pub fn iter(&mut self) -> QueryBorrow<'_, Q, F> {
+        QueryBorrow::new(
+            &self.world.archetypes,
+            self.archetype_access,
+            self.last_change_tick,
+            self.change_tick,
+        )
+    }
+
+    /// Like `iter`, but splits the query into batches of at most `batch_size` entities that can be processed in
+    /// parallel on the thread pool used by [ParallelExecutor](crate::ParallelExecutor)
+    #[inline]
+    pub fn par_iter(&mut self, batch_size: u32) -> ParIter<'_, Q, F> {
+        self.iter().par_iter(batch_size)
     }
 
     /// Gets a reference to the entity's component of the given type. This will fail if the entity does not have
@@ -71,7 +98,10 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
                     .mutable
                     .contains(location.archetype as usize)
             {
-                Ok(self.world.query_one(entity).unwrap())
+                Ok(self
+                    .world
+                    .query_one_with_ticks(entity, self.last_change_tick, self.change_tick)
+                    .unwrap())
             } else {
                 Err(QueryError::CannotReadArchetype)
             }
@@ -90,7 +120,7 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
                 .contains(location.archetype as usize)
             {
                 self.world
-                    .get_mut(entity)
+                    .get_mut_with_tick(entity, self.change_tick)
                     .map_err(|err| QueryError::ComponentError(err))
             } else {
                 Err(QueryError::CannotWriteArchetype)
@@ -100,10 +130,22 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
         }
     }
 
+    /// The entities that had a `C` component removed since the end of the last update. Removals made by
+    /// systems that run later in the update are not included. See [Query::read_removed].
     pub fn removed<C: Component>(&self) -> &[Entity] {
         self.world.removed::<C>()
     }
 
+    /// Reads the entities that had a `C` component removed (including despawned entities) since `reader` last read them.
+    /// Storing the reader in a `Local` resource lets a system see every removal exactly once, regardless of which stage
+    /// the removal happened in, as long as the system runs at least once per update.
+    pub fn read_removed<'r, C: Component>(
+        &'r self,
+        reader: &'r mut RemovedComponentsReader<C>,
+    ) -> impl Iterator<Item = Entity> + 'r {
+        reader.iter(self.world)
+    }
+
     /// Sets the entity's component to the given value. This will fail if the entity does not already have
     /// the given component type or if the given component type does not match this query.
     pub fn set<T: Component>(&self, entity: Entity, component: T) -> Result<(), QueryError> {
@@ -113,17 +155,24 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
     }
 }
 
-/// A borrow of a `World` sufficient to execute the query `Q`
+/// A borrow of a `World` sufficient to execute the query `Q` with the filter `F`
 ///
 /// Note that borrows are not released until this object is dropped.
-pub struct QueryBorrow<'w, Q: HecsQuery> {
+pub struct QueryBorrow<'w, Q: HecsQuery, F: QueryFilter = ()> {
     archetypes: &'w [Archetype],
     archetype_access: &'w ArchetypeAccess,
-    _marker: PhantomData<Q>,
+    last_change_tick: u32,
+    change_tick: u32,
+    _marker: PhantomData<(Q, F)>,
 }
 
-impl<'w, Q: HecsQuery> QueryBorrow<'w, Q> {
-    pub(crate) fn new(archetypes: &'w [Archetype], archetype_access: &'w ArchetypeAccess) -> Self {
+impl<'w, Q: HecsQuery, F: QueryFilter> QueryBorrow<'w, Q, F> {
+    pub(crate) fn new(
+        archetypes: &'w [Archetype],
+        archetype_access: &'w ArchetypeAccess,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self {
         for index in archetype_access.immutable.ones() {
             Q::Fetch::borrow(&archetypes[index]);
         }
@@ -134,6 +183,8 @@ impl<'w, Q: HecsQuery> QueryBorrow<'w, Q> {
         Self {
             archetypes,
             archetype_access,
+            last_change_tick,
+            change_tick,
             _marker: PhantomData,
         }
     }
@@ -142,19 +193,32 @@ impl<'w, Q: HecsQuery> QueryBorrow<'w, Q> {
     ///
     /// Must be called only once per query.
     #[inline]
-    pub fn iter<'q>(&'q mut self) -> QueryIter<'q, 'w, Q> {
+    pub fn iter<'q>(&'q mut self) -> QueryIter<'q, 'w, Q, F> {
         QueryIter {
             borrow: self,
             archetype_index: 0,
             iter: None,
         }
     }
+
+    /// Like `iter`, but splits the query into batches of at most `batch_size` entities that can be processed in
+    /// parallel on the thread pool used by [ParallelExecutor](crate::ParallelExecutor)
+    ///
+    /// Must be called only once per query.
+    #[inline]
+    pub fn par_iter(self, batch_size: u32) -> ParIter<'w, Q, F> {
+        assert!(batch_size > 0, "batch_size must be greater than 0");
+        ParIter {
+            borrow: self,
+            batch_size,
+        }
+    }
 }
 
-unsafe impl<'w, Q: HecsQuery> Send for QueryBorrow<'w, Q> {}
-unsafe impl<'w, Q: HecsQuery> Sync for QueryBorrow<'w, Q> {}
+unsafe impl<'w, Q: HecsQuery, F: QueryFilter> Send for QueryBorrow<'w, Q, F> {}
+unsafe impl<'w, Q: HecsQuery, F: QueryFilter> Sync for QueryBorrow<'w, Q, F> {}
 
-impl<'w, Q: HecsQuery> Drop for QueryBorrow<'w, Q> {
+impl<'w, Q: HecsQuery, F: QueryFilter> Drop for QueryBorrow<'w, Q, F> {
     #[inline]
     fn drop(&mut self) {
         for index in self.archetype_access.immutable.ones() {
@@ -167,8 +231,8 @@ impl<'w, Q: HecsQuery> Drop for QueryBorrow<'w, Q> {
     }
 }
 
-impl<'q, 'w, Q: HecsQuery> IntoIterator for &'q mut QueryBorrow<'w, Q> {
-    type IntoIter = QueryIter<'q, 'w, Q>;
+impl<'q, 'w, Q: HecsQuery, F: QueryFilter> IntoIterator for &'q mut QueryBorrow<'w, Q, F> {
+    type IntoIter = QueryIter<'q, 'w, Q, F>;
     type Item = <Q::Fetch as Fetch<'q>>::Item;
 
     #[inline]
@@ -177,17 +241,17 @@ impl<'q, 'w, Q: HecsQuery> IntoIterator for &'q mut QueryBorrow<'w, Q> {
     }
 }
 
-/// Iterator over the set of entities with the components in `Q`
-pub struct QueryIter<'q, 'w, Q: HecsQuery> {
-    borrow: &'q mut QueryBorrow<'w, Q>,
+/// Iterator over the set of entities with the components in `Q` that match the filter `F`
+pub struct QueryIter<'q, 'w, Q: HecsQuery, F: QueryFilter> {
+    borrow: &'q mut QueryBorrow<'w, Q, F>,
     archetype_index: u32,
-    iter: Option<ChunkIter<Q>>,
+    iter: Option<ChunkIter<Q, F>>,
 }
 
-unsafe impl<'q, 'w, Q: HecsQuery> Send for QueryIter<'q, 'w, Q> {}
-unsafe impl<'q, 'w, Q: HecsQuery> Sync for QueryIter<'q, 'w, Q> {}
+unsafe impl<'q, 'w, Q: HecsQuery, F: QueryFilter> Send for QueryIter<'q, 'w, Q, F> {}
+unsafe impl<'q, 'w, Q: HecsQuery, F: QueryFilter> Sync for QueryIter<'q, 'w, Q, F> {}
 
-impl<'q, 'w, Q: HecsQuery> Iterator for QueryIter<'q, 'w, Q> {
+impl<'q, 'w, Q: HecsQuery, F: QueryFilter> Iterator for QueryIter<'q, 'w, Q, F> {
     type Item = <Q::Fetch as Fetch<'q>>::Item;
 
     #[inline]
@@ -197,11 +261,23 @@ impl<'q, 'w, Q: HecsQuery> Iterator for QueryIter<'q, 'w, Q> {
                 None => {
                     let archetype = self.borrow.archetypes.get(self.archetype_index as usize)?;
                     self.archetype_index += 1;
+                    let last_change_tick = self.borrow.last_change_tick;
+                    let change_tick = self.borrow.change_tick;
                     unsafe {
-                        self.iter = Q::Fetch::get(archetype, 0).map(|fetch| ChunkIter {
-                            fetch,
-                            len: archetype.len(),
-                        });
+                        self.iter = F::get_entity_filter(archetype, last_change_tick, change_tick)
+                            .and_then(|filter| {
+                                Some(ChunkIter {
+                                    fetch: Q::Fetch::get(
+                                        archetype,
+                                        0,
+                                        last_change_tick,
+                                        change_tick,
+                                    )?,
+                                    filter,
+                                    position: 0,
+                                    len: archetype.len(),
+                                })
+                            });
                     }
                 }
                 Some(ref mut iter) => match unsafe { iter.next() } {
@@ -217,29 +293,92 @@ impl<'q, 'w, Q: HecsQuery> Iterator for QueryIter<'q, 'w, Q> {
         }
     }
 
+    // filters and some fetches skip individual entities, so only the entities left in the matching
+    // archetypes are known
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let n = self.len();
-        (n, Some(n))
+        let current = self.iter.as_ref().map_or(0, |iter| iter.len as usize);
+        let upper = self.borrow.archetypes[self.archetype_index as usize..]
+            .iter()
+            .filter(|&x| Q::Fetch::access(x).is_some() && F::access(x).is_some())
+            .map(|x| x.len() as usize)
+            .sum::<usize>();
+        (0, Some(current + upper))
     }
 }
 
-impl<'q, 'w, Q: HecsQuery> ExactSizeIterator for QueryIter<'q, 'w, Q> {
-    fn len(&self) -> usize {
-        self.borrow
-            .archetypes
-            .iter()
-            .filter(|&x| Q::Fetch::access(x).is_some())
-            .map(|x| x.len() as usize)
-            .sum()
+/// Parallel iterator over the set of entities with the components in `Q` that match the filter `F`
+///
+/// Returned by [Query::par_iter] and [QueryBorrow::par_iter].
+pub struct ParIter<'w, Q: HecsQuery, F: QueryFilter> {
+    borrow: QueryBorrow<'w, Q, F>,
+    batch_size: u32,
+}
+
+impl<'w, Q: HecsQuery, F: QueryFilter> ParIter<'w, Q, F> {
+    /// Calls `func` on every item of the query, spreading batches across the thread pool. Returns once every item
+    /// has been processed.
+    pub fn for_each<'q, Func>(&'q mut self, func: Func)
+    where
+        Func: Fn(<Q::Fetch as Fetch<'q>>::Item) + Send + Sync,
+    {
+        let func = &func;
+        let batch_size = self.batch_size;
+        let last_change_tick = self.borrow.last_change_tick;
+        let change_tick = self.borrow.change_tick;
+        let mut batches = Vec::new();
+        for archetype in self.borrow.archetypes.iter() {
+            let mut offset = 0;
+            while offset < archetype.len() {
+                // each batch visits a disjoint range of the archetype, so batches can run concurrently
+                let chunk = unsafe {
+                    F::get_entity_filter(archetype, last_change_tick, change_tick).and_then(
+                        |filter| {
+                            Some(ChunkIter::<Q, F> {
+                                fetch: Q::Fetch::get(
+                                    archetype,
+                                    offset as usize,
+                                    last_change_tick,
+                                    change_tick,
+                                )?,
+                                filter,
+                                position: offset as usize,
+                                len: batch_size.min(archetype.len() - offset),
+                            })
+                        },
+                    )
+                };
+                match chunk {
+                    Some(chunk) => batches.push(Batch(chunk)),
+                    None => break,
+                }
+                offset += batch_size;
+            }
+        }
+
+        rayon::scope(|scope| {
+            for mut batch in batches {
+                scope.spawn(move |_| {
+                    while let Some(item) = unsafe { batch.0.next() } {
+                        func(item);
+                    }
+                });
+            }
+        });
     }
 }
 
-struct ChunkIter<Q: HecsQuery> {
+struct Batch<Q: HecsQuery, F: QueryFilter>(ChunkIter<Q, F>);
+
+unsafe impl<Q: HecsQuery, F: QueryFilter> Send for Batch<Q, F> {}
+
+struct ChunkIter<Q: HecsQuery, F: QueryFilter> {
     fetch: Q::Fetch,
+    filter: F::EntityFilter,
+    position: usize,
     len: u32,
 }
 
-impl<Q: HecsQuery> ChunkIter<Q> {
+impl<Q: HecsQuery, F: QueryFilter> ChunkIter<Q, F> {
     #[inline]
     unsafe fn next<'a, 'w>(&mut self) -> Option<<Q::Fetch as Fetch<'a>>::Item> {
         loop {
@@ -248,9 +387,14 @@ impl<Q: HecsQuery> ChunkIter<Q> {
             }
 
             self.len -= 1;
-            if self.fetch.should_skip() {
+            let position = self.position;
+            self.position += 1;
+            if self.fetch.is_missing()
+                || self.fetch.should_skip()
+                || !self.filter.matches_entity(position)
+            {
                 // we still need to progress the iterator
-                let _ = self.fetch.next();
+                self.fetch.skip();
                 continue;
             }
 
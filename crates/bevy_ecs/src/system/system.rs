@@ -1,7 +1,11 @@
 use crate::resource::Resources;
-use bevy_hecs::{Access, Query, World};
+use bevy_hecs::{Access, Fetch, Query, QueryFilter, World};
 use fixedbitset::FixedBitSet;
-use std::{any::TypeId, borrow::Cow, collections::HashSet};
+use std::{
+    any::TypeId,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 
 /// Determines the strategy used to run the `run_thread_local` function in a [System]
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -20,16 +24,59 @@ impl SystemId {
 }
 
 /// An ECS system that can be added to a [Schedule](crate::Schedule)
-pub trait System: Send + Sync {
+///
+/// Systems that take an input or produce an output other than `()` can't be added to a schedule
+/// directly. Instead they are combined with other systems using [IntoChainSystem::chain].
+pub trait System<In = (), Out = ()>: Send + Sync {
     fn name(&self) -> Cow<'static, str>;
     fn id(&self) -> SystemId;
     fn update_archetype_access(&mut self, world: &World);
     fn archetype_access(&self) -> &ArchetypeAccess;
     fn resource_access(&self) -> &TypeAccess;
     fn thread_local_execution(&self) -> ThreadLocalExecution;
-    fn run(&mut self, world: &World, resources: &Resources);
+    fn run(&mut self, input: In, world: &World, resources: &Resources) -> Out;
     fn run_thread_local(&mut self, world: &mut World, resources: &mut Resources);
     fn initialize(&mut self, _resources: &mut Resources) {}
+    fn ordering(&self) -> &SystemOrdering;
+    fn ordering_mut(&mut self) -> &mut SystemOrdering;
+}
+
+/// Constraints that place a [System] relative to other systems in the same stage
+///
+/// Constraints that refer to labels without a matching system in the stage are ignored, which allows
+/// plugins to order themselves relative to systems that may not be present.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct SystemOrdering {
+    pub label: Option<Cow<'static, str>>,
+    pub before: Vec<Cow<'static, str>>,
+    pub after: Vec<Cow<'static, str>>,
+}
+
+/// Adds ordering constraints to a [System]
+pub trait IntoOrderedSystem {
+    /// Names the system so that other systems can be ordered relative to it
+    fn label(self, label: impl Into<Cow<'static, str>>) -> Box<dyn System>;
+    /// Runs the system before all systems in the same stage with the given label
+    fn before(self, label: impl Into<Cow<'static, str>>) -> Box<dyn System>;
+    /// Runs the system after all systems in the same stage with the given label
+    fn after(self, label: impl Into<Cow<'static, str>>) -> Box<dyn System>;
+}
+
+impl IntoOrderedSystem for Box<dyn System> {
+    fn label(mut self, label: impl Into<Cow<'static, str>>) -> Box<dyn System> {
+        self.ordering_mut().label = Some(label.into());
+        self
+    }
+
+    fn before(mut self, label: impl Into<Cow<'static, str>>) -> Box<dyn System> {
+        self.ordering_mut().before.push(label.into());
+        self
+    }
+
+    fn after(mut self, label: impl Into<Cow<'static, str>>) -> Box<dyn System> {
+        self.ordering_mut().after.push(label.into());
+        self
+    }
 }
 
 /// Provides information about the archetypes a [System] reads and writes
@@ -37,6 +84,8 @@ pub trait System: Send + Sync {
 pub struct ArchetypeAccess {
     pub immutable: FixedBitSet,
     pub mutable: FixedBitSet,
+    /// The component types read and written in these archetypes
+    pub components: TypeAccess,
 }
 
 // credit to Ratysz from the Yaks codebase
@@ -50,40 +99,83 @@ impl ArchetypeAccess {
     pub fn union(&mut self, other: &ArchetypeAccess) {
         self.mutable.union_with(&other.mutable);
         self.immutable.union_with(&other.immutable);
+        self.components.union(&other.components);
     }
 
-    pub fn set_access_for_query<Q>(&mut self, world: &World)
+    /// Adds the access `Q` has to each archetype of `world`, skipping archetypes that the filter `F` rejects
+    pub fn set_access_for_query<Q, F>(&mut self, world: &World)
     where
         Q: Query,
+        F: QueryFilter,
     {
         let iterator = world.archetypes();
         let bits = iterator.len();
         self.immutable.grow(bits);
         self.mutable.grow(bits);
-        iterator
-            .enumerate()
-            .filter_map(|(index, archetype)| archetype.access::<Q>().map(|access| (index, access)))
-            .for_each(|(archetype, access)| match access {
-                Access::Read => self.immutable.set(archetype, true),
-                Access::Write => self.mutable.set(archetype, true),
+        let components = &mut self.components;
+        let mut add_component = |id, name, access| components.add(id, name, access);
+        for (index, archetype) in iterator.enumerate() {
+            let access = match archetype.access::<Q>().zip(F::access(archetype)) {
+                Some((query_access, filter_access)) => query_access.max(filter_access),
+                None => continue,
+            };
+            match access {
+                Access::Read => self.immutable.set(index, true),
+                Access::Write => self.mutable.set(index, true),
                 Access::Iterate => (),
-            });
+            }
+            Q::Fetch::component_access(archetype, &mut add_component);
+            F::component_access(archetype, &mut add_component);
+        }
     }
 
     pub fn clear(&mut self) {
         self.immutable.clear();
         self.mutable.clear();
+        self.components.clear();
     }
 }
 
 /// Provides information about the types a [System] reads and writes
-#[derive(Debug, Default, Eq, PartialEq, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct TypeAccess {
     pub immutable: HashSet<TypeId>,
     pub mutable: HashSet<TypeId>,
+    type_names: HashMap<TypeId, &'static str>,
 }
 
 impl TypeAccess {
+    /// Adds read access to `T`, remembering its name
+    pub fn add_read<T: 'static>(&mut self) {
+        self.immutable.insert(TypeId::of::<T>());
+        self.type_names
+            .insert(TypeId::of::<T>(), std::any::type_name::<T>());
+    }
+
+    /// Adds write access to `T`, remembering its name
+    pub fn add_write<T: 'static>(&mut self) {
+        self.mutable.insert(TypeId::of::<T>());
+        self.type_names
+            .insert(TypeId::of::<T>(), std::any::type_name::<T>());
+    }
+
+    /// Adds `access` to the type `id`, remembering its name. [Access::Iterate] does not read the type,
+    /// so it is ignored.
+    pub fn add(&mut self, id: TypeId, name: &'static str, access: Access) {
+        match access {
+            Access::Read => self.immutable.insert(id),
+            Access::Write => self.mutable.insert(id),
+            Access::Iterate => return,
+        };
+        self.type_names.insert(id, name);
+    }
+
+    /// The name of the type `id`, if access to it was added with [TypeAccess::add_read] or
+    /// [TypeAccess::add_write]
+    pub fn type_name(&self, id: TypeId) -> Option<&'static str> {
+        self.type_names.get(&id).copied()
+    }
+
     pub fn is_compatible(&self, other: &TypeAccess) -> bool {
         self.mutable.is_disjoint(&other.mutable)
             && self.mutable.is_disjoint(&other.immutable)
@@ -93,19 +185,29 @@ impl TypeAccess {
     pub fn union(&mut self, other: &TypeAccess) {
         self.mutable.extend(&other.mutable);
         self.immutable.extend(&other.immutable);
+        self.type_names.extend(&other.type_names);
     }
 
     pub fn clear(&mut self) {
         self.immutable.clear();
         self.mutable.clear();
+        self.type_names.clear();
+    }
+}
+
+impl PartialEq for TypeAccess {
+    fn eq(&self, other: &Self) -> bool {
+        self.immutable == other.immutable && self.mutable == other.mutable
     }
 }
 
+impl Eq for TypeAccess {}
+
 #[cfg(test)]
 mod tests {
     use super::{ArchetypeAccess, TypeAccess};
     use crate::resource::{FetchResource, Res, ResMut, ResourceQuery};
-    use bevy_hecs::World;
+    use bevy_hecs::{Entity, Mutated, Or, With, Without, World};
     use std::any::TypeId;
 
     struct A;
@@ -120,7 +222,7 @@ mod tests {
         let e3 = world.spawn((A, B, C));
 
         let mut access = ArchetypeAccess::default();
-        access.set_access_for_query::<(&A,)>(&world);
+        access.set_access_for_query::<(&A,), ()>(&world);
 
         let e1_archetype = world.get_entity_location(e1).unwrap().archetype as usize;
         let e2_archetype = world.get_entity_location(e2).unwrap().archetype as usize;
@@ -131,10 +233,24 @@ mod tests {
         assert!(access.immutable.contains(e3_archetype));
 
         let mut access = ArchetypeAccess::default();
-        access.set_access_for_query::<(&A, &B)>(&world);
+        access.set_access_for_query::<(&A, &B), ()>(&world);
+
+        assert!(access.immutable.contains(e1_archetype) == false);
+        assert!(access.immutable.contains(e2_archetype));
+        assert!(access.immutable.contains(e3_archetype));
+
+        let mut access = ArchetypeAccess::default();
+        access.set_access_for_query::<&A, (With<B>, Without<C>)>(&world);
 
         assert!(access.immutable.contains(e1_archetype) == false);
         assert!(access.immutable.contains(e2_archetype));
+        assert!(access.immutable.contains(e3_archetype) == false);
+
+        let mut access = ArchetypeAccess::default();
+        access.set_access_for_query::<Entity, Or<(Mutated<C>, Without<B>)>>(&world);
+
+        assert!(access.immutable.contains(e1_archetype) == false);
+        assert!(access.immutable.contains(e2_archetype) == false);
         assert!(access.immutable.contains(e3_archetype));
     }
 
@@ -0,0 +1,102 @@
+use crate::{
+    resource::Resources,
+    system::{ArchetypeAccess, System, SystemId, SystemOrdering, ThreadLocalExecution, TypeAccess},
+};
+use bevy_hecs::World;
+use std::borrow::Cow;
+
+/// A [System] that runs two systems in sequence, passing the output of the first as the input of
+/// the second
+///
+/// Created with [IntoChainSystem::chain]. Its access is the union of the access of both systems.
+pub struct ChainSystem<In, Mid, Out> {
+    a: Box<dyn System<In, Mid>>,
+    b: Box<dyn System<Mid, Out>>,
+    name: Cow<'static, str>,
+    id: SystemId,
+    archetype_access: ArchetypeAccess,
+    resource_access: TypeAccess,
+    ordering: SystemOrdering,
+}
+
+impl<In, Mid, Out> System<In, Out> for ChainSystem<In, Mid, Out> {
+    fn name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+
+    fn id(&self) -> SystemId {
+        self.id
+    }
+
+    fn update_archetype_access(&mut self, world: &World) {
+        self.a.update_archetype_access(world);
+        self.b.update_archetype_access(world);
+
+        self.archetype_access.clear();
+        self.archetype_access.union(self.a.archetype_access());
+        self.archetype_access.union(self.b.archetype_access());
+    }
+
+    fn archetype_access(&self) -> &ArchetypeAccess {
+        &self.archetype_access
+    }
+
+    fn resource_access(&self) -> &TypeAccess {
+        &self.resource_access
+    }
+
+    fn thread_local_execution(&self) -> ThreadLocalExecution {
+        if self.a.thread_local_execution() == ThreadLocalExecution::Immediate
+            || self.b.thread_local_execution() == ThreadLocalExecution::Immediate
+        {
+            ThreadLocalExecution::Immediate
+        } else {
+            ThreadLocalExecution::NextFlush
+        }
+    }
+
+    fn run(&mut self, input: In, world: &World, resources: &Resources) -> Out {
+        let output = self.a.run(input, world, resources);
+        self.b.run(output, world, resources)
+    }
+
+    fn run_thread_local(&mut self, world: &mut World, resources: &mut Resources) {
+        self.a.run_thread_local(world, resources);
+        self.b.run_thread_local(world, resources);
+    }
+
+    fn initialize(&mut self, resources: &mut Resources) {
+        self.a.initialize(resources);
+        self.b.initialize(resources);
+    }
+
+    fn ordering(&self) -> &SystemOrdering {
+        &self.ordering
+    }
+
+    fn ordering_mut(&mut self) -> &mut SystemOrdering {
+        &mut self.ordering
+    }
+}
+
+/// Chains a [System] to another system that takes its output as an input
+pub trait IntoChainSystem<In, Mid> {
+    /// Runs `system` after this system, passing it this system's output
+    fn chain<Out: 'static>(self, system: Box<dyn System<Mid, Out>>) -> Box<dyn System<In, Out>>;
+}
+
+impl<In: 'static, Mid: 'static> IntoChainSystem<In, Mid> for Box<dyn System<In, Mid>> {
+    fn chain<Out: 'static>(self, system: Box<dyn System<Mid, Out>>) -> Box<dyn System<In, Out>> {
+        let mut resource_access = self.resource_access().clone();
+        resource_access.union(system.resource_access());
+        Box::new(ChainSystem {
+            name: format!("Chain({}, {})", self.name(), system.name()).into(),
+            id: SystemId::new(),
+            archetype_access: ArchetypeAccess::default(),
+            resource_access,
+            ordering: SystemOrdering::default(),
+            a: self,
+            b: system,
+        })
+    }
+}
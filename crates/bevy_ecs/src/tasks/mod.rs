@@ -0,0 +1,7 @@
+mod task;
+mod task_pool;
+mod usages;
+
+pub use task::*;
+pub use task_pool::*;
+pub use usages::*;
@@ -0,0 +1,59 @@
+use futures_lite::future;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A future spawned onto a [TaskPool](super::TaskPool)
+///
+/// Dropping a task cancels it. Use [Task::detach] to let it run to completion in the background
+/// instead. Systems can check whether a task has finished without blocking using [Task::poll].
+#[derive(Debug)]
+pub struct Task<T>(Option<async_executor::Task<T>>);
+
+impl<T> Task<T> {
+    pub(crate) fn new(task: async_executor::Task<T>) -> Self {
+        Self(Some(task))
+    }
+
+    /// Returns the output of the task if it has finished, without blocking
+    ///
+    /// The output is only returned once. Later calls return `None`.
+    pub fn poll(&mut self) -> Option<T> {
+        let task = self.0.as_mut()?;
+        let output = future::block_on(future::poll_once(task))?;
+        self.0 = None;
+        Some(output)
+    }
+
+    /// Lets the task keep running after this handle is dropped
+    pub fn detach(mut self) {
+        if let Some(task) = self.0.take() {
+            task.detach();
+        }
+    }
+
+    /// Cancels the task and waits for it to stop. Returns its output if it finished first.
+    pub async fn cancel(mut self) -> Option<T> {
+        self.0.take()?.cancel().await
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let task = self
+            .0
+            .as_mut()
+            .expect("Task polled after its output was taken");
+        match Pin::new(task).poll(cx) {
+            Poll::Ready(output) => {
+                self.0 = None;
+                Poll::Ready(output)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use super::Task;
+use futures_lite::future;
+use std::{
+    future::Future,
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+/// Configures the threads of a [TaskPool], using the builder pattern
+#[derive(Debug, Default, Clone)]
+pub struct TaskPoolBuilder {
+    /// If some value, the pool uses this many threads. Otherwise it uses one per logical core.
+    num_threads: Option<usize>,
+    /// If some value, the threads are named after it
+    thread_name: Option<String>,
+}
+
+impl TaskPoolBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    pub fn thread_name(mut self, thread_name: impl Into<String>) -> Self {
+        self.thread_name = Some(thread_name.into());
+        self
+    }
+
+    pub fn build(self) -> TaskPool {
+        let num_threads = self.num_threads.unwrap_or_else(num_cpus::get).max(1);
+        let executor = Arc::new(async_executor::Executor::new());
+        let (shutdown_sender, shutdown_receiver) = async_channel::unbounded::<()>();
+
+        let threads = (0..num_threads)
+            .map(|index| {
+                let executor = executor.clone();
+                let shutdown_receiver = shutdown_receiver.clone();
+                let mut thread_builder = thread::Builder::new();
+                if let Some(thread_name) = &self.thread_name {
+                    thread_builder = thread_builder.name(format!("{} ({})", thread_name, index));
+                }
+                thread_builder
+                    .spawn(move || {
+                        // runs tasks until the pool is dropped and the channel closes
+                        let _ = future::block_on(executor.run(shutdown_receiver.recv()));
+                    })
+                    .expect("failed to spawn task pool thread")
+            })
+            .collect();
+
+        TaskPool {
+            executor,
+            inner: Arc::new(TaskPoolInner {
+                threads,
+                shutdown_sender,
+            }),
+        }
+    }
+}
+
+/// A pool of threads that run futures
+///
+/// Cloning a pool is cheap and refers to the same threads. The threads stop once every clone has
+/// been dropped, cancelling the tasks that haven't finished.
+#[derive(Debug, Clone)]
+pub struct TaskPool {
+    executor: Arc<async_executor::Executor<'static>>,
+    inner: Arc<TaskPoolInner>,
+}
+
+#[derive(Debug)]
+struct TaskPoolInner {
+    threads: Vec<JoinHandle<()>>,
+    shutdown_sender: async_channel::Sender<()>,
+}
+
+impl Drop for TaskPoolInner {
+    fn drop(&mut self) {
+        self.shutdown_sender.close();
+        let current_thread = thread::current().id();
+        for thread in self.threads.drain(..) {
+            // a task dropped the last clone of its own pool. its thread can't be joined from itself, so it is detached
+            // and exits once the task returns.
+            if thread.thread().id() == current_thread {
+                continue;
+            }
+            thread.join().expect("task pool thread panicked");
+        }
+    }
+}
+
+impl TaskPool {
+    /// A pool with one thread per logical core
+    pub fn new() -> Self {
+        TaskPoolBuilder::new().build()
+    }
+
+    pub fn thread_num(&self) -> usize {
+        self.inner.threads.len()
+    }
+
+    /// Runs `future` on the pool's threads. The returned [Task] can be polled from systems, or
+    /// awaited.
+    pub fn spawn<T>(&self, future: impl Future<Output = T> + Send + 'static) -> Task<T>
+    where
+        T: Send + 'static,
+    {
+        Task::new(self.executor.spawn(future))
+    }
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,177 @@
+use super::{TaskPool, TaskPoolBuilder};
+use crate::resource::Resources;
+use std::ops::Deref;
+
+/// A [TaskPool] for work that must be finished within the current frame, such as work systems
+/// split up and wait on
+#[derive(Debug, Clone)]
+pub struct ComputeTaskPool(pub TaskPool);
+
+/// A [TaskPool] for CPU-heavy work that may span several frames, such as pathfinding or
+/// procedural generation
+#[derive(Debug, Clone)]
+pub struct AsyncComputeTaskPool(pub TaskPool);
+
+/// A [TaskPool] for work that mostly waits on IO, such as reading files or network requests
+#[derive(Debug, Clone)]
+pub struct IoTaskPool(pub TaskPool);
+
+macro_rules! impl_task_pool_deref {
+    ($pool: ident) => {
+        impl Deref for $pool {
+            type Target = TaskPool;
+
+            fn deref(&self) -> &TaskPool {
+                &self.0
+            }
+        }
+    };
+}
+
+impl_task_pool_deref!(ComputeTaskPool);
+impl_task_pool_deref!(AsyncComputeTaskPool);
+impl_task_pool_deref!(IoTaskPool);
+
+/// This can be added as an app resource to control the number of threads of the task pools the
+/// app creates. By default, a quarter of the logical cores (at least one) go to each of
+/// [IoTaskPool] and [AsyncComputeTaskPool], and the rest go to [ComputeTaskPool].
+///
+/// Apps create their task pools before building their first plugin, so this must be added before
+/// any plugins.
+#[derive(Debug, Default, Clone)]
+pub struct TaskPoolOptions {
+    /// If some value, [ComputeTaskPool] uses this many threads
+    pub compute_threads: Option<usize>,
+    /// If some value, [AsyncComputeTaskPool] uses this many threads
+    pub async_compute_threads: Option<usize>,
+    /// If some value, [IoTaskPool] uses this many threads
+    pub io_threads: Option<usize>,
+}
+
+impl TaskPoolOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts each task pool that isn't in `resources` yet
+    pub fn create_default_pools(&self, resources: &mut Resources) {
+        let cores = num_cpus::get();
+        let io_threads = self.io_threads.unwrap_or_else(|| (cores / 4).max(1));
+        let async_compute_threads = self
+            .async_compute_threads
+            .unwrap_or_else(|| (cores / 4).max(1));
+        let compute_threads = self.compute_threads.unwrap_or_else(|| {
+            cores
+                .saturating_sub(io_threads + async_compute_threads)
+                .max(1)
+        });
+
+        if !resources.contains::<IoTaskPool>() {
+            resources.insert(IoTaskPool(
+                TaskPoolBuilder::new()
+                    .num_threads(io_threads)
+                    .thread_name("IO Task Pool")
+                    .build(),
+            ));
+        }
+        if !resources.contains::<AsyncComputeTaskPool>() {
+            resources.insert(AsyncComputeTaskPool(
+                TaskPoolBuilder::new()
+                    .num_threads(async_compute_threads)
+                    .thread_name("Async Compute Task Pool")
+                    .build(),
+            ));
+        }
+        if !resources.contains::<ComputeTaskPool>() {
+            resources.insert(ComputeTaskPool(
+                TaskPoolBuilder::new()
+                    .num_threads(compute_threads)
+                    .thread_name("Compute Task Pool")
+                    .build(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncComputeTaskPool, TaskPoolOptions};
+    use crate::tasks::TaskPoolBuilder;
+    use crate::{
+        resource::{Local, Res, ResMut, Resources},
+        schedule::Schedule,
+        system::IntoQuerySystem,
+        tasks::Task,
+        World,
+    };
+    use std::{sync::mpsc, thread, time::Duration};
+
+    #[test]
+    fn drop_pool_from_its_own_thread() {
+        let pool = TaskPoolBuilder::new().num_threads(2).build();
+        let (start_sender, start_receiver) = async_channel::bounded::<()>(1);
+        let (done_sender, done_receiver) = mpsc::channel();
+        let task_pool = pool.clone();
+        pool.spawn(async move {
+            start_receiver.recv().await.unwrap();
+            drop(task_pool);
+            done_sender.send(()).unwrap();
+        })
+        .detach();
+
+        drop(pool);
+        start_sender.try_send(()).unwrap();
+        done_receiver
+            .recv_timeout(Duration::from_secs(10))
+            .expect("the task that dropped the pool finishes");
+    }
+
+    #[test]
+    fn poll_task_across_frames() {
+        fn spawn_and_poll(
+            pool: Res<AsyncComputeTaskPool>,
+            mut task: Local<Option<Task<u32>>>,
+            mut results: ResMut<Vec<u32>>,
+        ) {
+            if results.is_empty() && task.is_none() {
+                *task = Some(pool.spawn(async { (1..=10).sum() }));
+            }
+            if let Some(result) = task.as_mut().and_then(|task| task.poll()) {
+                results.push(result);
+                *task = None;
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<u32>::new());
+        TaskPoolOptions {
+            async_compute_threads: Some(1),
+            ..Default::default()
+        }
+        .create_default_pools(&mut resources);
+        assert_eq!(
+            resources
+                .get::<AsyncComputeTaskPool>()
+                .unwrap()
+                .thread_num(),
+            1
+        );
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", spawn_and_poll.system());
+
+        schedule.initialize(&mut resources);
+        for _ in 0..1000 {
+            schedule.run(&mut world, &mut resources);
+            if !resources.get::<Vec<u32>>().unwrap().is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        schedule.run(&mut world, &mut resources);
+
+        assert_eq!(*resources.get::<Vec<u32>>().unwrap(), vec![55]);
+    }
+}
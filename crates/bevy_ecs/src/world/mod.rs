@@ -1,3 +1,5 @@
 mod world_builder;
+mod world_hash;
 
 pub use world_builder::*;
+pub use world_hash::*;
@@ -0,0 +1,118 @@
+use bevy_hecs::{Component, Entity, World};
+use std::hash::{Hash, Hasher};
+
+/// The component types included in a [World] hash
+///
+/// Comparing the hashes of two worlds that were updated the same way verifies that they ran
+/// deterministically. Hashes use a fixed hash function, so they are comparable across builds and
+/// Rust releases as long as the registered components hash the same way and the platforms share
+/// endianness and pointer width.
+#[derive(Default)]
+pub struct ComponentHashRegistry {
+    hashers: Vec<fn(&World, Entity, &mut WorldHasher)>,
+}
+
+impl ComponentHashRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes `T` components in hashes
+    pub fn register<T: Component + Hash>(&mut self) -> &mut Self {
+        self.hashers.push(hash_component::<T>);
+        self
+    }
+
+    /// Hashes the registered components of every entity in `world`
+    ///
+    /// Entities are hashed in order of their ids, so the hash does not depend on how they are
+    /// stored.
+    pub fn hash_world(&self, world: &World) -> u64 {
+        let mut entities = world.iter().map(|(entity, _)| entity).collect::<Vec<_>>();
+        entities.sort_by_key(|entity| entity.id());
+
+        let mut hasher = WorldHasher::default();
+        for entity in entities {
+            entity.hash(&mut hasher);
+            for (index, hash_component) in self.hashers.iter().enumerate() {
+                index.hash(&mut hasher);
+                hash_component(world, entity, &mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+fn hash_component<T: Component + Hash>(world: &World, entity: Entity, hasher: &mut WorldHasher) {
+    world.get::<T>(entity).ok().as_deref().hash(hasher);
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output does not change between Rust releases
+struct WorldHasher(u64);
+
+impl Default for WorldHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for WorldHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentHashRegistry, WorldHasher};
+    use bevy_hecs::World;
+    use std::hash::{Hash, Hasher};
+
+    #[test]
+    fn hash_world() {
+        let mut registry = ComponentHashRegistry::new();
+        registry.register::<u32>();
+
+        let mut world = World::new();
+        let a = world.spawn((1u32, 1.0f32));
+        world.spawn((2u32,));
+        let hash = registry.hash_world(&world);
+
+        // unregistered components and storage don't change the hash
+        *world.get_mut::<f32>(a).unwrap() = 2.0;
+        world.remove_one::<f32>(a).unwrap();
+        assert_eq!(registry.hash_world(&world), hash);
+
+        *world.get_mut::<u32>(a).unwrap() = 3;
+        assert_ne!(registry.hash_world(&world), hash);
+    }
+
+    #[test]
+    fn hash_world_in_id_order() {
+        let mut registry = ComponentHashRegistry::new();
+        registry.register::<u32>();
+
+        // a's id is reused with a newer generation, so a sorts before b by id but after it by generation
+        let mut world = World::new();
+        let a = world.spawn((1u32,));
+        let b = world.spawn((2u32,));
+        world.despawn(a).unwrap();
+        let a = world.spawn((3u32,));
+        assert!(a.id() < b.id() && a.generation() > b.generation());
+
+        let mut hasher = WorldHasher::default();
+        for (entity, value) in [(a, 3u32), (b, 2u32)].iter() {
+            entity.hash(&mut hasher);
+            0usize.hash(&mut hasher);
+            Some(value).hash(&mut hasher);
+        }
+        assert_eq!(registry.hash_world(&world), hasher.finish());
+    }
+}
@@ -13,4 +13,8 @@ impl Plugin for GltfPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset_loader::<Mesh, GltfLoader>();
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_render::RenderPlugin>();
+    }
 }
@@ -37,4 +37,8 @@ impl Plugin for PbrPlugin {
         let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
         add_pbr_graph(&mut render_graph, resources);
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_render::RenderPlugin>();
+    }
 }
@@ -15,7 +15,7 @@ use bevy_ecs::{
     TypeAccess, UnsafeClone,
 };
 use bevy_property::Properties;
-use std::{any::TypeId, ops::Range, sync::Arc};
+use std::{ops::Range, sync::Arc};
 use thiserror::Error;
 
 /// A queued command for the renderer
@@ -175,23 +175,50 @@ impl<'a> FetchResource<'a> for FetchDrawContext {
         resources.release::<SharedBuffers>();
     }
 
-    unsafe fn get(resources: &'a Resources, _system_id: Option<SystemId>) -> Self::Item {
+    unsafe fn get(
+        resources: &'a Resources,
+        _system_id: Option<SystemId>,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Self::Item {
+        let (pipelines, pipelines_ticks) = resources
+            .get_unsafe_ref_with_ticks::<Assets<PipelineDescriptor>>(ResourceIndex::Global);
+        let (shaders, shaders_ticks) =
+            resources.get_unsafe_ref_with_ticks::<Assets<Shader>>(ResourceIndex::Global);
+        let (pipeline_compiler, pipeline_compiler_ticks) =
+            resources.get_unsafe_ref_with_ticks::<PipelineCompiler>(ResourceIndex::Global);
+        let (render_resource_context, render_resource_context_ticks) = resources
+            .get_unsafe_ref_with_ticks::<Box<dyn RenderResourceContext>>(ResourceIndex::Global);
+        let (vertex_buffer_descriptors, vertex_buffer_descriptors_ticks) =
+            resources.get_unsafe_ref_with_ticks::<VertexBufferDescriptors>(ResourceIndex::Global);
+        let (shared_buffers, shared_buffers_ticks) =
+            resources.get_unsafe_ref_with_ticks::<SharedBuffers>(ResourceIndex::Global);
         DrawContext {
-            pipelines: ResMut::new(
-                resources.get_unsafe_ref::<Assets<PipelineDescriptor>>(ResourceIndex::Global),
-            ),
-            shaders: ResMut::new(resources.get_unsafe_ref::<Assets<Shader>>(ResourceIndex::Global)),
+            pipelines: ResMut::new(pipelines, pipelines_ticks, last_change_tick, change_tick),
+            shaders: ResMut::new(shaders, shaders_ticks, last_change_tick, change_tick),
             pipeline_compiler: ResMut::new(
-                resources.get_unsafe_ref::<PipelineCompiler>(ResourceIndex::Global),
+                pipeline_compiler,
+                pipeline_compiler_ticks,
+                last_change_tick,
+                change_tick,
             ),
             render_resource_context: Res::new(
-                resources.get_unsafe_ref::<Box<dyn RenderResourceContext>>(ResourceIndex::Global),
+                render_resource_context,
+                render_resource_context_ticks,
+                last_change_tick,
+                change_tick,
             ),
             vertex_buffer_descriptors: Res::new(
-                resources.get_unsafe_ref::<VertexBufferDescriptors>(ResourceIndex::Global),
+                vertex_buffer_descriptors,
+                vertex_buffer_descriptors_ticks,
+                last_change_tick,
+                change_tick,
             ),
             shared_buffers: Res::new(
-                resources.get_unsafe_ref::<SharedBuffers>(ResourceIndex::Global),
+                shared_buffers,
+                shared_buffers_ticks,
+                last_change_tick,
+                change_tick,
             ),
             current_pipeline: None,
         }
@@ -199,18 +226,12 @@ impl<'a> FetchResource<'a> for FetchDrawContext {
 
     fn access() -> TypeAccess {
         let mut access = TypeAccess::default();
-        access
-            .mutable
-            .insert(TypeId::of::<Assets<PipelineDescriptor>>());
-        access.mutable.insert(TypeId::of::<Assets<Shader>>());
-        access.mutable.insert(TypeId::of::<PipelineCompiler>());
-        access
-            .immutable
-            .insert(TypeId::of::<Box<dyn RenderResourceContext>>());
-        access
-            .immutable
-            .insert(TypeId::of::<VertexBufferDescriptors>());
-        access.immutable.insert(TypeId::of::<SharedBuffers>());
+        access.add_write::<Assets<PipelineDescriptor>>();
+        access.add_write::<Assets<Shader>>();
+        access.add_write::<PipelineCompiler>();
+        access.add_read::<Box<dyn RenderResourceContext>>();
+        access.add_read::<VertexBufferDescriptors>();
+        access.add_read::<SharedBuffers>();
         access
     }
 }
@@ -176,4 +176,11 @@ impl Plugin for RenderPlugin {
             }
         }
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies
+            .plugin::<bevy_type_registry::TypeRegistryPlugin>()
+            .plugin::<bevy_window::WindowPlugin>()
+            .plugin::<bevy_asset::AssetPlugin>();
+    }
 }
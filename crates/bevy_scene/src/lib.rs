@@ -28,4 +28,10 @@ impl Plugin for ScenePlugin {
             .add_stage_after(stage::EVENT_UPDATE, SCENE_STAGE)
             .add_system_to_stage(SCENE_STAGE, scene_spawner_system.thread_local_system());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies
+            .plugin::<bevy_type_registry::TypeRegistryPlugin>()
+            .plugin::<bevy_asset::AssetPlugin>();
+    }
 }
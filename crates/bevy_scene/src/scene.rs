@@ -1,6 +1,6 @@
 use crate::serde::SceneSerializer;
 use anyhow::Result;
-use bevy_ecs::World;
+use bevy_ecs::{ComponentId, World};
 use bevy_property::{DynamicProperties, PropertyTypeRegistry};
 use bevy_type_registry::ComponentRegistry;
 use serde::Serialize;
@@ -23,12 +23,16 @@ impl Scene {
             for (index, entity) in archetype.iter_entities().enumerate() {
                 if index == entities.len() {
                     entities.push(Entity {
-                        entity: *entity,
+                        entity: entity.id(),
                         components: Vec::new(),
                     })
                 }
                 for type_info in archetype.types() {
-                    if let Some(component_registration) = component_registry.get(&type_info.id()) {
+                    let registration = match type_info.id() {
+                        ComponentId::RustTypeId(id) => component_registry.get(&id),
+                        ComponentId::ExternalId(_) => None,
+                    };
+                    if let Some(component_registration) = registration {
                         let properties =
                             component_registration.get_component_properties(&archetype, index);
 
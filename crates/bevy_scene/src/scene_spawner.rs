@@ -98,7 +98,7 @@ impl SceneSpawner {
                 *instance_info
                     .entity_map
                     .entry(scene_entity.entity)
-                    .or_insert_with(|| bevy_ecs::Entity::new())
+                    .or_insert_with(|| world.reserve_entity())
             } else {
                 bevy_ecs::Entity::from_id(scene_entity.entity)
             };
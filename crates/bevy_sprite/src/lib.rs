@@ -64,4 +64,8 @@ impl Plugin for SpritePlugin {
         let mut color_materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
         color_materials.add_default(ColorMaterial::default());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_render::RenderPlugin>();
+    }
 }
@@ -26,4 +26,8 @@ impl Plugin for TextPlugin {
             .add_asset::<FontAtlasSet>()
             .add_asset_loader::<Font, FontLoader>();
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_asset::AssetPlugin>();
+    }
 }
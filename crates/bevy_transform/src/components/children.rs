@@ -1,4 +1,4 @@
-use bevy_ecs::Entity;
+use bevy_ecs::{Entity, EntityMap, MapEntities, MapEntitiesError};
 use bevy_property::Properties;
 use smallvec::SmallVec;
 use std::ops::{Deref, DerefMut};
@@ -12,6 +12,15 @@ impl Children {
     }
 }
 
+impl MapEntities for Children {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for entity in self.0.iter_mut() {
+            *entity = entity_map.get(*entity)?;
+        }
+        Ok(())
+    }
+}
+
 impl Deref for Children {
     type Target = SmallVec<[Entity; 8]>;
 
@@ -82,7 +82,11 @@ impl WorldWriter for PushChildren {
 
 impl<'a> ChildBuilder<'a> {
     pub fn spawn(&mut self, components: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
-        self.spawn_as_entity(Entity::new(), components)
+        self.commands.spawn(components);
+        self.push_children
+            .children
+            .push(self.commands.current_entity.unwrap());
+        self
     }
 
     pub fn spawn_as_entity(
@@ -224,6 +228,7 @@ mod tests {
         let mut world = World::default();
         let mut resources = Resources::default();
         let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
 
         let mut parent = None;
         let mut child1 = None;
@@ -271,6 +276,7 @@ mod tests {
         let mut world = World::default();
         let mut resources = Resources::default();
         let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
         let entities = world
             .spawn_batch(vec![(1,), (2,), (3,), (4,), (5,)])
             .collect::<Vec<Entity>>();
@@ -83,15 +83,20 @@ impl DespawnRecursiveExt for Commands {
 #[cfg(test)]
 mod tests {
     use super::DespawnRecursiveExt;
-    use crate::hierarchy::BuildChildren;
-    use bevy_ecs::{Commands, Entity, Resources, World};
+    use crate::{
+        components::{Children, Parent, PreviousParent},
+        hierarchy::BuildChildren,
+    };
+    use bevy_ecs::{Commands, Entity, MapEntitiesError, Resources, World};
+    use bevy_type_registry::ComponentRegistry;
 
     #[test]
     fn despawn_recursive() {
         let mut world = World::default();
         let mut resources = Resources::default();
         let mut command_buffer = Commands::default();
-        let parent_entity = Entity::new();
+        command_buffer.set_entity_reserver(world.get_entity_reserver());
+        let parent_entity = world.reserve_entity();
 
         command_buffer.spawn((0u32, 0u64)).with_children(|parent| {
             parent.spawn((0u32, 0u64));
@@ -122,4 +127,117 @@ mod tests {
         // the (0, 0) tuples remaining.
         assert_eq!(results, vec![(0u32, 0u64), (0u32, 0u64), (0u32, 0u64)]);
     }
+
+    #[test]
+    fn move_and_clone_hierarchy() {
+        let mut staging = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(staging.get_entity_reserver());
+        let parent_entity = staging.reserve_entity();
+        command_buffer
+            .spawn_as_entity(parent_entity, (1u32,))
+            .with_children(|parent| {
+                parent.spawn((2u32, PreviousParent(Some(parent_entity))));
+            });
+        command_buffer.apply(&mut staging, &mut resources);
+        let child_entity = staging.get::<Children>(parent_entity).unwrap()[0];
+
+        let mut registry = ComponentRegistry::default();
+        registry.register::<Children>();
+        registry.register::<Parent>();
+        registry.register_map_entities::<Children>();
+        registry.register_map_entities::<Parent>();
+        registry.register_map_entities::<PreviousParent>();
+
+        let mut world = World::default();
+        world.spawn((0u32,));
+        let entity_map = registry
+            .clone_entities(&staging, &mut world, &resources, |_| true)
+            .unwrap();
+        let parent = entity_map.get(parent_entity).unwrap();
+        let child = entity_map.get(child_entity).unwrap();
+        assert_eq!(world.get::<Children>(parent).unwrap()[0], child);
+        assert_eq!(world.get::<Parent>(child).unwrap().0, parent);
+        assert!(
+            world.get::<u32>(child).is_err(),
+            "unregistered components are not cloned"
+        );
+        assert!(staging.get::<Children>(parent_entity).is_ok());
+
+        let entity_map = registry
+            .move_entities(&mut staging, &mut world, |_| true)
+            .unwrap();
+        let parent = entity_map.get(parent_entity).unwrap();
+        let child = entity_map.get(child_entity).unwrap();
+        assert_eq!(world.get::<Children>(parent).unwrap()[0], child);
+        assert_eq!(world.get::<Parent>(child).unwrap().0, parent);
+        assert_eq!(
+            *world.get::<PreviousParent>(child).unwrap(),
+            PreviousParent(Some(parent))
+        );
+        assert_eq!(*world.get::<u32>(child).unwrap(), 2);
+        assert_eq!(staging.iter().count(), 0);
+    }
+
+    #[test]
+    fn move_and_clone_partial_hierarchy() {
+        let mut staging = World::default();
+        let mut resources = Resources::default();
+        let mut command_buffer = Commands::default();
+        command_buffer.set_entity_reserver(staging.get_entity_reserver());
+        let parent_entity = staging.reserve_entity();
+        command_buffer
+            .spawn_as_entity(parent_entity, (1u32,))
+            .with_children(|parent| {
+                parent.spawn((2u32,));
+            });
+        command_buffer.apply(&mut staging, &mut resources);
+        let child_entity = staging.get::<Children>(parent_entity).unwrap()[0];
+
+        let mut registry = ComponentRegistry::default();
+        registry.register::<Children>();
+        registry.register::<Parent>();
+        registry.register_map_entities::<Children>();
+        registry.register_map_entities::<Parent>();
+
+        let mut world = World::default();
+        world.spawn((0u32,));
+        assert_eq!(
+            registry
+                .clone_entities(&staging, &mut world, &resources, |entity| entity
+                    .get::<Parent>()
+                    .is_some())
+                .err(),
+            Some(MapEntitiesError::EntityNotFound(parent_entity))
+        );
+        assert_eq!(
+            registry
+                .move_entities(&mut staging, &mut world, |entity| entity
+                    .get::<Parent>()
+                    .is_some())
+                .err(),
+            Some(MapEntitiesError::EntityNotFound(parent_entity))
+        );
+        assert_eq!(
+            registry
+                .move_entities(&mut staging, &mut world, |entity| entity
+                    .get::<Children>()
+                    .is_some())
+                .err(),
+            Some(MapEntitiesError::EntityNotFound(child_entity))
+        );
+
+        // nothing was moved or cloned, and the hierarchy is intact
+        assert_eq!(world.iter().count(), 1);
+        assert_eq!(
+            staging.get::<Children>(parent_entity).unwrap()[0],
+            child_entity
+        );
+        assert_eq!(
+            staging.get::<Parent>(child_entity).unwrap().0,
+            parent_entity
+        );
+        assert_eq!(*staging.get::<u32>(child_entity).unwrap(), 2);
+    }
 }
@@ -8,7 +8,8 @@ pub struct WorldChildBuilder<'a, 'b> {
 
 impl<'a, 'b> WorldChildBuilder<'a, 'b> {
     pub fn spawn(&mut self, components: impl DynamicBundle + Send + Sync + 'static) -> &mut Self {
-        self.spawn_as_entity(Entity::new(), components)
+        let entity = self.world_builder.world.reserve_entity();
+        self.spawn_as_entity(entity, components)
     }
 
     pub fn spawn_as_entity(
@@ -12,7 +12,8 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_type_registry::RegisterType;
 use prelude::{
-    Children, LocalTransform, NonUniformScale, Parent, Rotation, Scale, Transform, Translation,
+    Children, LocalTransform, NonUniformScale, Parent, PreviousParent, Rotation, Scale, Transform,
+    Translation,
 };
 
 pub(crate) fn transform_systems() -> Vec<Box<dyn System>> {
@@ -39,8 +40,15 @@ impl Plugin for TransformPlugin {
             .register_component::<Rotation>()
             .register_component::<Scale>()
             .register_component::<NonUniformScale>()
+            .register_map_entities::<Children>()
+            .register_map_entities::<Parent>()
+            .register_map_entities::<PreviousParent>()
             // add transform systems to startup so the first update is "correct"
             .add_startup_systems(transform_systems())
             .add_systems_to_stage(stage::POST_UPDATE, transform_systems());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies.plugin::<bevy_type_registry::TypeRegistryPlugin>();
+    }
 }
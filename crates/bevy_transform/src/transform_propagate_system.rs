@@ -128,6 +128,7 @@ mod test {
 
         // Root entity
         let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
         let mut children = Vec::new();
         commands
             .spawn((Translation::new(1.0, 0.0, 0.0), Transform::identity()))
@@ -1,4 +1,7 @@
-use bevy_ecs::{Archetype, Component, Entity, FromResources, Resources, World};
+use bevy_ecs::{
+    Archetype, Component, Entity, EntityBuilder, EntityMap, EntityRef, FromResources, MapEntities,
+    MapEntitiesError, Resources, World,
+};
 use bevy_property::{Properties, Property, PropertyTypeRegistration, PropertyTypeRegistry};
 use std::{
     any::TypeId,
@@ -18,6 +21,7 @@ pub struct ComponentRegistry {
     pub short_names: HashMap<String, TypeId>,
     pub full_names: HashMap<String, TypeId>,
     pub ambigous_names: HashSet<String>,
+    map_entities_fns: HashMap<TypeId, fn(&mut World, &EntityMap) -> Result<(), MapEntitiesError>>,
 }
 
 impl ComponentRegistry {
@@ -39,6 +43,89 @@ impl ComponentRegistry {
         self.registrations.insert(registration.ty, registration);
     }
 
+    /// Map the entities referred to by `T` components when entities are moved or cloned between
+    /// worlds. `T` doesn't need to be registered with [ComponentRegistry::register]
+    pub fn register_map_entities<T>(&mut self)
+    where
+        T: Component + MapEntities,
+    {
+        self.map_entities_fns
+            .insert(TypeId::of::<T>(), World::map_entities::<T>);
+    }
+
+    /// Update the components registered with [ComponentRegistry::register_map_entities] of the
+    /// entities mapped to by `entity_map` to refer to the entities they are mapped to
+    pub fn map_entities(
+        &self,
+        world: &mut World,
+        entity_map: &EntityMap,
+    ) -> Result<(), MapEntitiesError> {
+        for map_entities in self.map_entities_fns.values() {
+            map_entities(world, entity_map)?;
+        }
+        Ok(())
+    }
+
+    /// Move the entities of `from` that `filter` accepts into `to` with all of their components,
+    /// returning the map from the moved entities to the entities they were spawned as
+    ///
+    /// Entities referred to by the moved components are mapped with [ComponentRegistry::map_entities].
+    /// The moved entities must only refer to each other, so a hierarchy is moved with all of its
+    /// parents and children. Otherwise nothing is moved and the unmapped entity is returned as an
+    /// error.
+    pub fn move_entities(
+        &self,
+        from: &mut World,
+        to: &mut World,
+        mut filter: impl FnMut(EntityRef<'_>) -> bool,
+    ) -> Result<EntityMap, MapEntitiesError> {
+        let mut moved = EntityMap::new();
+        for (entity, entity_ref) in from.iter() {
+            if filter(entity_ref) {
+                moved.insert(entity, entity);
+            }
+        }
+        // mapping every moved entity to itself leaves the components unchanged, but fails on the
+        // same references the actual mapping would
+        self.map_entities(from, &moved)?;
+
+        let entity_map =
+            to.move_entities_from(from, |entity_ref| moved.get(entity_ref.entity()).is_ok());
+        self.map_entities(to, &entity_map)?;
+        Ok(entity_map)
+    }
+
+    /// Like [ComponentRegistry::move_entities], but spawns copies of the entities in `to` and
+    /// leaves `from` unchanged. Only registered components are copied. The copies are despawned
+    /// again if their entities can not be mapped.
+    pub fn clone_entities(
+        &self,
+        from: &World,
+        to: &mut World,
+        resources: &Resources,
+        mut filter: impl FnMut(EntityRef<'_>) -> bool,
+    ) -> Result<EntityMap, MapEntitiesError> {
+        let entities = from
+            .iter()
+            .filter_map(|(entity, entity_ref)| Some(entity).filter(|_| filter(entity_ref)))
+            .collect::<Vec<_>>();
+        let mut entity_map = EntityMap::new();
+        let mut builder = EntityBuilder::new();
+        for entity in entities {
+            for registration in self.registrations.values() {
+                registration.clone_component(from, entity, resources, &mut builder);
+            }
+            entity_map.insert(entity, to.spawn(builder.build()));
+        }
+        if let Err(err) = self.map_entities(to, &entity_map) {
+            for entity in entity_map.values() {
+                let _ = to.despawn(entity);
+            }
+            return Err(err);
+        }
+        Ok(entity_map)
+    }
+
     pub fn get(&self, type_id: &TypeId) -> Option<&ComponentRegistration> {
         self.registrations.get(type_id)
     }
@@ -75,6 +162,7 @@ pub struct ComponentRegistration {
     component_add_fn: fn(&mut World, resources: &Resources, Entity, &dyn Property),
     component_apply_fn: fn(&mut World, Entity, &dyn Property),
     component_properties_fn: fn(&Archetype, usize) -> &dyn Properties,
+    component_clone_fn: fn(&World, Entity, &Resources, &mut EntityBuilder),
     pub short_name: String,
     pub long_name: &'static str,
 }
@@ -107,6 +195,16 @@ impl ComponentRegistration {
                     ptr.as_ref().unwrap()
                 }
             },
+            component_clone_fn: |from: &World,
+                                 source: Entity,
+                                 resources: &Resources,
+                                 builder: &mut EntityBuilder| {
+                if let Ok(component) = from.get::<T>(source) {
+                    let mut clone = T::from_resources(resources);
+                    clone.apply(&*component);
+                    builder.add(clone);
+                }
+            },
             short_name: PropertyTypeRegistration::get_short_name(std::any::type_name::<T>()),
             long_name: std::any::type_name::<T>(),
         }
@@ -131,6 +229,17 @@ impl ComponentRegistration {
         (self.component_apply_fn)(world, entity, property);
     }
 
+    /// Adds a copy of the `source` entity's component, if it has one, to `builder`
+    pub fn clone_component(
+        &self,
+        from: &World,
+        source: Entity,
+        resources: &Resources,
+        builder: &mut EntityBuilder,
+    ) {
+        (self.component_clone_fn)(from, source, resources, builder);
+    }
+
     pub fn get_component_properties<'a>(
         &self,
         archetype: &'a Archetype,
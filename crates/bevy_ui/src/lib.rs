@@ -52,4 +52,10 @@ impl Plugin for UiPlugin {
         let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
         render_graph.add_ui_graph(resources);
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies
+            .plugin::<bevy_render::RenderPlugin>()
+            .plugin::<bevy_text::TextPlugin>();
+    }
 }
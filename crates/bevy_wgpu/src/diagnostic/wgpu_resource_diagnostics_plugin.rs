@@ -12,6 +12,12 @@ impl Plugin for WgpuResourceDiagnosticsPlugin {
         app.add_startup_system(Self::setup_system.system())
             .add_system(Self::diagnostic_system.system());
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies
+            .plugin::<crate::WgpuPlugin>()
+            .resource::<Diagnostics>();
+    }
 }
 
 impl WgpuResourceDiagnosticsPlugin {
@@ -29,6 +29,12 @@ impl Plugin for WgpuPlugin {
             free_shared_buffers_system.system(),
         );
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies
+            .plugin::<bevy_window::WindowPlugin>()
+            .plugin::<bevy_render::RenderPlugin>();
+    }
 }
 
 pub fn wgpu_render_system(resources: &mut Resources) -> impl FnMut(&mut World, &mut Resources) {
@@ -31,6 +31,12 @@ impl Plugin for WinitPlugin {
             .init_resource::<WinitWindows>()
             .set_runner(winit_runner);
     }
+
+    fn dependencies(&self, dependencies: &mut PluginDependencies) {
+        dependencies
+            .plugin::<bevy_window::WindowPlugin>()
+            .plugin::<bevy_input::InputPlugin>();
+    }
 }
 
 pub fn winit_runner(mut app: App) {
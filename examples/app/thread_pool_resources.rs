@@ -1,10 +1,17 @@
-use bevy::{ecs::ParallelExecutorOptions, prelude::*};
+use bevy::{
+    ecs::{ParallelExecutorOptions, TaskPoolOptions},
+    prelude::*,
+};
 
 /// This example illustrates how to customize the thread pool used internally (e.g. to only use a
-/// certain number of threads).
+/// certain number of threads), and the task pools systems can spawn futures onto.
 fn main() {
     App::build()
         .add_resource(ParallelExecutorOptions::new().with_num_threads(Some(4)))
+        .add_resource(TaskPoolOptions {
+            io_threads: Some(1),
+            ..Default::default()
+        })
         .add_default_plugins()
         .run();
 }
@@ -0,0 +1,47 @@
+use crate::app::{AppBuilder, PluginGroup, PluginGroupBuilder};
+
+/// The plugins that make up the "full" engine, depending on the enabled features
+///
+/// Add them with `AppBuilder::add_plugins(DefaultPlugins)`, or with `AppBuilder::add_plugins_with`
+/// to disable or replace some of them.
+pub struct DefaultPlugins;
+
+impl PluginGroup for DefaultPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group.add(bevy_type_registry::TypeRegistryPlugin::default());
+        group.add(bevy_core::CorePlugin::default());
+        group.add(bevy_transform::TransformPlugin::default());
+        group.add(bevy_diagnostic::DiagnosticsPlugin::default());
+        group.add(bevy_input::InputPlugin::default());
+        group.add(bevy_window::WindowPlugin::default());
+        group.add(bevy_asset::AssetPlugin::default());
+        group.add(bevy_scene::ScenePlugin::default());
+        group.add(bevy_render::RenderPlugin::default());
+        group.add(bevy_sprite::SpritePlugin::default());
+        group.add(bevy_pbr::PbrPlugin::default());
+        group.add(bevy_text::TextPlugin::default());
+        group.add(bevy_ui::UiPlugin::default());
+
+        #[cfg(feature = "bevy_audio")]
+        group.add(bevy_audio::AudioPlugin::default());
+
+        #[cfg(feature = "bevy_gltf")]
+        group.add(bevy_gltf::GltfPlugin::default());
+
+        #[cfg(feature = "bevy_winit")]
+        group.add(bevy_winit::WinitPlugin::default());
+
+        #[cfg(feature = "bevy_wgpu")]
+        group.add(bevy_wgpu::WgpuPlugin::default());
+    }
+}
+
+pub trait AddDefaultPlugins {
+    fn add_default_plugins(&mut self) -> &mut Self;
+}
+
+impl AddDefaultPlugins for AppBuilder {
+    fn add_default_plugins(&mut self) -> &mut Self {
+        self.add_plugins(DefaultPlugins)
+    }
+}
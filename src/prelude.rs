@@ -2,7 +2,7 @@ pub use crate::{
     app::prelude::*, asset::prelude::*, core::prelude::*, ecs::prelude::*, input::prelude::*,
     math::prelude::*, pbr::prelude::*, property::prelude::*, render::prelude::*, scene::prelude::*,
     sprite::prelude::*, text::prelude::*, transform::prelude::*, type_registry::RegisterType,
-    ui::prelude::*, window::prelude::*, AddDefaultPlugins,
+    ui::prelude::*, window::prelude::*, AddDefaultPlugins, DefaultPlugins,
 };
 
 #[cfg(feature = "bevy_audio")]